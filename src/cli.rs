@@ -0,0 +1,73 @@
+//! Command line interface
+//!
+//! Without a subcommand WSLNetMan opens its window. Subcommands run a tool in the terminal
+//! instead, for scripts and for machines where the window is not at hand.
+
+use crate::packet::{start_test_listener, ListenerProtocol, ProbeSource};
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(name = "wslnetman", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Start a test listener, check that it can be reached from Windows and WSL, then stop it
+    Listen {
+        /// Where to listen: windows, wsl, wsl:<distro>, docker:<container> or podman:<container>
+        #[arg(long = "in", default_value = "windows")]
+        source: ProbeSource,
+        /// tcp (echo), udp (echo) or http
+        #[arg(long, default_value = "tcp")]
+        protocol: ListenerProtocol,
+        /// Local address to listen on
+        #[arg(long, default_value = "0.0.0.0")]
+        address: String,
+        #[arg(long)]
+        port: u16,
+        /// Keep listening after the checks, printing what arrives, until interrupted
+        #[arg(long)]
+        keep: bool,
+    },
+}
+
+/// Run a subcommand, returning whether it succeeded
+pub fn run(command: Command) -> Result<bool, Box<dyn std::error::Error>> {
+    match command {
+        Command::Listen {
+            source,
+            protocol,
+            address,
+            port,
+            keep,
+        } => {
+            let listener = start_test_listener(&source, protocol, &address, port, |event| {
+                println!("  {event}")
+            })?;
+            println!("{protocol} listener on {address} port {port} in {source}");
+
+            let mut reachable = true;
+            for check in listener.check_all() {
+                println!(
+                    "{} -> {}: {} ({})",
+                    check.source,
+                    check.target,
+                    check.verdict(),
+                    check.detail
+                );
+                reachable &= check.reachable;
+            }
+
+            if keep {
+                println!("Listening until interrupted");
+                loop {
+                    std::thread::park();
+                }
+            }
+            Ok(reachable)
+        }
+    }
+}
@@ -0,0 +1,546 @@
+//! User-space TCP port forwarder
+//!
+//! An alternative to `netsh interface portproxy` and the WSL localhost relay: WSLNetMan listens
+//! on chosen Windows addresses and ports itself and relays every connection to a WSL
+//! distribution or a container. Unlike portproxy it needs no administrator rights, and it looks
+//! the WSL address up again while running, so its rules keep working after WSL restarts with a
+//! new address.
+//!
+//! Rules are stored in `forwarder.json` next to the probe history and the enabled ones start
+//! with WSLNetMan. Connections are only relayed while WSLNetMan runs.
+
+use crate::network::ContainerRuntime;
+use crate::packet::ProbeSource;
+use crate::storage::{data_dir, load_json, write_json};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Handle;
+use tokio::task::{JoinHandle, JoinSet};
+
+/// How often the target address is looked up again, to follow WSL address changes
+const RESOLVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Time allowed to connect to the target
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A Windows address and port relayed to a port of a WSL distribution or a container
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForwardRule {
+    pub id: u64,
+    /// Windows address to listen on, e.g. "127.0.0.1", or "0.0.0.0" to accept LAN clients
+    pub listen_address: String,
+    pub listen_port: u16,
+    /// Where to relay to, in the form of [`ProbeSource::spec`], e.g. "wsl" or "docker:web"
+    pub target: String,
+    pub target_port: u16,
+    pub enabled: bool,
+}
+
+impl ForwardRule {
+    /// Address and port the rule listens on
+    pub fn listen(&self) -> String {
+        if self.listen_address.contains(':') {
+            format!("[{}]:{}", self.listen_address, self.listen_port)
+        } else {
+            format!("{}:{}", self.listen_address, self.listen_port)
+        }
+    }
+
+    /// Target the rule relays to
+    pub fn target_source(&self) -> Result<ProbeSource, String> {
+        self.target.parse()
+    }
+}
+
+impl std::fmt::Display for ForwardRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.target_source() {
+            Ok(target) => write!(f, "{} -> {target} port {}", self.listen(), self.target_port),
+            Err(_) => write!(
+                f,
+                "{} -> {} port {}",
+                self.listen(),
+                self.target,
+                self.target_port
+            ),
+        }
+    }
+}
+
+/// The rules as stored on disk
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredRules {
+    rules: Vec<ForwardRule>,
+}
+
+/// Live counters of a rule, shared with its connections
+#[derive(Debug, Default)]
+struct ForwardStats {
+    active: AtomicUsize,
+    connections: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    target_address: Mutex<Option<SocketAddr>>,
+    error: Mutex<Option<String>>,
+}
+
+impl ForwardStats {
+    fn target_address(&self) -> Option<SocketAddr> {
+        self.target_address.lock().ok().and_then(|address| *address)
+    }
+
+    fn set_target_address(&self, address: SocketAddr) {
+        if let Ok(mut current) = self.target_address.lock() {
+            *current = Some(address);
+        }
+    }
+
+    fn set_error(&self, error: Option<String>) {
+        if let Ok(mut current) = self.error.lock() {
+            *current = error;
+        }
+    }
+}
+
+/// A rule with its live counters
+#[derive(Debug, Clone)]
+pub struct ForwardStatus {
+    pub rule: ForwardRule,
+    /// Whether the rule is listening
+    pub running: bool,
+    /// Address connections are currently relayed to, once looked up
+    pub target_address: Option<SocketAddr>,
+    /// Why the rule could not start, or the last failure to reach the target
+    pub error: Option<String>,
+    /// Connections being relayed now
+    pub active: usize,
+    /// Connections accepted since the rule started
+    pub connections: u64,
+    /// Bytes relayed from clients to the target
+    pub bytes_sent: u64,
+    /// Bytes relayed from the target back to clients
+    pub bytes_received: u64,
+}
+
+/// The forwarding rules and the listeners of the enabled ones
+pub struct Forwarder {
+    runtime: Handle,
+    rules: Vec<ForwardRule>,
+    stats: HashMap<u64, Arc<ForwardStats>>,
+    tasks: HashMap<u64, JoinHandle<()>>,
+}
+
+impl Forwarder {
+    /// Load the stored rules and start the enabled ones
+    ///
+    /// Must be called within the tokio runtime, which runs the listeners and connections.
+    /// Rules that cannot start show why in their status.
+    pub fn load() -> Self {
+        let stored: StoredRules = load_json(&rules_path());
+        let mut forwarder = Forwarder {
+            runtime: Handle::current(),
+            rules: stored.rules,
+            stats: HashMap::new(),
+            tasks: HashMap::new(),
+        };
+        for rule in forwarder.rules.clone() {
+            if rule.enabled {
+                if let Err(e) = forwarder.start(&rule) {
+                    eprintln!("Error starting port forwarding {rule}: {e}");
+                }
+            }
+        }
+        forwarder
+    }
+
+    /// Write the rules to disk
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(
+            &rules_path(),
+            &StoredRules {
+                rules: self.rules.clone(),
+            },
+        )
+    }
+
+    /// Add a rule and start it
+    pub fn add(
+        &mut self,
+        listen_address: &str,
+        listen_port: u16,
+        target: &ProbeSource,
+        target_port: u16,
+    ) -> Result<ForwardRule, Box<dyn std::error::Error>> {
+        if !target.is_linux() {
+            return Err("Forward to a WSL distribution or a container".into());
+        }
+        let listen_address = listen_address
+            .trim()
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        listen_address
+            .parse::<IpAddr>()
+            .map_err(|e| format!("Invalid listen address {listen_address}: {e}"))?;
+        if let Some(existing) = self
+            .rules
+            .iter()
+            .find(|rule| rule.listen_address == listen_address && rule.listen_port == listen_port)
+        {
+            return Err(format!("{} is already forwarded: {existing}", existing.listen()).into());
+        }
+
+        let rule = ForwardRule {
+            id: self.rules.iter().map(|rule| rule.id).max().unwrap_or(0) + 1,
+            listen_address: listen_address.to_string(),
+            listen_port,
+            target: target.spec(),
+            target_port,
+            enabled: true,
+        };
+        if let Err(e) = self.start(&rule) {
+            self.stats.remove(&rule.id);
+            return Err(e);
+        }
+        self.rules.push(rule.clone());
+        self.save()?;
+        Ok(rule)
+    }
+
+    /// Start or stop a rule, keeping it
+    pub fn set_enabled(
+        &mut self,
+        id: u64,
+        enabled: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let rule = self
+            .rules
+            .iter_mut()
+            .find(|rule| rule.id == id)
+            .ok_or_else(|| format!("No forwarding rule {id}"))?;
+        rule.enabled = enabled;
+        let rule = rule.clone();
+        self.save()?;
+        if enabled {
+            self.start(&rule)
+        } else {
+            self.stop(id);
+            Ok(())
+        }
+    }
+
+    /// Stop a rule and drop it
+    pub fn remove(&mut self, id: u64) -> Result<(), Box<dyn std::error::Error>> {
+        self.stop(id);
+        self.stats.remove(&id);
+        self.rules.retain(|rule| rule.id != id);
+        self.save()
+    }
+
+    /// The rules with their live counters, in the order they were added
+    pub fn status(&self) -> Vec<ForwardStatus> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                let stats = self.stats.get(&rule.id).cloned().unwrap_or_default();
+                ForwardStatus {
+                    rule: rule.clone(),
+                    running: self.tasks.contains_key(&rule.id),
+                    target_address: stats.target_address(),
+                    error: stats.error.lock().ok().and_then(|error| error.clone()),
+                    active: stats.active.load(Ordering::SeqCst),
+                    connections: stats.connections.load(Ordering::SeqCst),
+                    bytes_sent: stats.bytes_sent.load(Ordering::SeqCst),
+                    bytes_received: stats.bytes_received.load(Ordering::SeqCst),
+                }
+            })
+            .collect()
+    }
+
+    /// Listen for a rule, with fresh counters
+    fn start(&mut self, rule: &ForwardRule) -> Result<(), Box<dyn std::error::Error>> {
+        self.stop(rule.id);
+        let stats = Arc::new(ForwardStats::default());
+        self.stats.insert(rule.id, stats.clone());
+
+        let listener = rule
+            .target_source()
+            .map_err(Box::<dyn std::error::Error>::from)
+            .and_then(|target| Ok((target, self.bind(rule)?)));
+        match listener {
+            Ok((target, listener)) => {
+                let task = self
+                    .runtime
+                    .spawn(forward(listener, target, rule.target_port, stats));
+                self.tasks.insert(rule.id, task);
+                Ok(())
+            }
+            Err(e) => {
+                stats.set_error(Some(e.to_string()));
+                Err(e)
+            }
+        }
+    }
+
+    /// Stop listening for a rule, closing its connections
+    fn stop(&mut self, id: u64) {
+        if let Some(task) = self.tasks.remove(&id) {
+            task.abort();
+        }
+        if let Some(stats) = self.stats.get(&id) {
+            stats.set_error(None);
+        }
+    }
+
+    fn bind(&self, rule: &ForwardRule) -> Result<TcpListener, Box<dyn std::error::Error>> {
+        let ip: IpAddr = rule
+            .listen_address
+            .parse()
+            .map_err(|e| format!("Invalid listen address {}: {e}", rule.listen_address))?;
+        // Only warn and let the bind decide, as the ranges are cached and change at any time
+        let warning = crate::network::excluded_port_warning("TCP", Some(ip), rule.listen_port);
+        if let Some(warning) = &warning {
+            eprintln!("Port forwarding {rule}: {warning}");
+        }
+        let address = SocketAddr::new(ip, rule.listen_port);
+        let listener = std::net::TcpListener::bind(address).map_err(|e| match &warning {
+            Some(warning) => format!("Could not listen on {}: {e}. {warning}", rule.listen()),
+            None => format!("Could not listen on {}: {e}", rule.listen()),
+        })?;
+        listener.set_nonblocking(true)?;
+        let _runtime = self.runtime.enter();
+        Ok(TcpListener::from_std(listener)?)
+    }
+}
+
+impl Drop for Forwarder {
+    fn drop(&mut self) {
+        for (_, task) in self.tasks.drain() {
+            task.abort();
+        }
+    }
+}
+
+fn rules_path() -> PathBuf {
+    data_dir().join("forwarder.json")
+}
+
+/// Find the address to relay to in the target's current network
+///
+/// WSL is reached at its `eth0` address. A container is reached at the WSL address on the host
+/// port it publishes `port` on, or at its own address when it does not publish it, which needs
+/// a route to the container network.
+pub fn resolve_target(
+    target: &ProbeSource,
+    port: u16,
+) -> Result<SocketAddr, Box<dyn std::error::Error>> {
+    match target {
+        ProbeSource::Windows => Err("Forward to a WSL distribution or a container".into()),
+        ProbeSource::Wsl { .. } => Ok(SocketAddr::new(wsl_address(target)?, port)),
+        ProbeSource::Container { runtime, name } => {
+            let wsl = ProbeSource::Wsl { distro: None };
+            let cli = match runtime {
+                ContainerRuntime::Docker => "docker",
+                ContainerRuntime::Podman => "podman",
+            };
+
+            // Format: one binding per line, e.g. "0.0.0.0:8080" and "[::]:8080"
+            let output = wsl
+                .command(
+                    cli,
+                    &["port".to_string(), name.clone(), format!("{port}/tcp")],
+                )
+                .output()?;
+            let published = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .find_map(|line| line.trim().rsplit_once(':')?.1.parse::<u16>().ok());
+            if let (true, Some(host_port)) = (output.status.success(), published) {
+                return Ok(SocketAddr::new(wsl_address(&wsl)?, host_port));
+            }
+
+            let output = wsl
+                .command(
+                    cli,
+                    &[
+                        "inspect".to_string(),
+                        "-f".to_string(),
+                        "{{range .NetworkSettings.Networks}}{{.IPAddress}} {{end}}".to_string(),
+                        name.clone(),
+                    ],
+                )
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("{cli} inspect {name} failed: {}", stderr.trim()).into());
+            }
+            String::from_utf8_lossy(&output.stdout)
+                .split_whitespace()
+                .find_map(|address| address.parse::<IpAddr>().ok())
+                .map(|address| SocketAddr::new(address, port))
+                .ok_or_else(|| {
+                    format!("{target} has no address and does not publish port {port}").into()
+                })
+        }
+    }
+}
+
+/// Get the IPv4 address of `eth0` in a WSL distribution
+fn wsl_address(source: &ProbeSource) -> Result<IpAddr, Box<dyn std::error::Error>> {
+    let output = source
+        .command(
+            "ip",
+            &["-o", "-4", "addr", "show", "eth0"].map(String::from),
+        )
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ip addr in {source} failed: {}", stderr.trim()).into());
+    }
+
+    // Format: "2: eth0    inet 172.20.10.5/20 brd 172.20.15.255 scope global eth0"
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .skip_while(|part| *part != "inet")
+        .nth(1)
+        .and_then(|address| address.split('/').next()?.parse().ok())
+        .ok_or_else(|| format!("{source} has no IPv4 address on eth0").into())
+}
+
+/// Look the target address up without blocking the runtime, remembering it
+async fn resolve(target: &ProbeSource, port: u16, stats: &ForwardStats) -> Option<SocketAddr> {
+    let lookup = target.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        resolve_target(&lookup, port).map_err(|e| e.to_string())
+    })
+    .await;
+    match result {
+        Ok(Ok(address)) => {
+            stats.set_target_address(address);
+            Some(address)
+        }
+        Ok(Err(e)) => {
+            stats.set_error(Some(format!("Could not find {target}: {e}")));
+            None
+        }
+        Err(_) => None,
+    }
+}
+
+/// Accept connections for a rule and relay them, until the task is aborted
+async fn forward(listener: TcpListener, target: ProbeSource, port: u16, stats: Arc<ForwardStats>) {
+    // Dropping the sets when the task is aborted stops the lookups and closes the connections
+    let mut lookups = JoinSet::new();
+    let mut connections = JoinSet::new();
+
+    let (lookup_target, lookup_stats) = (target.clone(), stats.clone());
+    lookups.spawn(async move {
+        let mut interval = tokio::time::interval(RESOLVE_INTERVAL);
+        loop {
+            interval.tick().await;
+            resolve(&lookup_target, port, &lookup_stats).await;
+        }
+    });
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((client, _)) => {
+                    connections.spawn(relay(client, target.clone(), port, stats.clone()));
+                }
+                Err(e) => {
+                    // E.g. too many open files; keep listening
+                    stats.set_error(Some(format!("Could not accept a connection: {e}")));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+/// Relay one client connection to the target
+async fn relay(client: TcpStream, target: ProbeSource, port: u16, stats: Arc<ForwardStats>) {
+    stats.connections.fetch_add(1, Ordering::SeqCst);
+    stats.active.fetch_add(1, Ordering::SeqCst);
+
+    match connect(&target, port, &stats).await {
+        Ok(server) => {
+            let _ = client.set_nodelay(true);
+            let _ = server.set_nodelay(true);
+            let (client_read, client_write) = client.into_split();
+            let (server_read, server_write) = server.into_split();
+            tokio::join!(
+                pipe(client_read, server_write, &stats.bytes_sent),
+                pipe(server_read, client_write, &stats.bytes_received),
+            );
+        }
+        Err(e) => stats.set_error(Some(e)),
+    }
+
+    stats.active.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Connect to the target, looking its address up again when it stopped answering
+async fn connect(
+    target: &ProbeSource,
+    port: u16,
+    stats: &ForwardStats,
+) -> Result<TcpStream, String> {
+    let known = stats.target_address();
+    let address = match known {
+        Some(address) => address,
+        None => resolve(target, port, stats)
+            .await
+            .ok_or_else(|| format!("Could not find {target}"))?,
+    };
+    let mut result = try_connect(address).await;
+
+    // WSL may have restarted with a new address since the last lookup
+    if result.is_err() && known.is_some() {
+        if let Some(new_address) = resolve(target, port, stats).await {
+            if new_address != address {
+                result = try_connect(new_address).await;
+            }
+        }
+    }
+
+    match result {
+        Ok(server) => {
+            stats.set_error(None);
+            Ok(server)
+        }
+        Err(e) => Err(format!("Could not connect to {target}: {e}")),
+    }
+}
+
+async fn try_connect(address: SocketAddr) -> Result<TcpStream, String> {
+    match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(address)).await {
+        Ok(Ok(stream)) => Ok(stream),
+        Ok(Err(e)) => Err(format!("{address}: {e}")),
+        Err(_) => Err(format!("{address}: timed out")),
+    }
+}
+
+/// Copy one direction of a connection, counting the bytes, then pass the close on
+async fn pipe(mut from: OwnedReadHalf, mut to: OwnedWriteHalf, counter: &AtomicU64) {
+    let mut buffer = vec![0u8; 16 * 1024];
+    loop {
+        match from.read(&mut buffer).await {
+            Ok(0) | Err(_) => break,
+            Ok(read) => {
+                if to.write_all(&buffer[..read]).await.is_err() {
+                    break;
+                }
+                counter.fetch_add(read as u64, Ordering::SeqCst);
+            }
+        }
+    }
+    let _ = to.shutdown().await;
+}
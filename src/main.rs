@@ -1,17 +1,40 @@
 slint::include_modules!();
 use arboard::Clipboard;
+use clap::Parser;
 use slint::Model;
 
 // Import packet sending modules
+mod cli;
+mod forwarder;
 mod packet;
-use packet::{send_http_request, send_ping};
+mod storage;
+use forwarder::Forwarder;
+use packet::{
+    inspect_tls, resolve_dns, send_http_request, send_ping, send_socket_probe, start_test_listener,
+    test_throughput, trace_route, DnsResolver, DnsResult, EchoReply, HttpAuth, HttpBody,
+    HttpRequest, HttpRequestResult, ListenerProtocol, PingOptions, PingResult, PingStatistics,
+    ProbeCollection, ProbeCollections, ProbeHistory, ProbeParameters, ProbeSource, RecordType,
+    SocketProbeOptions, SocketProbeResult, TestListener, ThroughputOptions, ThroughputResult,
+    TracerouteHop, TracerouteOptions,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 // Import network modules
 mod network;
-use network::docker::get_containers_for_network;
+use network::docker::daemon::{self, DockerDaemonKind};
+use network::docker::events::{self, DockerEventKind};
+use network::docker::get_docker_network;
+use network::process::{self, KillMode, KillTarget, ProcessDetails};
+use network::search::{
+    search_ports, PortFilters, PortQuery, PortRecord, PortSnapshots, PortSortColumn,
+};
 use network::{
-    filter_ports_for_interface, get_active_ports, get_all_docker_networks,
-    get_all_network_interfaces,
+    filter_ports_for_compose_project, filter_ports_for_interface, get_active_ports,
+    get_all_container_networks, get_all_network_interfaces, get_compose_projects,
+    get_containers_for_network, podman, ContainerRuntime,
 };
 
 /// Refresh all data for the application
@@ -54,6 +77,7 @@ fn refresh_all_data(app_weak: &slint::Weak<MainWindow>) {
                 .collect();
 
             app.set_network_interfaces(slint_interfaces.as_slice().into());
+            set_interface_options(&app);
         }
         Err(e) => {
             eprintln!("Error getting network interfaces: {e}");
@@ -62,55 +86,2043 @@ fn refresh_all_data(app_weak: &slint::Weak<MainWindow>) {
 
     // Refresh ports
     match get_active_ports() {
-        Ok(ports) => {
+        Ok(ports) => set_ports(&app, ports),
+        Err(e) => {
+            eprintln!("Error getting active ports: {e}");
+        }
+    }
+
+    // Refresh container networks of all runtimes
+    match get_all_container_networks() {
+        Ok(networks) => {
             // Convert to Slint-compatible format
-            let slint_ports: Vec<slint_generatedMainWindow::PortInfo> = ports
+            let slint_networks: Vec<slint_generatedMainWindow::DockerNetwork> = networks
                 .into_iter()
-                .map(|port| slint_generatedMainWindow::PortInfo {
-                    process_id: port.process_id.into(),
-                    process_name: port.process_name.into(),
-                    protocol: port.protocol.into(),
-                    port: port.port.into(),
-                    direction: port.direction.into(),
-                    network: port.network.into(),
-                    environment: match port.environment {
-                        network::NetworkEnvironment::Windows => "Windows".into(),
-                        network::NetworkEnvironment::Wsl => "WSL".into(),
-                    },
+                .map(|network| slint_generatedMainWindow::DockerNetwork {
+                    name: network.name.into(),
+                    driver: network.driver.into(),
+                    scope: network.scope.into(),
+                    subnet: network.subnet.into(),
+                    compose_project: network.compose_project.into(),
+                    runtime: network.runtime.to_string().into(),
+                    daemon: network.daemon.into(),
+                    context: network.context.into(),
                 })
                 .collect();
 
-            app.set_ports(slint_ports.as_slice().into());
+            app.set_docker_networks(slint_networks.as_slice().into());
         }
         Err(e) => {
-            eprintln!("Error getting active ports: {e}");
+            eprintln!("Error getting container networks: {e}");
+        }
+    }
+
+    // Refresh the detected Docker daemons
+    refresh_docker_daemons(&app);
+
+    // Refresh Podman details and pods
+    if podman::is_available() {
+        refresh_podman(&app);
+    }
+
+    // Refresh the environments probes can be sent from
+    set_probe_sources(&app);
+
+    // Refresh Docker Compose projects
+    match get_compose_projects() {
+        Ok(projects) => set_compose_projects(&app, projects),
+        Err(e) => {
+            eprintln!("Error getting Docker Compose projects: {e}");
+        }
+    }
+}
+
+/// Replace the probe sources offered by the packet sender, keeping the current selection
+fn set_probe_sources(app: &MainWindow) {
+    let previously_selected: Vec<slint::SharedString> = app
+        .get_probe_sources()
+        .iter()
+        .filter(|source| source.selected)
+        .map(|source| source.label)
+        .collect();
+
+    let mut sources = vec![ProbeSource::Windows];
+    match network::wsl::get_distributions() {
+        Ok(distros) => sources.extend(
+            distros
+                .into_iter()
+                // Docker Desktop's own distros cannot run probes
+                .filter(|distro| !distro.starts_with(daemon::DESKTOP_DISTRO))
+                .map(|distro| ProbeSource::Wsl {
+                    distro: Some(distro),
+                }),
+        ),
+        Err(e) => eprintln!("Error getting WSL distributions: {e}"),
+    }
+    sources.extend(network::get_all_containers().into_iter().map(|container| {
+        ProbeSource::Container {
+            runtime: container.runtime,
+            name: container.name,
+        }
+    }));
+
+    // Convert to Slint-compatible format
+    let slint_sources: Vec<slint_generatedMainWindow::ProbeSourceOption> = sources
+        .into_iter()
+        .map(|source| {
+            let label: slint::SharedString = source.to_string().into();
+            let (kind, name, runtime) = match source {
+                ProbeSource::Windows => ("Windows", String::new(), String::new()),
+                ProbeSource::Wsl { distro } => ("WSL", distro.unwrap_or_default(), String::new()),
+                ProbeSource::Container { runtime, name } => {
+                    ("Container", name, runtime.to_string())
+                }
+            };
+            slint_generatedMainWindow::ProbeSourceOption {
+                // Probe from Windows until the user picks something else
+                selected: if previously_selected.is_empty() {
+                    kind == "Windows"
+                } else {
+                    previously_selected.contains(&label)
+                },
+                label,
+                kind: kind.into(),
+                name: name.into(),
+                runtime: runtime.into(),
+            }
+        })
+        .collect();
+
+    let labels: Vec<slint::SharedString> = slint_sources
+        .iter()
+        .map(|source| source.label.clone())
+        .collect();
+    // The throughput test's server has to be one of the sources
+    if !labels.contains(&app.get_throughput_server()) {
+        app.set_throughput_server(ProbeSource::Windows.to_string().into());
+    }
+    app.set_probe_source_labels(labels.as_slice().into());
+    // Ports can only be forwarded to Linux sources
+    let target_labels: Vec<slint::SharedString> = slint_sources
+        .iter()
+        .filter(|source| source.kind != "Windows")
+        .map(|source| source.label.clone())
+        .collect();
+    app.set_forward_target_labels(target_labels.as_slice().into());
+    app.set_probe_sources(slint_sources.as_slice().into());
+}
+
+/// Choice of the source interface picker that does not bind probes
+const ANY_INTERFACE: &str = "Any interface";
+
+/// An address probes can be bound to, as offered by the source interface picker
+struct InterfaceBinding {
+    label: String,
+    environment: network::NetworkEnvironment,
+    address: String,
+}
+
+/// List the addresses of the known network interfaces that probes can be bound to
+fn interface_bindings(app: &MainWindow) -> Vec<InterfaceBinding> {
+    app.get_network_interfaces()
+        .iter()
+        .filter(|interface| interface.is_up)
+        .flat_map(|interface| {
+            let environment = match interface.environment.as_str() {
+                "Windows" => network::NetworkEnvironment::Windows,
+                _ => network::NetworkEnvironment::Wsl,
+            };
+            interface
+                .ipv4_addresses
+                .iter()
+                .chain(interface.ipv6_addresses.iter())
+                .map(|address| InterfaceBinding {
+                    label: format!(
+                        "{} · {} ({})",
+                        interface.name, address, interface.environment
+                    ),
+                    environment: environment.clone(),
+                    address: address.to_string(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Replace the choices of the source interface picker, keeping the current choice if it still exists
+fn set_interface_options(app: &MainWindow) {
+    let bindings = interface_bindings(app);
+
+    let mut options: Vec<slint::SharedString> = vec![ANY_INTERFACE.into()];
+    options.extend(bindings.iter().map(|binding| binding.label.clone().into()));
+
+    let selected = app.get_selected_interface();
+    if !options.contains(&selected) {
+        app.set_selected_interface(ANY_INTERFACE.into());
+    }
+    app.set_interface_options(options.as_slice().into());
+}
+
+/// Convert a probe source from the Slint-compatible format
+fn from_slint_probe_source(source: &slint_generatedMainWindow::ProbeSourceOption) -> ProbeSource {
+    match source.kind.as_str() {
+        "WSL" => ProbeSource::Wsl {
+            distro: Some(source.name.to_string()).filter(|distro| !distro.is_empty()),
+        },
+        "Container" => ProbeSource::Container {
+            runtime: source.runtime.parse().unwrap_or(ContainerRuntime::Docker),
+            name: source.name.to_string(),
+        },
+        _ => ProbeSource::Windows,
+    }
+}
+
+/// DNS record type and resolver from the packet sender form
+fn dns_options(app: &MainWindow) -> Result<(RecordType, DnsResolver), String> {
+    let record_type = app.get_dns_record_type().parse()?;
+    let resolver = match app.get_dns_resolver().as_str() {
+        "Custom" => app.get_dns_server().parse()?,
+        resolver => resolver.parse()?,
+    };
+    Ok((record_type, resolver))
+}
+
+/// Format a DNS result for the DNS response view
+fn format_dns_result(result: &DnsResult) -> String {
+    let answers = if result.answers.is_empty() {
+        "(no answers)".to_string()
+    } else {
+        result
+            .answers
+            .iter()
+            .map(|record| record.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    format!(
+        "{} {} via {}{}\nStatus: {}\nQuery Time: {:.1} ms\n\nAnswers:\n{}",
+        result.record_type,
+        result.name,
+        result.resolver,
+        result
+            .server
+            .as_ref()
+            .map(|server| format!(" ({server})"))
+            .unwrap_or_default(),
+        result.rcode,
+        result.latency,
+        answers
+    )
+}
+
+/// Socket probe options from the packet sender form
+fn socket_options(app: &MainWindow, protocol: &str) -> Result<SocketProbeOptions, String> {
+    let defaults = SocketProbeOptions::default();
+    let port = app
+        .get_socket_port()
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| format!("Invalid port: {}", app.get_socket_port()))?;
+    Ok(SocketProbeOptions {
+        protocol: protocol.parse()?,
+        port,
+        payload: packet::socket::parse_payload(&app.get_socket_payload(), app.get_socket_hex())?,
+        idle_timeout: app
+            .get_socket_wait()
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| *seconds > 0.0)
+            .map(std::time::Duration::from_secs_f64)
+            .unwrap_or(defaults.idle_timeout),
+        ..defaults
+    })
+}
+
+/// Format a socket probe result for the socket response view
+fn format_socket_result(result: &SocketProbeResult) -> String {
+    let optional_ms = |time: Option<f64>| {
+        time.map(|time| format!("{time:.1} ms"))
+            .unwrap_or_else(|| "n/a".to_string())
+    };
+    format!(
+        "{} {} ({} -> {})\nConnect: {}\nFirst byte: {}\nTotal: {:.1} ms\nSent: {} bytes\nReceived: {} bytes{}{}\n\nHex:\n{}\n\nText:\n{}",
+        result.protocol,
+        result.destination,
+        result.local_address.as_deref().unwrap_or("?"),
+        result.remote_address.as_deref().unwrap_or("?"),
+        optional_ms(result.connect_time),
+        optional_ms(result.first_byte_time),
+        result.total_time,
+        result.bytes_sent,
+        result.received.len(),
+        if result.closed_by_peer {
+            " (closed by peer)"
+        } else {
+            ""
+        },
+        result
+            .error
+            .as_ref()
+            .map(|error| format!("\nError: {error}"))
+            .unwrap_or_default(),
+        packet::socket::hex_dump(&result.received),
+        String::from_utf8_lossy(&result.received)
+    )
+}
+
+/// Server source and options of a throughput test from the packet sender form
+fn throughput_options(app: &MainWindow) -> Result<(ProbeSource, ThroughputOptions), String> {
+    let defaults = ThroughputOptions::default();
+    let server_label = app.get_throughput_server();
+    let server = app
+        .get_probe_sources()
+        .iter()
+        .find(|source| source.label == server_label)
+        .map(|source| from_slint_probe_source(&source))
+        .ok_or_else(|| format!("Unknown server source: {server_label}"))?;
+    let port = app
+        .get_throughput_port()
+        .trim()
+        .parse::<u16>()
+        .ok()
+        .filter(|port| *port > 0)
+        .ok_or_else(|| format!("Invalid port: {}", app.get_throughput_port()))?;
+    let duration = app
+        .get_throughput_duration()
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0 && *seconds <= 3600.0)
+        .ok_or_else(|| format!("Invalid duration: {}", app.get_throughput_duration()))?;
+    let bitrate = app
+        .get_throughput_bitrate()
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|megabits| *megabits > 0.0)
+        .ok_or_else(|| format!("Invalid bitrate: {}", app.get_throughput_bitrate()))?;
+    let options = ThroughputOptions {
+        protocol: app.get_throughput_protocol().parse()?,
+        port,
+        duration: std::time::Duration::from_secs_f64(duration),
+        bitrate: (bitrate * 1_000_000.0) as u64,
+        ..defaults
+    };
+    if options.protocol == packet::socket::SocketProtocol::Udp
+        && options.bitrate < options.min_bitrate()
+    {
+        return Err(format!(
+            "Invalid bitrate: {}, UDP needs at least {} Mbit/s to send one datagram per second",
+            app.get_throughput_bitrate(),
+            options.min_bitrate() as f64 / 1_000_000.0
+        ));
+    }
+    Ok((server, options))
+}
+
+/// Format bits per second with a readable unit
+fn format_bitrate(bits_per_second: f64) -> String {
+    if bits_per_second >= 1e9 {
+        format!("{:.2} Gbit/s", bits_per_second / 1e9)
+    } else if bits_per_second >= 1e6 {
+        format!("{:.1} Mbit/s", bits_per_second / 1e6)
+    } else {
+        format!("{:.0} kbit/s", bits_per_second / 1e3)
+    }
+}
+
+/// Format a throughput test result for the throughput view
+fn format_throughput_result(result: &ThroughputResult) -> String {
+    let mut text = format!(
+        "{} to {}\nThroughput: {}\nSent: {} bytes\nReceived: {} bytes in {:.2} s",
+        result.protocol,
+        result.destination,
+        format_bitrate(result.throughput),
+        result.bytes_sent,
+        result.bytes_received,
+        result.seconds
+    );
+    if let Some(retransmits) = result.retransmits {
+        text.push_str(&format!(
+            "\nRetransmits: {retransmits}{}",
+            if result.retransmits_system_wide {
+                " (whole system during the test)"
+            } else {
+                ""
+            }
+        ));
+    }
+    if let (Some(sent), Some(received)) = (result.packets_sent, result.packets_received) {
+        text.push_str(&format!(
+            "\nDatagrams: {received}/{sent} received, {} lost ({:.2}%), {} out of order\nJitter: {:.3} ms",
+            result.packets_lost.unwrap_or_default(),
+            result.loss().unwrap_or_default(),
+            result.out_of_order.unwrap_or_default(),
+            result.jitter.unwrap_or_default()
+        ));
+    }
+    text
+}
+
+/// Format a ping result for the ping response view
+fn format_ping_result(result: &PingResult) -> String {
+    format!(
+        "Destination: {}\nTransmitted: {}\nReceived: {}\nPacket Loss: {:.2}%\nMin Time: {:.2}ms\nAvg Time: {:.2}ms\nMax Time: {:.2}ms\nJitter: {:.2}ms",
+        result.destination,
+        result.transmitted,
+        result.received,
+        result.packet_loss,
+        result.min_time,
+        result.avg_time,
+        result.max_time,
+        result.jitter
+    )
+}
+
+/// Read the ping options from the packet sender, using the defaults for invalid input
+fn ping_options(app: &MainWindow) -> PingOptions {
+    let defaults = PingOptions::default();
+    PingOptions {
+        count: if app.get_ping_continuous() {
+            None
+        } else {
+            app.get_ping_count()
+                .trim()
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .or(defaults.count)
+        },
+        // Intervals below 200 ms need root for Linux ping
+        interval: app
+            .get_ping_interval()
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|seconds| *seconds >= 0.2)
+            .map(std::time::Duration::from_secs_f32)
+            .unwrap_or(defaults.interval),
+        payload_size: app
+            .get_ping_size()
+            .trim()
+            .parse()
+            .ok()
+            .filter(|size| *size <= 65500)
+            .unwrap_or(defaults.payload_size),
+        ttl: app
+            .get_ping_ttl()
+            .trim()
+            .parse()
+            .ok()
+            .filter(|ttl| (1..=255).contains(ttl)),
+        ..defaults
+    }
+}
+
+/// Comparison row for a ping that is still running
+fn ping_progress_row(
+    label: &str,
+    statistics: &PingStatistics,
+    reply: &EchoReply,
+) -> slint_generatedMainWindow::ProbeResult {
+    slint_generatedMainWindow::ProbeResult {
+        source: label.into(),
+        success: statistics.received > 0,
+        status: format!("{}/{} replies", statistics.received, statistics.transmitted).into(),
+        loss: format!("{:.0}%", statistics.packet_loss()).into(),
+        latency: format!(
+            "{:.1} / {:.1} / {:.1} ms",
+            statistics.min_time(),
+            statistics.avg_time(),
+            statistics.max_time()
+        )
+        .into(),
+        jitter: format!("{:.1} ms", statistics.jitter()).into(),
+        detail: reply.to_string().into(),
+    }
+}
+
+/// Read the traceroute options from the packet sender, using the defaults for invalid input
+fn traceroute_options(app: &MainWindow) -> TracerouteOptions {
+    let defaults = TracerouteOptions::default();
+    TracerouteOptions {
+        protocol: app
+            .get_trace_protocol()
+            .parse()
+            .unwrap_or(defaults.protocol),
+        max_hops: app
+            .get_trace_max_hops()
+            .trim()
+            .parse()
+            .ok()
+            .filter(|hops| (1..=64).contains(hops))
+            .unwrap_or(defaults.max_hops),
+        port: app.get_trace_port().trim().parse().ok(),
+        ..defaults
+    }
+}
+
+/// Split "name<separator>value" lines, skipping blank lines
+fn parse_pairs(text: &str, separator: char) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| match line.split_once(separator) {
+            Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+            None => (line.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// HTTP request described by the request builder, sent to `destination`
+///
+/// Destinations without a scheme are sent over plain HTTP.
+fn http_request(app: &MainWindow, destination: &str) -> HttpRequest {
+    let method = match app.get_http_method().as_str() {
+        "Custom" => app.get_http_custom_method().trim().to_uppercase(),
+        method => method.to_string(),
+    };
+    let url = if destination.contains("://") {
+        destination.to_string()
+    } else {
+        format!("http://{destination}")
+    };
+    let content_type = Some(app.get_http_content_type().trim().to_string())
+        .filter(|content_type| !content_type.is_empty());
+    let body = match app.get_http_body_type().as_str() {
+        "Raw" => HttpBody::Raw {
+            content_type,
+            text: app.get_http_body().to_string(),
+        },
+        "JSON" => HttpBody::Json(app.get_http_body().to_string()),
+        "Form" => HttpBody::Form(parse_pairs(&app.get_http_body(), '=')),
+        "File" => HttpBody::File {
+            content_type,
+            path: app.get_http_file_path().trim().into(),
+        },
+        _ => HttpBody::None,
+    };
+    let auth = match app.get_http_auth_type().as_str() {
+        "Basic" => HttpAuth::Basic {
+            username: app.get_http_username().to_string(),
+            password: app.get_http_password().to_string(),
+        },
+        "Bearer" => HttpAuth::Bearer(app.get_http_token().trim().to_string()),
+        _ => HttpAuth::None,
+    };
+    let defaults = HttpRequest::new(&method, &url);
+    HttpRequest {
+        query: parse_pairs(&app.get_http_query(), '='),
+        headers: parse_pairs(&app.get_http_headers(), ':'),
+        body,
+        auth,
+        max_redirects: if app.get_http_follow_redirects() {
+            app.get_http_max_redirects()
+                .trim()
+                .parse()
+                .unwrap_or(defaults.max_redirects)
+        } else {
+            0
+        },
+        ignore_tls_errors: app.get_http_ignore_tls_errors(),
+        windows_curl: app.get_http_windows_curl(),
+        ..defaults
+    }
+}
+
+/// Traceroute table row for a hop, labelled with its role in the local WSL setup
+fn traceroute_hop_row(
+    source_index: usize,
+    label: &str,
+    hop: &TracerouteHop,
+    landmarks: &network::PathLandmarks,
+) -> slint_generatedMainWindow::TracerouteHopRow {
+    slint_generatedMainWindow::TracerouteHopRow {
+        source_index: source_index as i32,
+        source: label.into(),
+        ttl: hop.ttl as i32,
+        address: hop.address.clone().into(),
+        hostname: hop.hostname.clone().into(),
+        rtts: hop
+            .rtts
+            .iter()
+            .map(|rtt| match rtt {
+                Some(rtt) => format!("{rtt:.1} ms"),
+                None => "*".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+            .into(),
+        roles: landmarks
+            .classify(&hop.address)
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>()
+            .join(" · ")
+            .into(),
+        note: hop.note.clone().into(),
+    }
+}
+
+/// Add a hop to the traceroute table, after the hops of the same and earlier sources
+fn add_traceroute_hop(app: &MainWindow, row: slint_generatedMainWindow::TracerouteHopRow) {
+    let hops = app.get_traceroute_hops();
+    let Some(hops) = hops
+        .as_any()
+        .downcast_ref::<slint::VecModel<slint_generatedMainWindow::TracerouteHopRow>>()
+    else {
+        return;
+    };
+    let position = hops
+        .iter()
+        .take_while(|hop| hop.source_index <= row.source_index)
+        .count();
+    hops.insert(position, row);
+}
+
+/// Number of most recent round trip times shown per source in the latency chart
+const LATENCY_WINDOW: usize = 60;
+
+/// Line colors of the latency chart, cycled through by source
+const LATENCY_COLORS: [(u8, u8, u8); 6] = [
+    (0x4e, 0x9a, 0xf1),
+    (0xf1, 0x8f, 0x3b),
+    (0x5c, 0xc8, 0x5c),
+    (0xe0, 0x5a, 0x5a),
+    (0xb0, 0x7c, 0xe8),
+    (0xd8, 0xc8, 0x4a),
+];
+
+/// Start one empty latency chart line per source
+fn reset_latency_series(app: &MainWindow, labels: &[String]) {
+    let series: Vec<slint_generatedMainWindow::LatencySeries> = labels
+        .iter()
+        .enumerate()
+        .map(|(index, label)| {
+            let (r, g, b) = LATENCY_COLORS[index % LATENCY_COLORS.len()];
+            slint_generatedMainWindow::LatencySeries {
+                source: label.into(),
+                color: slint::Color::from_rgb_u8(r, g, b),
+                samples: slint::ModelRc::new(slint::VecModel::<f32>::default()),
+                commands: "M 0 100".into(),
+            }
+        })
+        .collect();
+    app.set_latency_series(slint::ModelRc::new(slint::VecModel::from(series)));
+    app.set_max_latency(0.0);
+}
+
+/// Add a round trip time (`None` for a lost request) to a source's chart line
+///
+/// All lines share one scale, so every line is redrawn when a sample is added.
+fn record_latency(app: &MainWindow, index: usize, rtt: Option<f32>) {
+    let series = app.get_latency_series();
+    let Some(line) = series.row_data(index) else {
+        return;
+    };
+    let mut samples: Vec<f32> = line.samples.iter().collect();
+    samples.push(rtt.unwrap_or(-1.0));
+    if samples.len() > LATENCY_WINDOW {
+        samples.drain(..samples.len() - LATENCY_WINDOW);
+    }
+    series.set_row_data(
+        index,
+        slint_generatedMainWindow::LatencySeries {
+            samples: slint::ModelRc::new(slint::VecModel::from(samples)),
+            ..line
+        },
+    );
+
+    let max_latency = series
+        .iter()
+        .flat_map(|line| line.samples.iter().collect::<Vec<_>>())
+        .fold(0.0f32, f32::max);
+    app.set_max_latency(max_latency);
+
+    for row in 0..series.row_count() {
+        let Some(line) = series.row_data(row) else {
+            continue;
+        };
+        let samples: Vec<f32> = line.samples.iter().collect();
+        series.set_row_data(
+            row,
+            slint_generatedMainWindow::LatencySeries {
+                commands: latency_path(&samples, max_latency).into(),
+                ..line
+            },
+        );
+    }
+}
+
+/// Build the path commands of a chart line in a 100x100 viewbox
+///
+/// Lost requests break the line, so gaps in the chart show packet loss.
+fn latency_path(samples: &[f32], max_latency: f32) -> String {
+    let step = 100.0 / (LATENCY_WINDOW - 1) as f32;
+    let mut commands = String::new();
+    let mut drawing = false;
+    for (i, rtt) in samples.iter().enumerate() {
+        if *rtt < 0.0 {
+            drawing = false;
+            continue;
+        }
+        let x = i as f32 * step;
+        let y = if max_latency > 0.0 {
+            100.0 - rtt / max_latency * 95.0
+        } else {
+            100.0
+        };
+        let command = if drawing { 'L' } else { 'M' };
+        commands.push_str(&format!("{command} {x:.2} {y:.2} "));
+        drawing = true;
+    }
+    if commands.is_empty() {
+        // A path needs at least one command
+        commands.push_str("M 0 100");
+    }
+    commands
+}
+
+/// Identifies the latest packet sender run, so results of an older run are dropped
+static PROBE_RUN: AtomicUsize = AtomicUsize::new(0);
+
+/// Mark one source of a packet sender run as done, ending the run after the last one
+fn finish_probe(app: &MainWindow, run: usize, remaining: &AtomicUsize) {
+    if remaining.fetch_sub(1, Ordering::SeqCst) == 1 && PROBE_RUN.load(Ordering::SeqCst) == run {
+        app.set_probe_running(false);
+    }
+}
+
+/// Format an HTTP result for the HTTP response view
+fn format_http_result(result: &HttpRequestResult) -> String {
+    let proxy = match result.proxy_used {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "unknown",
+    };
+    let note = match &result.note {
+        Some(note) => format!("Note: {note}\n"),
+        None => String::new(),
+    };
+    format!(
+        "{} {}\nStatus Code: {}\nResponse Time: {:.2}ms\nTiming: {}\nRemote Address: {}\nHTTP Version: {}\nProxy: {}\nRedirects: {}\n{note}\nHeaders:\n{}\n\nBody:\n{}",
+        result.method,
+        result.url,
+        result.status_code,
+        result.response_time,
+        result.timing,
+        result.remote_address.as_deref().unwrap_or("unknown"),
+        result.http_version.as_deref().unwrap_or("unknown"),
+        proxy,
+        result.redirects,
+        result.response_headers,
+        result.response_body
+    )
+}
+
+/// Replace a row of the probe comparison table
+fn set_probe_result(
+    app: &MainWindow,
+    index: usize,
+    result: slint_generatedMainWindow::ProbeResult,
+) {
+    let results = app.get_probe_results();
+    if index < results.row_count() {
+        results.set_row_data(index, result);
+    }
+}
+
+/// Append a per-source section to a response view
+fn append_section(current: slint::SharedString, source: &str, text: &str) -> slint::SharedString {
+    let separator = if current.is_empty() { "" } else { "\n\n" };
+    format!("{current}{separator}=== {source} ===\n{text}").into()
+}
+
+/// Getter and setter of a probe type's response view, e.g. the ping response
+type ResponseView = (
+    fn(&MainWindow) -> slint::SharedString,
+    fn(&MainWindow, slint::SharedString),
+);
+
+/// Comparison row for a source whose probe failed
+fn probe_error_row(label: &str, error: String) -> slint_generatedMainWindow::ProbeResult {
+    slint_generatedMainWindow::ProbeResult {
+        source: label.into(),
+        success: false,
+        status: "Error".into(),
+        detail: error.into(),
+        ..Default::default()
+    }
+}
+
+/// Show the final result of a source: its comparison row, its sections of the type's response
+/// view (traceroutes have none besides the hop table) and of the raw output, then record it
+fn finish_probe_result(
+    app: &MainWindow,
+    index: usize,
+    row: slint_generatedMainWindow::ProbeResult,
+    view: Option<ResponseView>,
+    text: &str,
+    raw_output: &str,
+    parameters: &ProbeParameters,
+) {
+    let label = row.source.clone();
+    set_probe_result(app, index, row);
+    if let Some((get, set)) = view {
+        set(app, append_section(get(app), &label, text));
+    }
+    app.set_raw_output(append_section(app.get_raw_output(), &label, raw_output));
+    record_history(app, index, parameters, raw_output);
+}
+
+thread_local! {
+    /// Probe history and saved probes, only used from the UI thread
+    static PROBE_HISTORY: RefCell<ProbeHistory> = RefCell::new(ProbeHistory::load());
+    static PROBE_COLLECTIONS: RefCell<ProbeCollections> = RefCell::new(ProbeCollections::load());
+}
+
+/// Parameters of the packet sender form, to record or save the probe
+fn probe_parameters(app: &MainWindow) -> ProbeParameters {
+    let flag = |value: bool| value.to_string();
+    let packet_type = app.get_packet_type().to_string();
+    let options: Vec<(&str, String)> = match packet_type.as_str() {
+        "ping" => vec![
+            ("count", app.get_ping_count().into()),
+            ("continuous", flag(app.get_ping_continuous())),
+            ("interval", app.get_ping_interval().into()),
+            ("size", app.get_ping_size().into()),
+            ("ttl", app.get_ping_ttl().into()),
+        ],
+        "traceroute" => vec![
+            ("protocol", app.get_trace_protocol().into()),
+            ("max_hops", app.get_trace_max_hops().into()),
+            ("port", app.get_trace_port().into()),
+        ],
+        "DNS" => vec![
+            ("record_type", app.get_dns_record_type().into()),
+            ("resolver", app.get_dns_resolver().into()),
+            ("server", app.get_dns_server().into()),
+        ],
+        "TCP" | "UDP" => vec![
+            ("port", app.get_socket_port().into()),
+            ("payload", app.get_socket_payload().into()),
+            ("hex", flag(app.get_socket_hex())),
+            ("wait", app.get_socket_wait().into()),
+        ],
+        "Throughput" => vec![
+            ("protocol", app.get_throughput_protocol().into()),
+            ("server", app.get_throughput_server().into()),
+            ("port", app.get_throughput_port().into()),
+            ("duration", app.get_throughput_duration().into()),
+            ("bitrate", app.get_throughput_bitrate().into()),
+        ],
+        "HTTP over TCP" => vec![
+            ("method", app.get_http_method().into()),
+            ("custom_method", app.get_http_custom_method().into()),
+            ("query", app.get_http_query().into()),
+            ("headers", app.get_http_headers().into()),
+            ("body_type", app.get_http_body_type().into()),
+            ("body", app.get_http_body().into()),
+            ("content_type", app.get_http_content_type().into()),
+            ("file_path", app.get_http_file_path().into()),
+            ("auth_type", app.get_http_auth_type().into()),
+            ("username", app.get_http_username().into()),
+            ("password", app.get_http_password().into()),
+            ("token", app.get_http_token().into()),
+            ("follow_redirects", flag(app.get_http_follow_redirects())),
+            ("max_redirects", app.get_http_max_redirects().into()),
+            ("ignore_tls_errors", flag(app.get_http_ignore_tls_errors())),
+            ("windows_curl", flag(app.get_http_windows_curl())),
+        ],
+        _ => Vec::new(),
+    };
+    ProbeParameters {
+        packet_type,
+        destination: app.get_destination().to_string(),
+        sources: app
+            .get_probe_sources()
+            .iter()
+            .filter(|source| source.selected)
+            .map(|source| source.label.to_string())
+            .collect(),
+        interface: app.get_selected_interface().to_string(),
+        options: options
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect(),
+    }
+}
+
+/// Fill in the packet sender form from recorded or saved parameters
+///
+/// Sources and interfaces that no longer exist are skipped, and options that were not
+/// stored (like passwords) keep their current value.
+fn apply_probe_parameters(app: &MainWindow, parameters: &ProbeParameters) {
+    app.set_packet_type(parameters.packet_type.as_str().into());
+    app.set_destination(parameters.destination.as_str().into());
+    let interface: slint::SharedString = parameters.interface.as_str().into();
+    if app
+        .get_interface_options()
+        .iter()
+        .any(|option| option == interface)
+    {
+        app.set_selected_interface(interface);
+    } else {
+        app.set_selected_interface(ANY_INTERFACE.into());
+    }
+    let sources = app.get_probe_sources();
+    for index in 0..sources.row_count() {
+        if let Some(mut source) = sources.row_data(index) {
+            source.selected = parameters.sources.contains(&source.label.to_string());
+            sources.set_row_data(index, source);
+        }
+    }
+
+    for (key, value) in &parameters.options {
+        let text: slint::SharedString = value.as_str().into();
+        let flag = value == "true";
+        match (parameters.packet_type.as_str(), key.as_str()) {
+            ("ping", "count") => app.set_ping_count(text),
+            ("ping", "continuous") => app.set_ping_continuous(flag),
+            ("ping", "interval") => app.set_ping_interval(text),
+            ("ping", "size") => app.set_ping_size(text),
+            ("ping", "ttl") => app.set_ping_ttl(text),
+            ("traceroute", "protocol") => app.set_trace_protocol(text),
+            ("traceroute", "max_hops") => app.set_trace_max_hops(text),
+            ("traceroute", "port") => app.set_trace_port(text),
+            ("DNS", "record_type") => app.set_dns_record_type(text),
+            ("DNS", "resolver") => app.set_dns_resolver(text),
+            ("DNS", "server") => app.set_dns_server(text),
+            ("TCP" | "UDP", "port") => app.set_socket_port(text),
+            ("TCP" | "UDP", "payload") => app.set_socket_payload(text),
+            ("TCP" | "UDP", "hex") => app.set_socket_hex(flag),
+            ("TCP" | "UDP", "wait") => app.set_socket_wait(text),
+            ("Throughput", "protocol") => app.set_throughput_protocol(text),
+            ("Throughput", "server") => app.set_throughput_server(text),
+            ("Throughput", "port") => app.set_throughput_port(text),
+            ("Throughput", "duration") => app.set_throughput_duration(text),
+            ("Throughput", "bitrate") => app.set_throughput_bitrate(text),
+            ("HTTP over TCP", "method") => app.set_http_method(text),
+            ("HTTP over TCP", "custom_method") => app.set_http_custom_method(text),
+            ("HTTP over TCP", "query") => app.set_http_query(text),
+            ("HTTP over TCP", "headers") => app.set_http_headers(text),
+            ("HTTP over TCP", "body_type") => app.set_http_body_type(text),
+            ("HTTP over TCP", "body") => app.set_http_body(text),
+            ("HTTP over TCP", "content_type") => app.set_http_content_type(text),
+            ("HTTP over TCP", "file_path") => app.set_http_file_path(text),
+            ("HTTP over TCP", "auth_type") => app.set_http_auth_type(text),
+            ("HTTP over TCP", "username") => app.set_http_username(text),
+            ("HTTP over TCP", "follow_redirects") => app.set_http_follow_redirects(flag),
+            ("HTTP over TCP", "max_redirects") => app.set_http_max_redirects(text),
+            ("HTTP over TCP", "ignore_tls_errors") => app.set_http_ignore_tls_errors(flag),
+            ("HTTP over TCP", "windows_curl") => app.set_http_windows_curl(flag),
+            _ => {}
+        }
+    }
+}
+
+/// Record a source's result of a packet sender run, as shown in its comparison row
+fn record_history(app: &MainWindow, index: usize, parameters: &ProbeParameters, raw_output: &str) {
+    let Some(row) = app.get_probe_results().row_data(index) else {
+        return;
+    };
+    let summary = [
+        &row.status,
+        &row.loss,
+        &row.latency,
+        &row.jitter,
+        &row.detail,
+    ]
+    .into_iter()
+    .filter(|field| !field.is_empty())
+    .map(|field| field.as_str())
+    .collect::<Vec<_>>()
+    .join(" · ");
+    PROBE_HISTORY.with_borrow_mut(|history| {
+        history.record(&row.source, parameters, row.success, &summary, raw_output);
+        if let Err(e) = history.save() {
+            eprintln!("Error saving probe history: {e}");
+        }
+    });
+    refresh_history(app);
+}
+
+/// Show the history entries matching the search
+fn refresh_history(app: &MainWindow) {
+    let query = app.get_history_query();
+    let rows: Vec<slint_generatedMainWindow::HistoryRow> = PROBE_HISTORY.with_borrow(|history| {
+        history
+            .search(&query)
+            .into_iter()
+            .map(|entry| slint_generatedMainWindow::HistoryRow {
+                id: entry.id as i32,
+                time: storage::format_timestamp(entry.timestamp).into(),
+                source: entry.source.clone().into(),
+                probe: entry.parameters.to_string().into(),
+                success: entry.success,
+                summary: entry.summary.clone().into(),
+            })
+            .collect()
+    });
+    app.set_history_rows(rows.as_slice().into());
+}
+
+/// Show the saved probes and the names of their collections
+fn refresh_saved_probes(app: &MainWindow) {
+    let (rows, names): (
+        Vec<slint_generatedMainWindow::SavedProbeRow>,
+        Vec<slint::SharedString>,
+    ) = PROBE_COLLECTIONS.with_borrow(|collections| {
+        let rows = collections
+            .collections
+            .iter()
+            .flat_map(|collection| {
+                collection
+                    .probes
+                    .iter()
+                    .map(|probe| slint_generatedMainWindow::SavedProbeRow {
+                        collection: collection.name.clone().into(),
+                        name: probe.name.clone().into(),
+                        probe: probe.parameters.to_string().into(),
+                    })
+            })
+            .collect();
+        let names = collections
+            .collections
+            .iter()
+            .map(|collection| collection.name.clone().into())
+            .collect();
+        (rows, names)
+    });
+    app.set_saved_probe_rows(rows.as_slice().into());
+    app.set_collection_names(names.as_slice().into());
+}
+
+/// Change the saved probe collections, saving them and reporting the outcome
+fn update_collections(app: &MainWindow, update: impl FnOnce(&mut ProbeCollections) -> String) {
+    let status = PROBE_COLLECTIONS.with_borrow_mut(|collections| {
+        let status = update(collections);
+        match collections.save() {
+            Ok(()) => status,
+            Err(e) => format!("Error saving probe collections: {e}"),
+        }
+    });
+    app.set_history_status(status.into());
+    refresh_saved_probes(app);
+}
+
+thread_local! {
+    /// Running test listener, shared with the thread running its checks
+    static TEST_LISTENER: RefCell<Option<Arc<TestListener>>> = const { RefCell::new(None) };
+}
+
+/// Lines of the test listener's received view
+const MAX_LISTENER_EVENTS: usize = 200;
+
+/// Append a line to the test listener's received view, keeping the latest lines
+fn add_listener_event(app: &MainWindow, event: &str) {
+    let events = app.get_listener_events();
+    let mut lines: Vec<&str> = events.lines().collect();
+    lines.push(event);
+    let start = lines.len().saturating_sub(MAX_LISTENER_EVENTS);
+    app.set_listener_events(lines[start..].join("\n").into());
+}
+
+/// Run the reachability checks of the test listener on another thread
+fn check_listener(app: &MainWindow, listener: Arc<TestListener>) {
+    app.set_listener_checks(slint::ModelRc::new(slint::VecModel::<
+        slint_generatedMainWindow::ListenerCheckRow,
+    >::default()));
+    app.set_listener_status(
+        format!(
+            "{} listener on {} port {} in {}: checking…",
+            listener.protocol, listener.address, listener.port, listener.source
+        )
+        .into(),
+    );
+    let app_weak = app.as_weak();
+    std::thread::spawn(move || {
+        let checks = listener.check_all();
+        let _ = slint::invoke_from_event_loop(move || {
+            let Some(app) = app_weak.upgrade() else {
+                return;
+            };
+            let reachable = checks.iter().filter(|check| check.reachable).count();
+            let rows: Vec<slint_generatedMainWindow::ListenerCheckRow> = checks
+                .into_iter()
+                .map(|check| slint_generatedMainWindow::ListenerCheckRow {
+                    verdict: check.verdict().into(),
+                    source: check.source.into(),
+                    target: check.target.into(),
+                    reachable: check.reachable,
+                    detail: check.detail.into(),
+                })
+                .collect();
+            app.set_listener_status(
+                format!(
+                    "{} listener on {} port {} in {}: reachable from {reachable} of {} checks",
+                    listener.protocol,
+                    listener.address,
+                    listener.port,
+                    listener.source,
+                    rows.len()
+                )
+                .into(),
+            );
+            app.set_listener_checks(rows.as_slice().into());
+        });
+    });
+}
+
+thread_local! {
+    /// Port forwarding rules, started when first used
+    static FORWARDER: RefCell<Forwarder> = RefCell::new(Forwarder::load());
+}
+
+/// Format a byte count with a readable unit
+fn format_bytes(bytes: u64) -> String {
+    let bytes = bytes as f64;
+    if bytes >= 1024.0 * 1024.0 * 1024.0 {
+        format!("{:.2} GiB", bytes / (1024.0 * 1024.0 * 1024.0))
+    } else if bytes >= 1024.0 * 1024.0 {
+        format!("{:.1} MiB", bytes / (1024.0 * 1024.0))
+    } else if bytes >= 1024.0 {
+        format!("{:.1} KiB", bytes / 1024.0)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+/// Load the port forwarding rules and their counters into the port forwarder view
+fn refresh_forwarder(app: &MainWindow) {
+    let rows: Vec<slint_generatedMainWindow::ForwardRuleRow> = FORWARDER
+        .with_borrow(|forwarder| forwarder.status())
+        .into_iter()
+        .map(|status| {
+            let (state, error) = match (&status.error, status.running) {
+                (Some(error), _) => (error.clone(), true),
+                (None, true) => ("Forwarding".to_string(), false),
+                (None, false) => ("Disabled".to_string(), false),
+            };
+            slint_generatedMainWindow::ForwardRuleRow {
+                id: status.rule.id as i32,
+                listen: status.rule.listen().into(),
+                target: match status.rule.target_source() {
+                    Ok(target) => format!("{target} port {}", status.rule.target_port),
+                    Err(_) => format!("{} port {}", status.rule.target, status.rule.target_port),
+                }
+                .into(),
+                target_address: status
+                    .target_address
+                    .map(|address| address.to_string())
+                    .unwrap_or_default()
+                    .into(),
+                enabled: status.rule.enabled,
+                state: state.into(),
+                error,
+                active: status.active as i32,
+                connections: status.connections.to_string().into(),
+                sent: format_bytes(status.bytes_sent).into(),
+                received: format_bytes(status.bytes_received).into(),
+            }
+        })
+        .collect();
+    app.set_forward_rules(rows.as_slice().into());
+}
+
+/// Rows of the process details view, leaving out what a process does not have
+fn process_detail_rows(process: &ProcessDetails) -> Vec<slint_generatedMainWindow::DetailRow> {
+    let mut rows = vec![
+        ("Process", format!("{} (PID {})", process.name, process.pid)),
+        ("User", process.user.clone()),
+        ("Executable", process.executable.clone()),
+        ("Command line", process.command_line.clone()),
+        ("Started (UTC)", process.start_time.clone()),
+        ("Working directory", process.working_directory.clone()),
+        ("Parents", process.parent_chain()),
+        ("Systemd unit", process.systemd_unit.clone()),
+        ("Container", process.container.clone()),
+        ("Services", process.services.join("\n")),
+        ("Cgroup", process.cgroup.clone()),
+    ];
+    rows.retain(|(_, value)| !value.is_empty());
+    rows.into_iter()
+        .map(|(label, value)| slint_generatedMainWindow::DetailRow {
+            label: label.into(),
+            value: value.into(),
+        })
+        .collect()
+}
+
+/// Open the details of a port's process, looking them up on another thread
+fn open_process_details(app: &MainWindow, port: network::PortInfo) {
+    let Ok(pid) = port.process_id.parse::<u32>() else {
+        return;
+    };
+    let title = match port.environment {
+        network::NetworkEnvironment::Windows => format!("Windows process {pid}"),
+        network::NetworkEnvironment::Wsl => format!("WSL process {pid}"),
+    };
+    app.set_process_detail_title(title.clone().into());
+    app.set_process_details(slint::ModelRc::new(slint::VecModel::<
+        slint_generatedMainWindow::DetailRow,
+    >::default()));
+    app.set_process_sockets(slint::ModelRc::new(slint::VecModel::<
+        slint_generatedMainWindow::ProcessSocketRow,
+    >::default()));
+    app.set_process_detail_status("Looking up process details…".into());
+    app.set_show_process_detail(true);
+
+    let app_weak = app.as_weak();
+    std::thread::spawn(move || {
+        let result =
+            process::get_process_details(pid, &port.environment).map_err(|e| e.to_string());
+        let _ = slint::invoke_from_event_loop(move || {
+            let Some(app) = app_weak.upgrade() else {
+                return;
+            };
+            // The view may have been opened for another process meanwhile
+            if app.get_process_detail_title() != title.as_str() {
+                return;
+            }
+            match result {
+                Ok(process) => {
+                    let sockets: Vec<slint_generatedMainWindow::ProcessSocketRow> = process
+                        .sockets
+                        .iter()
+                        .map(|socket| slint_generatedMainWindow::ProcessSocketRow {
+                            protocol: socket.protocol.clone().into(),
+                            local: socket.local_address.clone().into(),
+                            remote: socket.remote_address.clone().into(),
+                            state: socket.state.clone().into(),
+                        })
+                        .collect();
+                    app.set_process_details(process_detail_rows(&process).as_slice().into());
+                    app.set_process_sockets(sockets.as_slice().into());
+                    app.set_process_detail_status("".into());
+                }
+                Err(e) => app.set_process_detail_status(e.into()),
+            }
+        });
+    });
+}
+
+thread_local! {
+    /// Process or container the kill confirmation is open for
+    static PENDING_KILL: RefCell<Option<KillTarget>> = const { RefCell::new(None) };
+}
+
+/// Open the kill confirmation for a port, looking up the owning process's details
+fn open_kill_dialog(app: &MainWindow, port: network::PortInfo) {
+    let detail = |label: &str, value: String| slint_generatedMainWindow::DetailRow {
+        label: label.into(),
+        value: value.into(),
+    };
+    let environment = match port.environment {
+        network::NetworkEnvironment::Windows => "Windows",
+        network::NetworkEnvironment::Wsl => "WSL",
+    };
+    let mut details = vec![
+        detail("Port", format!("{} {}", port.protocol, port.network)),
+        detail("Environment", environment.to_string()),
+        detail(
+            "Process",
+            format!("{} (PID {})", port.process_name, port.process_id),
+        ),
+    ];
+    if let Some(container) = &port.container {
+        details.push(detail(
+            "Container",
+            format!("{} ({})", container.container_name, container.image),
+        ));
+        if !container.compose_project.is_empty() {
+            details.push(detail("Compose project", container.compose_project.clone()));
+        }
+        details.push(detail("Runs in", container.daemon()));
+    }
+
+    let target = KillTarget::for_port(&port);
+    PENDING_KILL.with_borrow_mut(|pending| *pending = target.clone().ok());
+    app.set_kill_details(details.as_slice().into());
+    app.set_kill_busy(false);
+    app.set_kill_done(false);
+    app.set_show_kill_dialog(true);
+    let target = match target {
+        Ok(target) => target,
+        Err(e) => {
+            app.set_kill_title("Cannot terminate".into());
+            app.set_kill_graceful_action("".into());
+            app.set_kill_forced_action("".into());
+            app.set_kill_ready(false);
+            app.set_kill_done(true);
+            app.set_kill_status(e.into());
+            return;
+        }
+    };
+    app.set_kill_title(format!("Terminate {target}?").into());
+    app.set_kill_graceful_action(target.describe(KillMode::Graceful).into());
+    app.set_kill_forced_action(target.describe(KillMode::Forced).into());
+
+    let pid = match &target {
+        KillTarget::Windows { pid } | KillTarget::Wsl { pid } => *pid,
+        // The container's details are known from the port
+        KillTarget::Container { .. } => {
+            app.set_kill_ready(true);
+            app.set_kill_status("".into());
+            return;
+        }
+    };
+    app.set_kill_ready(false);
+    app.set_kill_status("Looking up process details…".into());
+    let app_weak = app.as_weak();
+    std::thread::spawn(move || {
+        let result =
+            process::get_process_details(pid, &port.environment).map_err(|e| e.to_string());
+        let _ = slint::invoke_from_event_loop(move || {
+            let Some(app) = app_weak.upgrade() else {
+                return;
+            };
+            // The dialog may have been closed or opened for another port meanwhile
+            if PENDING_KILL.with_borrow(|pending| pending.as_ref() != Some(&target)) {
+                return;
+            }
+            match result {
+                Ok(process) => {
+                    let mut rows: Vec<slint_generatedMainWindow::DetailRow> =
+                        app.get_kill_details().iter().collect();
+                    // The port list has the short name (15 characters in WSL)
+                    rows.retain(|row| row.label != "Process");
+                    rows.extend(process_detail_rows(&process));
+                    app.set_kill_details(rows.as_slice().into());
+                    app.set_kill_ready(true);
+                    app.set_kill_status("".into());
+                }
+                Err(e) => {
+                    app.set_kill_done(true);
+                    app.set_kill_status(e.into());
+                }
+            }
+        });
+    });
+}
+
+/// Load the Docker daemons behind the docker contexts into the containers view
+fn refresh_docker_daemons(app: &MainWindow) {
+    let daemons = daemon::detect_daemons();
+
+    // Desktop's distro exists even when Desktop is not running or not the selected context
+    let desktop_detected = daemons
+        .iter()
+        .any(|daemon| daemon.kind == DockerDaemonKind::Desktop);
+    app.set_docker_desktop_stopped(!desktop_detected && daemon::desktop_distro_installed());
+
+    // Convert to Slint-compatible format
+    let slint_daemons: Vec<slint_generatedMainWindow::DockerDaemon> = daemons
+        .into_iter()
+        .map(|daemon| slint_generatedMainWindow::DockerDaemon {
+            context: daemon.context.into(),
+            kind: daemon.kind.to_string().into(),
+            endpoint: daemon
+                .endpoint
+                .map(|endpoint| endpoint.to_string())
+                .unwrap_or_default()
+                .into(),
+            version: daemon.version.into(),
+            operating_system: daemon.operating_system.into(),
+        })
+        .collect();
+
+    app.set_docker_daemons(slint_daemons.as_slice().into());
+}
+
+/// Load the Podman installation details and pods into the containers view
+fn refresh_podman(app: &MainWindow) {
+    match podman::get_podman_info() {
+        Ok(info) => app.set_podman_info(slint_generatedMainWindow::PodmanInfo {
+            available: true,
+            version: info.version.into(),
+            rootless: info.rootless,
+            network_backend: info.network_backend.into(),
+            rootless_network_cmd: info.rootless_network_cmd.into(),
+        }),
+        Err(e) => {
+            eprintln!("Error getting Podman info: {e}");
+        }
+    }
+
+    match podman::get_podman_pods() {
+        Ok(pods) => {
+            // Convert to Slint-compatible format
+            let slint_pods: Vec<slint_generatedMainWindow::ContainerPod> = pods
+                .into_iter()
+                .map(|pod| slint_generatedMainWindow::ContainerPod {
+                    name: pod.name.into(),
+                    id: pod.id.chars().take(12).collect::<String>().into(),
+                    status: pod.status.into(),
+                    containers: pod.containers.join(", ").into(),
+                    networks: pod.networks.join(", ").into(),
+                })
+                .collect();
+
+            app.set_podman_pods(slint_pods.as_slice().into());
+        }
+        Err(e) => {
+            eprintln!("Error getting Podman pods: {e}");
+        }
+    }
+}
+
+/// Replace the Docker Compose projects shown in the UI and the project filter choices
+fn set_compose_projects(app: &MainWindow, projects: Vec<network::docker::compose::ComposeProject>) {
+    // The first choice clears the filter
+    let mut project_names: Vec<slint::SharedString> = vec!["All projects".into()];
+    project_names.extend(projects.iter().map(|project| project.name.clone().into()));
+
+    // Drop a filter for a project that no longer exists
+    let filter = app.get_compose_project_filter();
+    if !filter.is_empty()
+        && !projects
+            .iter()
+            .any(|project| project.name == filter.as_str())
+    {
+        app.set_compose_project_filter("".into());
+    }
+
+    // Convert to Slint-compatible format
+    let slint_projects: Vec<slint_generatedMainWindow::ComposeProject> = projects
+        .into_iter()
+        .map(|project| slint_generatedMainWindow::ComposeProject {
+            name: project.name.into(),
+            services: project
+                .services
+                .into_iter()
+                .map(|service| slint_generatedMainWindow::ComposeService {
+                    name: service.name.into(),
+                    containers: service.containers.join(", ").into(),
+                    networks: service.networks.join(", ").into(),
+                    ports: service
+                        .published_ports
+                        .iter()
+                        .map(|mapping| {
+                            format!(
+                                "{} → {}/{}",
+                                mapping.host_port, mapping.container_port, mapping.protocol
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                        .into(),
+                })
+                .collect::<Vec<_>>()
+                .as_slice()
+                .into(),
+            networks: project
+                .networks
+                .into_iter()
+                .map(|network| slint_generatedMainWindow::ComposeNetwork {
+                    name: network.name.into(),
+                    services: network.services.join(", ").into(),
+                })
+                .collect::<Vec<_>>()
+                .as_slice()
+                .into(),
+        })
+        .collect();
+
+    app.set_compose_project_names(project_names.as_slice().into());
+    app.set_compose_projects(slint_projects.as_slice().into());
+}
+
+/// Replace the port list shown in the UI, along with the excluded port ranges and conflicts
+fn set_ports(app: &MainWindow, ports: Vec<network::PortInfo>) {
+    let excluded_ranges: Vec<slint_generatedMainWindow::ExcludedRangeRow> =
+        network::excluded_port_ranges()
+            .into_iter()
+            .map(|range| {
+                let mut ports_in_use: Vec<String> = ports
+                    .iter()
+                    .filter(|port| {
+                        port.port
+                            .parse()
+                            .is_ok_and(|number| range.contains(&port.protocol, None, number))
+                    })
+                    .map(|port| format!("{} ({})", port.port, port.process_name))
+                    .collect();
+                ports_in_use.sort();
+                ports_in_use.dedup();
+                slint_generatedMainWindow::ExcludedRangeRow {
+                    protocol: range.protocol.clone().into(),
+                    ip_version: range.ip_version.to_string().into(),
+                    range: format!("{}-{}", range.start, range.end).into(),
+                    administered: range.administered,
+                    ports_in_use: ports_in_use.join(", ").into(),
+                }
+            })
+            .collect();
+    app.set_excluded_ranges(excluded_ranges.as_slice().into());
+
+    let port_conflicts: Vec<slint_generatedMainWindow::PortConflictRow> =
+        network::conflicts::find_port_conflicts(&ports)
+            .into_iter()
+            .map(|conflict| {
+                let listeners: Vec<slint::SharedString> = conflict
+                    .listeners
+                    .iter()
+                    .map(|port| {
+                        format!(
+                            "{} on {}",
+                            network::conflicts::port_owner(port),
+                            port.network
+                        )
+                        .into()
+                    })
+                    .collect();
+                slint_generatedMainWindow::PortConflictRow {
+                    kind: conflict.kind.to_string().into(),
+                    port: format!("{}/{}", conflict.port, conflict.protocol).into(),
+                    summary: conflict.summary.into(),
+                    explanation: conflict.explanation.into(),
+                    listeners: listeners.as_slice().into(),
+                }
+            })
+            .collect();
+    app.set_port_conflicts(port_conflicts.as_slice().into());
+
+    // Convert to Slint-compatible format
+    let slint_ports: Vec<slint_generatedMainWindow::PortInfo> =
+        ports.into_iter().map(to_slint_port).collect();
+
+    app.set_ports(slint_ports.as_slice().into());
+    refresh_port_search(app);
+}
+
+/// Describe the excluded port ranges a port falls in, or an empty string when it is in none
+fn excluded_ranges_of(ranges: &[network::ExcludedPortRange], port: &network::PortInfo) -> String {
+    let Ok(number) = port.port.parse::<u16>() else {
+        return String::new();
+    };
+    ranges
+        .iter()
+        // Ports are listed without their address, so ranges of either IP version apply
+        .filter(|range| range.contains(&port.protocol, None, number))
+        .map(|range| range.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Convert a port to the Slint-compatible format
+fn to_slint_port(port: network::PortInfo) -> slint_generatedMainWindow::PortInfo {
+    let excluded_range = excluded_ranges_of(&network::excluded_port_ranges(), &port);
+    let container = port.container.unwrap_or_default();
+    let container_daemon = container.daemon();
+
+    slint_generatedMainWindow::PortInfo {
+        process_id: port.process_id.into(),
+        process_name: port.process_name.into(),
+        protocol: port.protocol.into(),
+        port: port.port.into(),
+        direction: port.direction.into(),
+        network: port.network.into(),
+        environment: match port.environment {
+            network::NetworkEnvironment::Windows => "Windows".into(),
+            network::NetworkEnvironment::Wsl => "WSL".into(),
+        },
+        container_name: container.container_name.into(),
+        container_id: container.container_id.into(),
+        container_image: container.image.into(),
+        compose_project: container.compose_project.into(),
+        container_port: container.container_port.into(),
+        container_runtime: container.runtime.to_string().into(),
+        container_daemon: container_daemon.into(),
+        container_reachability: container.reachability.into(),
+        excluded_range: excluded_range.into(),
+    }
+}
+
+/// Convert a port from the Slint-compatible format
+fn from_slint_port(port: &slint_generatedMainWindow::PortInfo) -> network::PortInfo {
+    network::PortInfo {
+        process_id: port.process_id.to_string(),
+        process_name: port.process_name.to_string(),
+        protocol: port.protocol.to_string(),
+        port: port.port.to_string(),
+        direction: port.direction.to_string(),
+        network: port.network.to_string(),
+        environment: match port.environment.as_str() {
+            "Windows" => network::NetworkEnvironment::Windows,
+            _ => network::NetworkEnvironment::Wsl,
+        },
+        container: if port.container_name.is_empty() {
+            None
+        } else {
+            Some(network::ContainerPortOwner {
+                container_name: port.container_name.to_string(),
+                container_id: port.container_id.to_string(),
+                image: port.container_image.to_string(),
+                compose_project: port.compose_project.to_string(),
+                container_port: port.container_port.to_string(),
+                runtime: port
+                    .container_runtime
+                    .parse()
+                    .unwrap_or(ContainerRuntime::Docker),
+                daemon_kind: port.container_daemon.parse().ok(),
+                reachability: port.container_reachability.to_string(),
+            })
+        },
+    }
+}
+
+/// Convert a network interface from the Slint-compatible format
+fn from_slint_interface(
+    interface: &slint_generatedMainWindow::NetworkInterface,
+) -> network::NetworkInterface {
+    network::NetworkInterface {
+        name: interface.name.to_string(),
+        ipv4_addresses: interface
+            .ipv4_addresses
+            .iter()
+            .map(|ip| ip.to_string())
+            .collect(),
+        ipv6_addresses: interface
+            .ipv6_addresses
+            .iter()
+            .map(|ip| ip.to_string())
+            .collect(),
+        mac_address: if interface.mac_address == "N/A" {
+            None
+        } else {
+            Some(interface.mac_address.to_string())
+        },
+        is_up: interface.is_up,
+        is_loopback: interface.is_loopback,
+        environment: match interface.environment.as_str() {
+            "Windows" => network::NetworkEnvironment::Windows,
+            _ => network::NetworkEnvironment::Wsl,
+        },
+    }
+}
+
+thread_local! {
+    /// Saved port filters and port snapshots, only used from the UI thread
+    static PORT_FILTERS: RefCell<PortFilters> = RefCell::new(PortFilters::load());
+    static PORT_SNAPSHOTS: RefCell<PortSnapshots> = RefCell::new(PortSnapshots::load());
+}
+
+/// The current ports of every environment, with the interfaces they can be reached on
+fn live_port_records(app: &MainWindow) -> Vec<PortRecord> {
+    let interfaces: Vec<network::NetworkInterface> = app
+        .get_network_interfaces()
+        .iter()
+        .map(|interface| from_slint_interface(&interface))
+        .collect();
+    app.get_ports()
+        .iter()
+        .map(|port| PortRecord::new(&from_slint_port(&port), &interfaces))
+        .collect()
+}
+
+/// Show the ports matching the ports view's query, in the chosen order
+fn refresh_port_search(app: &MainWindow) {
+    let query: PortQuery = match app.get_port_query().parse() {
+        Ok(query) => {
+            app.set_port_query_error("".into());
+            query
+        }
+        Err(e) => {
+            app.set_port_query_error(e.into());
+            return;
+        }
+    };
+    let column: PortSortColumn = app.get_port_sort_column().parse().unwrap_or_default();
+    let live = live_port_records(app);
+    let (matches, snapshot_count) = PORT_SNAPSHOTS.with_borrow(|snapshots| {
+        let searched = app.get_port_include_snapshots().then_some(snapshots);
+        let matches = search_ports(
+            &query,
+            &live,
+            searched,
+            column,
+            app.get_port_sort_ascending(),
+        );
+        (matches, snapshots.snapshots.len())
+    });
+
+    let rows: Vec<slint_generatedMainWindow::PortSearchRow> = matches
+        .iter()
+        .map(|found| slint_generatedMainWindow::PortSearchRow {
+            source: found.source.clone().into(),
+            environment: found.record.environment.clone().into(),
+            protocol: found.record.protocol.clone().into(),
+            port: found.record.port.to_string().into(),
+            address: found.record.address.clone().into(),
+            process: found.record.process_name.clone().into(),
+            pid: found.record.process_id.clone().into(),
+            container: found.record.container.clone().into(),
+            interfaces: found.record.interfaces.join(", ").into(),
+        })
+        .collect();
+    app.set_port_search_rows(rows.as_slice().into());
+    app.set_port_snapshot_count(snapshot_count as i32);
+    let status = if app.get_port_include_snapshots() {
+        format!(
+            "{} ports found in the live ports and {snapshot_count} snapshots",
+            rows.len()
+        )
+    } else {
+        format!("{} of {} live ports", rows.len(), live.len())
+    };
+    app.set_port_search_status(status.into());
+}
+
+/// Show the saved port filters
+fn refresh_port_filters(app: &MainWindow) {
+    let rows: Vec<slint_generatedMainWindow::SavedPortFilterRow> =
+        PORT_FILTERS.with_borrow(|filters| {
+            filters
+                .filters
+                .iter()
+                .map(|filter| slint_generatedMainWindow::SavedPortFilterRow {
+                    name: filter.name.clone().into(),
+                    query: filter.query.clone().into(),
+                })
+                .collect()
+        });
+    app.set_port_filters(rows.as_slice().into());
+}
+
+/// Load the containers of a container network into the docker network detail view
+fn load_docker_containers(
+    app: &MainWindow,
+    runtime: ContainerRuntime,
+    context: &str,
+    network_name: &str,
+) {
+    let containers =
+        get_containers_for_network(runtime, context, network_name).map_err(|e| e.to_string());
+    show_docker_containers(app, network_name, containers);
+}
+
+/// Show fetched containers of a container network in the docker network detail view
+fn show_docker_containers(
+    app: &MainWindow,
+    network_name: &str,
+    containers: Result<Vec<network::docker::DockerContainer>, String>,
+) {
+    match containers {
+        Ok(containers) => {
+            // Convert to Slint-compatible format
+            let slint_containers: Vec<slint_generatedMainWindow::DockerContainer> = containers
+                .into_iter()
+                .map(|container| {
+                    let endpoint = container.endpoint.unwrap_or_default();
+                    slint_generatedMainWindow::DockerContainer {
+                        name: container.name.into(),
+                        image: container.image.into(),
+                        status: container.status.into(),
+                        port_mappings: container
+                            .port_mappings
+                            .into_iter()
+                            .map(|mapping| slint_generatedMainWindow::PortMapping {
+                                host_ip: mapping.host_ip.into(),
+                                host_port: mapping.host_port.into(),
+                                container_port: mapping.container_port.into(),
+                                protocol: mapping.protocol.into(),
+                            })
+                            .collect::<Vec<_>>()
+                            .as_slice()
+                            .into(),
+                        id: container.id.into(),
+                        ipv4_address: endpoint.ipv4_address.into(),
+                        ipv6_address: endpoint.ipv6_address.into(),
+                        mac_address: endpoint.mac_address.into(),
+                        gateway: endpoint.gateway.into(),
+                        aliases: endpoint
+                            .aliases
+                            .iter()
+                            .map(|alias| alias.clone().into())
+                            .collect::<Vec<_>>()
+                            .as_slice()
+                            .into(),
+                        dns_names: endpoint
+                            .dns_names
+                            .iter()
+                            .map(|name| name.clone().into())
+                            .collect::<Vec<_>>()
+                            .as_slice()
+                            .into(),
+                        pod: container.pod.into(),
+                        runtime: container.runtime.to_string().into(),
+                        daemon: container
+                            .daemon
+                            .map(|daemon| daemon.kind.to_string())
+                            .unwrap_or_default()
+                            .into(),
+                    }
+                })
+                .collect();
+
+            app.set_docker_containers(slint_containers.as_slice().into());
+        }
+        Err(e) => {
+            eprintln!("Error getting containers for network {network_name}: {e}");
+            // Still show the modal but with empty containers
+            app.set_docker_containers(slint::ModelRc::new(slint::VecModel::default()));
+        }
+    }
+}
+
+/// Maximum number of entries kept in the Docker event log
+const MAX_DOCKER_EVENTS: usize = 200;
+
+/// Data fetched on the event thread for a batch of Docker events, so the UI thread does not
+/// block on it
+struct DockerEventUpdate {
+    events: Vec<events::DockerEvent>,
+    /// Networks created by the events
+    networks: Vec<network::docker::DockerNetwork>,
+    /// Current containers of every network a container connected to, by context and network
+    containers: Vec<(
+        String,
+        String,
+        Result<Vec<network::docker::DockerContainer>, String>,
+    )>,
+    ports: Option<Vec<network::PortInfo>>,
+    compose_projects: Option<Vec<network::docker::compose::ComposeProject>>,
+}
+
+impl DockerEventUpdate {
+    /// Fetch whatever the events require: new networks, the containers of networks that gained
+    /// containers, and the port list after containers published or released ports
+    ///
+    /// Each list is fetched at most once per batch.
+    fn fetch(events: Vec<events::DockerEvent>) -> Self {
+        let networks = events
+            .iter()
+            .filter(|event| event.kind == DockerEventKind::NetworkCreated)
+            .filter_map(|event| {
+                get_docker_network(&event.context, &event.id)
+                    .map_err(|e| eprintln!("Error getting Docker network {}: {e}", event.name))
+                    .ok()
+            })
+            .collect();
+
+        // A started container reports a network connect event for every network it joins, so
+        // connect events alone tell which container lists changed
+        let mut connected: Vec<(&str, &str)> = events
+            .iter()
+            .filter(|event| event.kind == DockerEventKind::NetworkConnected)
+            .map(|event| (event.context.as_str(), event.name.as_str()))
+            .collect();
+        connected.sort_unstable();
+        connected.dedup();
+        let containers = connected
+            .into_iter()
+            .map(|(context, network_name)| {
+                let containers =
+                    get_containers_for_network(ContainerRuntime::Docker, context, network_name)
+                        .map_err(|e| e.to_string());
+                (context.to_string(), network_name.to_string(), containers)
+            })
+            .collect();
+
+        let ports = events
+            .iter()
+            .any(|event| {
+                matches!(
+                    event.kind,
+                    DockerEventKind::ContainerStarted
+                        | DockerEventKind::ContainerStopped
+                        | DockerEventKind::ContainerDied
+                )
+            })
+            .then(|| {
+                get_active_ports()
+                    .map_err(|e| eprintln!("Error getting active ports: {e}"))
+                    .ok()
+            })
+            .flatten();
+        // Every tracked event can change which services and networks a project has
+        let compose_projects = get_compose_projects()
+            .map_err(|e| eprintln!("Error getting Docker Compose projects: {e}"))
+            .ok();
+
+        DockerEventUpdate {
+            events,
+            networks,
+            containers,
+            ports,
+            compose_projects,
+        }
+    }
+}
+
+/// Apply a batch of Docker events to the Docker models and the port list, and record the events
+/// in the event log
+fn apply_docker_events(app: &MainWindow, update: DockerEventUpdate) {
+    // Only networks of the event's daemon are affected; networks with the same name on other
+    // daemons or in Podman are left alone
+    let is_docker = |network: &slint_generatedMainWindow::DockerNetwork, context: &str| {
+        network.runtime == ContainerRuntime::Docker.to_string().as_str()
+            && network.context == context
+    };
+    let selected = app.get_selected_docker_network();
+    let selected_network = selected.name.to_string();
+    let networks = app.get_docker_networks();
+    let networks = networks
+        .as_any()
+        .downcast_ref::<slint::VecModel<slint_generatedMainWindow::DockerNetwork>>();
+    let containers = app.get_docker_containers();
+    let containers = containers
+        .as_any()
+        .downcast_ref::<slint::VecModel<slint_generatedMainWindow::DockerContainer>>();
+
+    for event in update.events {
+        record_docker_event(app, &event);
+        let detail_open =
+            app.get_show_docker_network_detail() && is_docker(&selected, &event.context);
+
+        match event.kind {
+            DockerEventKind::NetworkCreated => {
+                let network = update
+                    .networks
+                    .iter()
+                    .find(|network| network.name == event.name);
+                if let (Some(networks), Some(network)) = (networks, network) {
+                    if !networks
+                        .iter()
+                        .any(|n| is_docker(&n, &event.context) && n.name == network.name.as_str())
+                    {
+                        networks.push(slint_generatedMainWindow::DockerNetwork {
+                            name: network.name.clone().into(),
+                            driver: network.driver.clone().into(),
+                            scope: network.scope.clone().into(),
+                            subnet: network.subnet.clone().into(),
+                            compose_project: network.compose_project.clone().into(),
+                            runtime: network.runtime.to_string().into(),
+                            daemon: network
+                                .daemon
+                                .as_ref()
+                                .map(|daemon| daemon.kind.to_string())
+                                .unwrap_or_default()
+                                .into(),
+                            context: event.context.clone().into(),
+                        });
+                    }
+                }
+            }
+            DockerEventKind::NetworkDestroyed => {
+                if let Some(networks) = networks {
+                    if let Some(index) = networks.iter().position(|n| {
+                        is_docker(&n, &event.context) && n.name == event.name.as_str()
+                    }) {
+                        networks.remove(index);
+                    }
+                }
+                if detail_open && selected_network == event.name {
+                    app.set_show_docker_network_detail(false);
+                }
+            }
+            DockerEventKind::NetworkDisconnected => {
+                if let (true, Some(containers), Some(container_id)) = (
+                    detail_open && selected_network == event.name,
+                    containers,
+                    event.container_id.as_deref(),
+                ) {
+                    remove_docker_container(containers, container_id);
+                }
+            }
+            DockerEventKind::ContainerStopped | DockerEventKind::ContainerDied => {
+                if let Some(containers) = containers {
+                    remove_docker_container(containers, &event.id);
+                }
+            }
+            // The containers of connected networks were fetched for the whole batch
+            DockerEventKind::NetworkConnected | DockerEventKind::ContainerStarted => {}
         }
     }
 
-    // Refresh Docker networks
-    match get_all_docker_networks() {
-        Ok(networks) => {
-            // Convert to Slint-compatible format
-            let slint_networks: Vec<slint_generatedMainWindow::DockerNetwork> = networks
+    // The fetched container lists reflect the state after the whole batch
+    if app.get_show_docker_network_detail() {
+        if let Some((_, network_name, containers)) =
+            update
+                .containers
                 .into_iter()
-                .map(|network| slint_generatedMainWindow::DockerNetwork {
-                    name: network.name.into(),
-                    driver: network.driver.into(),
-                    scope: network.scope.into(),
-                    subnet: network.subnet.into(),
+                .find(|(context, network_name, _)| {
+                    is_docker(&selected, context) && *network_name == selected_network
                 })
-                .collect();
-
-            app.set_docker_networks(slint_networks.as_slice().into());
+        {
+            show_docker_containers(app, &network_name, containers);
         }
-        Err(e) => {
-            eprintln!("Error getting Docker networks: {e}");
+    }
+
+    if let Some(ports) = update.ports {
+        set_ports(app, ports);
+    }
+    if let Some(compose_projects) = update.compose_projects {
+        set_compose_projects(app, compose_projects);
+    }
+}
+
+/// Record a Docker event in the event log, newest first
+fn record_docker_event(app: &MainWindow, event: &events::DockerEvent) {
+    let log_entry = slint_generatedMainWindow::DockerEvent {
+        time: format_event_time(event.time).into(),
+        kind: event.kind.to_string().into(),
+        name: event.name.clone().into(),
+        id: event.id.chars().take(12).collect::<String>().into(),
+        context: event.context.clone().into(),
+    };
+    let event_log = app.get_docker_events();
+    if let Some(event_log) = event_log
+        .as_any()
+        .downcast_ref::<slint::VecModel<slint_generatedMainWindow::DockerEvent>>()
+    {
+        event_log.insert(0, log_entry);
+        if event_log.row_count() > MAX_DOCKER_EVENTS {
+            event_log.remove(MAX_DOCKER_EVENTS);
         }
+    } else {
+        app.set_docker_events(slint::ModelRc::new(slint::VecModel::from(vec![log_entry])));
+    }
+}
+
+/// Remove a container from the docker network detail view by (full or short) ID
+fn remove_docker_container(
+    containers: &slint::VecModel<slint_generatedMainWindow::DockerContainer>,
+    container_id: &str,
+) {
+    if let Some(index) = containers
+        .iter()
+        .position(|c| !c.id.is_empty() && container_id.starts_with(c.id.as_str()))
+    {
+        containers.remove(index);
     }
 }
 
+/// Format a Unix timestamp as a UTC time of day (HH:MM:SS)
+fn format_event_time(timestamp: i64) -> String {
+    let seconds_of_day = timestamp.rem_euclid(86_400);
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Subcommands run in the terminal without opening the window
+    if let Some(command) = cli::Cli::parse().command {
+        if !cli::run(command)? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let app = MainWindow::new()?;
 
     // Load initial data on startup
@@ -143,67 +2155,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         if let Some(selected_interface) = network_interfaces.iter().nth(index as usize) {
             // Convert Slint NetworkInterface back to our NetworkInterface for filtering
-            let rust_interface = network::NetworkInterface {
-                name: selected_interface.name.to_string(),
-                ipv4_addresses: selected_interface
-                    .ipv4_addresses
-                    .iter()
-                    .map(|ip| ip.to_string())
-                    .collect(),
-                ipv6_addresses: selected_interface
-                    .ipv6_addresses
-                    .iter()
-                    .map(|ip| ip.to_string())
-                    .collect(),
-                mac_address: if selected_interface.mac_address == "N/A" {
-                    None
-                } else {
-                    Some(selected_interface.mac_address.to_string())
-                },
-                is_up: selected_interface.is_up,
-                is_loopback: selected_interface.is_loopback,
-                environment: match selected_interface.environment.as_str() {
-                    "Windows" => network::NetworkEnvironment::Windows,
-                    _ => network::NetworkEnvironment::Wsl,
-                },
-            };
+            let rust_interface = from_slint_interface(&selected_interface);
 
             // Convert Slint ports to Rust ports for filtering
-            let rust_ports: Vec<network::PortInfo> = ports
-                .iter()
-                .map(|port| network::PortInfo {
-                    process_id: port.process_id.to_string(),
-                    process_name: port.process_name.to_string(),
-                    protocol: port.protocol.to_string(),
-                    port: port.port.to_string(),
-                    direction: port.direction.to_string(),
-                    network: port.network.to_string(),
-                    environment: match port.environment.as_str() {
-                        "Windows" => network::NetworkEnvironment::Windows,
-                        _ => network::NetworkEnvironment::Wsl,
-                    },
-                })
-                .collect();
+            let rust_ports: Vec<network::PortInfo> =
+                ports.iter().map(|port| from_slint_port(&port)).collect();
 
-            // Filter ports for this interface
-            let filtered_ports = filter_ports_for_interface(&rust_interface, &rust_ports);
+            // Filter ports for this interface and the selected Docker Compose project
+            let filtered_ports = filter_ports_for_compose_project(
+                filter_ports_for_interface(&rust_interface, &rust_ports),
+                &app.get_compose_project_filter(),
+            );
 
             // Convert filtered ports back to Slint format
-            let slint_filtered_ports: Vec<slint_generatedMainWindow::PortInfo> = filtered_ports
-                .into_iter()
-                .map(|port| slint_generatedMainWindow::PortInfo {
-                    process_id: port.process_id.into(),
-                    process_name: port.process_name.into(),
-                    protocol: port.protocol.into(),
-                    port: port.port.into(),
-                    direction: port.direction.into(),
-                    network: port.network.into(),
-                    environment: match port.environment {
-                        network::NetworkEnvironment::Windows => "Windows".into(),
-                        network::NetworkEnvironment::Wsl => "WSL".into(),
-                    },
-                })
-                .collect();
+            let slint_filtered_ports: Vec<slint_generatedMainWindow::PortInfo> =
+                filtered_ports.into_iter().map(to_slint_port).collect();
 
             // Set the selected network details and filtered ports
             app.set_selected_network_detail(selected_interface.clone());
@@ -214,82 +2180,975 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
+    // Stop flag of the running packet sender run, shared with its ping and traceroute threads,
+    // and whether the run can be interrupted (HTTP requests cannot)
+    let probe_stop = Rc::new(RefCell::new((Arc::new(AtomicBool::new(false)), false)));
+
     let app_weak = app.as_weak();
+    let probe_stop_send = probe_stop.clone();
     app.on_send_packet(move || {
         let app = app_weak.unwrap();
         // Handle packet sending
         let packet_type = app.get_packet_type();
         let destination = app.get_destination();
+        let dns_options = if packet_type == "DNS" {
+            match dns_options(&app) {
+                Ok(options) => Some(options),
+                Err(e) => {
+                    app.set_raw_output(format!("Error: {e}").into());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+        let socket_options = if packet_type == "TCP" || packet_type == "UDP" {
+            match socket_options(&app, &packet_type) {
+                Ok(options) => Some(options),
+                Err(e) => {
+                    app.set_raw_output(format!("Error: {e}").into());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let throughput_options = if packet_type == "Throughput" {
+            match throughput_options(&app) {
+                Ok(options) => Some(options),
+                Err(e) => {
+                    app.set_raw_output(format!("Error: {e}").into());
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        // Send from every selected environment, or from Windows when none is selected
+        let mut sources: Vec<ProbeSource> = app
+            .get_probe_sources()
+            .iter()
+            .filter(|source| source.selected)
+            .map(|source| from_slint_probe_source(&source))
+            .collect();
+        if sources.is_empty() {
+            sources.push(ProbeSource::Windows);
+        }
+
+        // The chosen interface only applies to sources in the interface's environment
+        let selected_interface = app.get_selected_interface();
+        let binding = interface_bindings(&app)
+            .into_iter()
+            .find(|binding| binding.label == selected_interface.as_str());
+        let labelled: Vec<(ProbeSource, Option<String>, String)> = sources
+            .into_iter()
+            .map(|source| {
+                let source_address = binding
+                    .as_ref()
+                    .filter(|binding| source.environment().as_ref() == Some(&binding.environment))
+                    .map(|binding| binding.address.clone());
+                let label = match &source_address {
+                    Some(address) => format!("{source} via {address}"),
+                    None => source.to_string(),
+                };
+                (source, source_address, label)
+            })
+            .collect();
 
-        // Clone values for async context
-        let destination_clone = destination.clone();
-        let app_weak_clone = app.as_weak();
+        // One comparison row per source, filled in as results arrive
+        let pending: Vec<slint_generatedMainWindow::ProbeResult> = labelled
+            .iter()
+            .map(|(_, _, label)| slint_generatedMainWindow::ProbeResult {
+                source: label.into(),
+                success: false,
+                status: "Running…".into(),
+                ..Default::default()
+            })
+            .collect();
+        app.set_probe_results(slint::ModelRc::new(slint::VecModel::from(pending)));
+        app.set_ping_response("".into());
+        app.set_http_response("".into());
+        app.set_tls_report("".into());
+        app.set_socket_response("".into());
+        app.set_dns_response("".into());
+        app.set_throughput_response("".into());
+        app.set_raw_output("".into());
+
+        // Replace the stop flag so stopping this run does not affect an older one
+        let stop = Arc::new(AtomicBool::new(false));
+        let interruptible = packet_type != "HTTP over TCP";
+        let (previous, _) = probe_stop_send.replace((stop.clone(), interruptible));
+        previous.store(true, Ordering::SeqCst);
+        let run = PROBE_RUN.fetch_add(1, Ordering::SeqCst) + 1;
+        let remaining = Arc::new(AtomicUsize::new(labelled.len()));
+        app.set_probe_running(true);
 
-        // Spawn async task for packet sending
-        slint::spawn_local(async move {
-            let app = app_weak_clone.unwrap();
+        if packet_type == "ping" {
+            let labels: Vec<String> = labelled.iter().map(|(_, _, label)| label.clone()).collect();
+            reset_latency_series(&app, &labels);
+        } else {
+            reset_latency_series(&app, &[]);
+        }
+        app.set_traceroute_hops(slint::ModelRc::new(slint::VecModel::<
+            slint_generatedMainWindow::TracerouteHopRow,
+        >::default()));
+        let options = ping_options(&app);
+        let trace_options = traceroute_options(&app);
+        let request = http_request(&app, &destination);
+        // Fetched once per run by the first trace that finds a hop
+        let landmarks = Arc::new(OnceLock::new());
+        let run_parameters = probe_parameters(&app);
+        let throughput_lock = Arc::new(Mutex::new(()));
+
+        for (index, (source, source_address, label)) in labelled.into_iter().enumerate() {
+            // Recorded per source, so re-running an entry only sends from its source
+            let parameters = ProbeParameters {
+                sources: vec![source.to_string()],
+                ..run_parameters.clone()
+            };
+            // Clone values for the probe thread or task
+            let destination_clone = destination.to_string();
+            let app_weak_clone = app.as_weak();
+            let remaining = remaining.clone();
 
             if packet_type == "ping" {
-                match send_ping(&destination_clone, Some(4), Some(5)) {
-                    Ok(result) => {
-                        // Format ping response
-                        let ping_response = format!(
-                            r#"
-                            Destination: {}
-                            Transmitted: {}
-                            Received: {}
-                            Packet Loss: {:.2}%
-                            Min Time: {:.2}ms
-                            Avg Time: {:.2}ms
-                            Max Time: {:.2}ms"#,
-                            result.destination,
-                            result.transmitted,
-                            result.received,
-                            result.packet_loss,
-                            result.min_time,
-                            result.avg_time,
-                            result.max_time
-                        );
-
-                        // Set ping response
-                        app.set_ping_response(ping_response.into());
-
-                        // Set raw output
-                        app.set_raw_output(result.raw_output.into());
+                let options = options.clone();
+                let stop = stop.clone();
+
+                // Pings run on their own thread and stream every reply to the UI
+                std::thread::spawn(move || {
+                    let result = send_ping(
+                        &source,
+                        &destination_clone,
+                        source_address.as_deref(),
+                        &options,
+                        &stop,
+                        |reply, statistics| {
+                            let row = ping_progress_row(&label, statistics, reply);
+                            let rtt = reply.rtt;
+                            let app_weak = app_weak_clone.clone();
+                            let _ = slint::invoke_from_event_loop(move || {
+                                let Some(app) = app_weak.upgrade() else {
+                                    return;
+                                };
+                                if PROBE_RUN.load(Ordering::SeqCst) == run {
+                                    set_probe_result(&app, index, row);
+                                    record_latency(&app, index, rtt);
+                                }
+                            });
+                        },
+                    )
+                    .map_err(|e| e.to_string());
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let Some(app) = app_weak_clone.upgrade() else {
+                            return;
+                        };
+                        finish_probe(&app, run, &remaining);
+                        if PROBE_RUN.load(Ordering::SeqCst) != run {
+                            return;
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    slint_generatedMainWindow::ProbeResult {
+                                        source: label.clone().into(),
+                                        success: result.received > 0,
+                                        status: format!(
+                                            "{}/{} replies",
+                                            result.received, result.transmitted
+                                        )
+                                        .into(),
+                                        loss: format!("{:.0}%", result.packet_loss).into(),
+                                        latency: format!(
+                                            "{:.1} / {:.1} / {:.1} ms",
+                                            result.min_time, result.avg_time, result.max_time
+                                        )
+                                        .into(),
+                                        jitter: format!("{:.1} ms", result.jitter).into(),
+                                        detail: result.destination.clone().into(),
+                                    },
+                                    Some((
+                                        MainWindow::get_ping_response,
+                                        MainWindow::set_ping_response,
+                                    )),
+                                    &format_ping_result(&result),
+                                    &result.raw_output,
+                                    &parameters,
+                                );
+                            }
+                            Err(e) => {
+                                let error_msg = format!("Error sending ping: {e}");
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    probe_error_row(&label, e),
+                                    Some((
+                                        MainWindow::get_ping_response,
+                                        MainWindow::set_ping_response,
+                                    )),
+                                    &error_msg,
+                                    &error_msg,
+                                    &parameters,
+                                );
+                            }
+                        }
+                    });
+                });
+            } else if packet_type == "traceroute" {
+                let options = trace_options.clone();
+                let stop = stop.clone();
+                let landmarks = landmarks.clone();
+
+                // Traces run on their own thread and stream every hop to the UI
+                std::thread::spawn(move || {
+                    let result = trace_route(
+                        &source,
+                        &destination_clone,
+                        source_address.as_deref(),
+                        &options,
+                        &stop,
+                        |hop| {
+                            let landmarks: &network::PathLandmarks =
+                                landmarks.get_or_init(network::get_path_landmarks);
+                            let row = traceroute_hop_row(index, &label, hop, landmarks);
+                            let progress = slint_generatedMainWindow::ProbeResult {
+                                source: label.clone().into(),
+                                success: !hop.address.is_empty(),
+                                status: format!("Hop {}", hop.ttl).into(),
+                                latency: hop
+                                    .average_rtt()
+                                    .map(|rtt| format!("{rtt:.1} ms"))
+                                    .unwrap_or_default()
+                                    .into(),
+                                detail: hop.to_string().trim().to_string().into(),
+                                ..Default::default()
+                            };
+                            let app_weak = app_weak_clone.clone();
+                            let _ = slint::invoke_from_event_loop(move || {
+                                let Some(app) = app_weak.upgrade() else {
+                                    return;
+                                };
+                                if PROBE_RUN.load(Ordering::SeqCst) == run {
+                                    set_probe_result(&app, index, progress);
+                                    add_traceroute_hop(&app, row);
+                                }
+                            });
+                        },
+                    )
+                    .map_err(|e| e.to_string());
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let Some(app) = app_weak_clone.upgrade() else {
+                            return;
+                        };
+                        finish_probe(&app, run, &remaining);
+                        if PROBE_RUN.load(Ordering::SeqCst) != run {
+                            return;
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                let last_hop = result.hops.last();
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    slint_generatedMainWindow::ProbeResult {
+                                        source: label.clone().into(),
+                                        success: result.reached,
+                                        status: if result.reached {
+                                            format!("Reached in {} hops", result.hops.len())
+                                        } else {
+                                            format!("Not reached ({} hops)", result.hops.len())
+                                        }
+                                        .into(),
+                                        latency: last_hop
+                                            .and_then(|hop| hop.average_rtt())
+                                            .map(|rtt| format!("{rtt:.1} ms"))
+                                            .unwrap_or_default()
+                                            .into(),
+                                        detail: format!(
+                                            "{} {} ({})",
+                                            result.protocol,
+                                            result.address,
+                                            last_hop
+                                                .filter(|hop| !hop.note.is_empty())
+                                                .map(|hop| hop.note.as_str())
+                                                .unwrap_or(result.destination.as_str())
+                                        )
+                                        .into(),
+                                        ..Default::default()
+                                    },
+                                    None,
+                                    "",
+                                    &result.raw_output,
+                                    &parameters,
+                                );
+                            }
+                            Err(e) => {
+                                let error_msg = format!("Error tracing route: {e}");
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    probe_error_row(&label, e),
+                                    None,
+                                    "",
+                                    &error_msg,
+                                    &parameters,
+                                );
+                            }
+                        }
+                    });
+                });
+            } else if let Some((record_type, resolver)) = dns_options.clone() {
+                // Queries block until answered, so they run on their own thread
+                std::thread::spawn(move || {
+                    let result = resolve_dns(
+                        &source,
+                        &destination_clone,
+                        record_type,
+                        &resolver,
+                        source_address.as_deref(),
+                        std::time::Duration::from_secs(5),
+                    )
+                    .map_err(|e| e.to_string());
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let Some(app) = app_weak_clone.upgrade() else {
+                            return;
+                        };
+                        finish_probe(&app, run, &remaining);
+                        if PROBE_RUN.load(Ordering::SeqCst) != run {
+                            return;
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    slint_generatedMainWindow::ProbeResult {
+                                        source: label.clone().into(),
+                                        success: result.rcode == "NOERROR"
+                                            && !result.answers.is_empty(),
+                                        status: format!(
+                                            "{} ({} answers)",
+                                            result.rcode,
+                                            result.answers.len()
+                                        )
+                                        .into(),
+                                        latency: format!("{:.1} ms", result.latency).into(),
+                                        detail: result
+                                            .answers
+                                            .iter()
+                                            .map(|record| record.data.as_str())
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                            .into(),
+                                        ..Default::default()
+                                    },
+                                    Some((
+                                        MainWindow::get_dns_response,
+                                        MainWindow::set_dns_response,
+                                    )),
+                                    &format_dns_result(&result),
+                                    &result.raw_output,
+                                    &parameters,
+                                );
+                            }
+                            Err(e) => {
+                                let error_msg = format!("Error resolving {destination_clone}: {e}");
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    probe_error_row(&label, e),
+                                    Some((
+                                        MainWindow::get_dns_response,
+                                        MainWindow::set_dns_response,
+                                    )),
+                                    &error_msg,
+                                    &error_msg,
+                                    &parameters,
+                                );
+                            }
+                        }
+                    });
+                });
+            } else if let Some((server, options)) = throughput_options.clone() {
+                let stop = stop.clone();
+                let throughput_lock = throughput_lock.clone();
+                let address = Some(destination_clone.trim().to_string()).filter(|a| !a.is_empty());
+
+                // Throughput tests saturate the link, so the sources take turns
+                std::thread::spawn(move || {
+                    let _turn = throughput_lock.lock();
+                    let result = if stop.load(Ordering::SeqCst) {
+                        Err("Stopped".to_string())
+                    } else {
+                        test_throughput(&source, &server, address.as_deref(), &options, &stop)
+                            .map_err(|e| e.to_string())
+                    };
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let Some(app) = app_weak_clone.upgrade() else {
+                            return;
+                        };
+                        finish_probe(&app, run, &remaining);
+                        if PROBE_RUN.load(Ordering::SeqCst) != run {
+                            return;
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                let detail = match (result.packets_sent, result.retransmits) {
+                                    (Some(sent), _) => format!(
+                                        "{}/{sent} datagrams to {}",
+                                        result.packets_received.unwrap_or_default(),
+                                        result.destination
+                                    ),
+                                    (None, Some(retransmits)) => format!(
+                                        "{retransmits} retransmits{} to {}",
+                                        if result.retransmits_system_wide {
+                                            " (system)"
+                                        } else {
+                                            ""
+                                        },
+                                        result.destination
+                                    ),
+                                    (None, None) => result.destination.clone(),
+                                };
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    slint_generatedMainWindow::ProbeResult {
+                                        source: label.clone().into(),
+                                        success: result.bytes_received > 0,
+                                        status: format_bitrate(result.throughput).into(),
+                                        loss: result
+                                            .loss()
+                                            .map(|loss| format!("{loss:.1}%"))
+                                            .unwrap_or_default()
+                                            .into(),
+                                        jitter: result
+                                            .jitter
+                                            .map(|jitter| format!("{jitter:.2} ms"))
+                                            .unwrap_or_default()
+                                            .into(),
+                                        detail: detail.into(),
+                                        ..Default::default()
+                                    },
+                                    Some((
+                                        MainWindow::get_throughput_response,
+                                        MainWindow::set_throughput_response,
+                                    )),
+                                    &format_throughput_result(&result),
+                                    &result.raw_output,
+                                    &parameters,
+                                );
+                            }
+                            Err(e) => {
+                                let error_msg = format!("Error testing throughput: {e}");
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    probe_error_row(&label, e),
+                                    Some((
+                                        MainWindow::get_throughput_response,
+                                        MainWindow::set_throughput_response,
+                                    )),
+                                    &error_msg,
+                                    &error_msg,
+                                    &parameters,
+                                );
+                            }
+                        }
+                    });
+                });
+            } else if let Some(options) = socket_options.clone() {
+                let stop = stop.clone();
+
+                // Socket probes block while waiting for data, so they run on their own thread
+                std::thread::spawn(move || {
+                    let result = send_socket_probe(
+                        &source,
+                        &destination_clone,
+                        source_address.as_deref(),
+                        &options,
+                        &stop,
+                    )
+                    .map_err(|e| e.to_string());
+
+                    let _ = slint::invoke_from_event_loop(move || {
+                        let Some(app) = app_weak_clone.upgrade() else {
+                            return;
+                        };
+                        finish_probe(&app, run, &remaining);
+                        if PROBE_RUN.load(Ordering::SeqCst) != run {
+                            return;
+                        }
+
+                        match result {
+                            Ok(result) => {
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    slint_generatedMainWindow::ProbeResult {
+                                        source: label.clone().into(),
+                                        success: result.error.is_none(),
+                                        status: result
+                                            .error
+                                            .clone()
+                                            .unwrap_or_else(|| {
+                                                format!("{} bytes received", result.received.len())
+                                            })
+                                            .into(),
+                                        latency: result
+                                            .first_byte_time
+                                            .or(result.connect_time)
+                                            .map(|time| format!("{time:.1} ms"))
+                                            .unwrap_or_else(|| {
+                                                format!("{:.1} ms", result.total_time)
+                                            })
+                                            .into(),
+                                        detail: result
+                                            .remote_address
+                                            .clone()
+                                            .unwrap_or_else(|| result.destination.clone())
+                                            .into(),
+                                        ..Default::default()
+                                    },
+                                    Some((
+                                        MainWindow::get_socket_response,
+                                        MainWindow::set_socket_response,
+                                    )),
+                                    &format_socket_result(&result),
+                                    &result.raw_output,
+                                    &parameters,
+                                );
+                            }
+                            Err(e) => {
+                                let error_msg = format!("Error sending {}: {e}", options.protocol);
+                                finish_probe_result(
+                                    &app,
+                                    index,
+                                    probe_error_row(&label, e),
+                                    Some((
+                                        MainWindow::get_socket_response,
+                                        MainWindow::set_socket_response,
+                                    )),
+                                    &error_msg,
+                                    &error_msg,
+                                    &parameters,
+                                );
+                            }
+                        }
+                    });
+                });
+            } else if packet_type == "HTTP over TCP" {
+                let request = request.clone();
+                // Spawn async task for packet sending
+                slint::spawn_local(async move {
+                    let result =
+                        send_http_request(&source, &request, source_address.as_deref()).await;
+                    // Inspected even when the request failed, as TLS errors are what it explains
+                    let tls = match request.tls_endpoint() {
+                        Some((host, port)) => Some(
+                            tokio::task::spawn_blocking(move || {
+                                inspect_tls(&source, &host, port, source_address.as_deref())
+                                    .map_err(|e| e.to_string())
+                            })
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|inspection| inspection),
+                        ),
+                        None => None,
+                    };
+
+                    let app = app_weak_clone.unwrap();
+                    finish_probe(&app, run, &remaining);
+                    if PROBE_RUN.load(Ordering::SeqCst) != run {
+                        return;
                     }
-                    Err(e) => {
-                        let error_msg = format!("Error sending ping: {e}");
-                        app.set_ping_response(error_msg.clone().into());
-                        app.set_raw_output(error_msg.into());
+                    match result {
+                        Ok(result) => {
+                            finish_probe_result(
+                                &app,
+                                index,
+                                slint_generatedMainWindow::ProbeResult {
+                                    source: label.clone().into(),
+                                    success: result.status_code > 0 && result.status_code < 400,
+                                    status: format!("HTTP {}", result.status_code).into(),
+                                    loss: "".into(),
+                                    latency: format!("{:.1} ms", result.response_time).into(),
+                                    jitter: "".into(),
+                                    // The phases show whether DNS, the relay or the app is slow
+                                    detail: format!(
+                                        "{} {} — {}{}",
+                                        result.http_version.as_deref().unwrap_or("HTTP"),
+                                        result.remote_address.as_deref().unwrap_or(&result.url),
+                                        result.timing,
+                                        if result.proxy_used == Some(true) {
+                                            " (via proxy)"
+                                        } else {
+                                            ""
+                                        }
+                                    )
+                                    .into(),
+                                },
+                                Some((
+                                    MainWindow::get_http_response,
+                                    MainWindow::set_http_response,
+                                )),
+                                &format_http_result(&result),
+                                &result.raw_output,
+                                &parameters,
+                            );
+                        }
+                        Err(e) => {
+                            let error_msg = format!("Error sending HTTP request: {e}");
+                            finish_probe_result(
+                                &app,
+                                index,
+                                probe_error_row(&label, e.to_string()),
+                                Some((
+                                    MainWindow::get_http_response,
+                                    MainWindow::set_http_response,
+                                )),
+                                &error_msg,
+                                &error_msg,
+                                &parameters,
+                            );
+                        }
                     }
-                }
-            } else if packet_type == "HTTP over TCP" {
-                match send_http_request(&destination_clone, Some("GET"), Some(30)).await {
-                    Ok(result) => {
-                        // Format HTTP response
-                        let http_response = format!(
-                            "URL: {}\nStatus Code: {}\nResponse Time: {:.2}ms\n\nHeaders:\n{}\n\nBody:\n{}",
-                            result.url,
-                            result.status_code,
-                            result.response_time,
-                            result.response_headers,
-                            result.response_body
-                        );
-
-                        // Set HTTP response
-                        app.set_http_response(http_response.into());
-
-                        // Set raw output
-                        app.set_raw_output(result.raw_output.into());
+                    match tls {
+                        Some(Ok(inspection)) => {
+                            app.set_tls_report(append_section(
+                                app.get_tls_report(),
+                                &label,
+                                &inspection.to_string(),
+                            ));
+                            app.set_raw_output(append_section(
+                                app.get_raw_output(),
+                                &format!("{label} TLS"),
+                                &inspection.raw_output,
+                            ));
+                        }
+                        Some(Err(e)) => {
+                            app.set_tls_report(append_section(
+                                app.get_tls_report(),
+                                &label,
+                                &format!("Error inspecting the certificate chain: {e}"),
+                            ));
+                        }
+                        None => {}
+                    }
+                })
+                .unwrap();
+            }
+        }
+    });
+    let app_weak = app.as_weak();
+    app.on_stop_probe(move || {
+        let (stop, interruptible) = &*probe_stop.borrow();
+        stop.store(true, Ordering::SeqCst);
+        // HTTP requests cannot be interrupted; ignore their results instead
+        if !interruptible {
+            let app = app_weak.unwrap();
+            PROBE_RUN.fetch_add(1, Ordering::SeqCst);
+            app.set_probe_running(false);
+        }
+    });
+
+    // Toggle a probe source of the packet sender
+    let app_weak_sources = app.as_weak();
+    app.on_toggle_probe_source(move |index| {
+        let app = app_weak_sources.unwrap();
+        let sources = app.get_probe_sources();
+        if let Some(mut source) = sources.row_data(index as usize) {
+            source.selected = !source.selected;
+            sources.set_row_data(index as usize, source);
+        }
+    });
+
+    // Probe history and saved probe collections
+    refresh_history(&app);
+    refresh_saved_probes(&app);
+
+    let app_weak_history = app.as_weak();
+    app.on_search_history(move || {
+        refresh_history(&app_weak_history.unwrap());
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_show_history_raw(move |id| {
+        let app = app_weak_history.unwrap();
+        let raw_output = PROBE_HISTORY.with_borrow(|history| {
+            history
+                .get(id as u64)
+                .map(|entry| entry.raw_output.clone())
+                .unwrap_or_default()
+        });
+        app.set_history_raw(raw_output.into());
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_rerun_history(move |id| {
+        let app = app_weak_history.unwrap();
+        let parameters = PROBE_HISTORY
+            .with_borrow(|history| history.get(id as u64).map(|e| e.parameters.clone()));
+        if let Some(parameters) = parameters {
+            apply_probe_parameters(&app, &parameters);
+            app.invoke_send_packet();
+        }
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_clear_history(move || {
+        let app = app_weak_history.unwrap();
+        PROBE_HISTORY.with_borrow_mut(|history| {
+            history.clear();
+            if let Err(e) = history.save() {
+                eprintln!("Error saving probe history: {e}");
+            }
+        });
+        app.set_history_raw("".into());
+        refresh_history(&app);
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_save_probe(move |collection, name| {
+        let app = app_weak_history.unwrap();
+        let parameters = probe_parameters(&app);
+        update_collections(&app, |collections| {
+            collections.save_probe(&collection, &name, &parameters);
+            format!("Saved \"{name}\" in {collection}")
+        });
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_save_history_probe(move |id, collection, name| {
+        let app = app_weak_history.unwrap();
+        let parameters = PROBE_HISTORY
+            .with_borrow(|history| history.get(id as u64).map(|e| e.parameters.clone()));
+        if let Some(parameters) = parameters {
+            update_collections(&app, |collections| {
+                collections.save_probe(&collection, &name, &parameters);
+                format!("Saved \"{name}\" in {collection}")
+            });
+        }
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_run_saved_probe(move |collection, name| {
+        let app = app_weak_history.unwrap();
+        let parameters = PROBE_COLLECTIONS.with_borrow(|collections| {
+            collections
+                .get(&collection)
+                .and_then(|collection| {
+                    collection
+                        .probes
+                        .iter()
+                        .find(|probe| probe.name == name.as_str())
+                })
+                .map(|probe| probe.parameters.clone())
+        });
+        if let Some(parameters) = parameters {
+            apply_probe_parameters(&app, &parameters);
+            app.invoke_send_packet();
+        }
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_delete_saved_probe(move |collection, name| {
+        let app = app_weak_history.unwrap();
+        update_collections(&app, |collections| {
+            collections.remove_probe(&collection, &name);
+            format!("Deleted \"{name}\" from {collection}")
+        });
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_export_collection(move |collection, path| {
+        let app = app_weak_history.unwrap();
+        let status =
+            PROBE_COLLECTIONS.with_borrow(|collections| match collections.get(&collection) {
+                Some(existing) => match existing.export(std::path::Path::new(path.as_str())) {
+                    Ok(()) => format!("Exported {collection} to {path}"),
+                    Err(e) => format!("Error exporting {collection}: {e}"),
+                },
+                None => format!("No saved probes in {collection}"),
+            });
+        app.set_history_status(status.into());
+    });
+
+    let app_weak_history = app.as_weak();
+    app.on_import_collection(move |path| {
+        let app = app_weak_history.unwrap();
+        match ProbeCollection::import(std::path::Path::new(path.as_str())) {
+            Ok(imported) => update_collections(&app, |collections| {
+                let status = format!(
+                    "Imported {} probes into {}",
+                    imported.probes.len(),
+                    imported.name
+                );
+                collections.add(imported);
+                status
+            }),
+            Err(e) => app.set_history_status(format!("Error importing {path}: {e}").into()),
+        }
+    });
+
+    // Test listener
+    let app_weak_listener = app.as_weak();
+    app.on_start_listener(move || {
+        let app = app_weak_listener.unwrap();
+        let source_label = app.get_listener_source();
+        let Some(source) = app
+            .get_probe_sources()
+            .iter()
+            .find(|source| source.label == source_label)
+            .map(|source| from_slint_probe_source(&source))
+        else {
+            app.set_listener_status(format!("Unknown source: {source_label}").into());
+            return;
+        };
+        let protocol = match app.get_listener_protocol().parse::<ListenerProtocol>() {
+            Ok(protocol) => protocol,
+            Err(e) => {
+                app.set_listener_status(e.into());
+                return;
+            }
+        };
+        let Some(port) = app.get_listener_port().trim().parse::<u16>().ok() else {
+            app.set_listener_status(format!("Invalid port: {}", app.get_listener_port()).into());
+            return;
+        };
+        let address = app.get_listener_address().to_string();
+
+        app.set_listener_running(true);
+        app.set_listener_events("".into());
+        app.set_listener_status(format!("Starting {protocol} listener in {source}…").into());
+        let app_weak = app.as_weak();
+        // Starting in WSL takes a while, so it happens on its own thread
+        std::thread::spawn(move || {
+            let app_weak_events = app_weak.clone();
+            let result = start_test_listener(&source, protocol, &address, port, move |event| {
+                let app_weak = app_weak_events.clone();
+                let _ = slint::invoke_from_event_loop(move || {
+                    if let Some(app) = app_weak.upgrade() {
+                        add_listener_event(&app, &event);
+                    }
+                });
+            })
+            .map_err(|e| e.to_string());
+            let _ = slint::invoke_from_event_loop(move || {
+                let Some(app) = app_weak.upgrade() else {
+                    return;
+                };
+                match result {
+                    Ok(listener) => {
+                        let listener = Arc::new(listener);
+                        TEST_LISTENER.with_borrow_mut(|running| *running = Some(listener.clone()));
+                        check_listener(&app, listener);
                     }
                     Err(e) => {
-                        let error_msg = format!("Error sending HTTP request: {e}");
-                        app.set_http_response(error_msg.clone().into());
-                        app.set_raw_output(error_msg.into());
+                        app.set_listener_running(false);
+                        app.set_listener_status(e.into());
                     }
                 }
+            });
+        });
+    });
+
+    let app_weak_listener = app.as_weak();
+    app.on_check_listener(move || {
+        let app = app_weak_listener.unwrap();
+        if let Some(listener) = TEST_LISTENER.with_borrow(|running| running.clone()) {
+            check_listener(&app, listener);
+        }
+    });
+
+    let app_weak_listener = app.as_weak();
+    app.on_stop_listener(move || {
+        let app = app_weak_listener.unwrap();
+        // The listener stops once a running check lets go of it too
+        TEST_LISTENER.with_borrow_mut(|running| running.take());
+        app.set_listener_running(false);
+        app.set_listener_status("Stopped".into());
+    });
+
+    // Port forwarder, starting the stored rules
+    refresh_forwarder(&app);
+    let app_weak_forwarder = app.as_weak();
+    let forwarder_timer = slint::Timer::default();
+    forwarder_timer.start(
+        slint::TimerMode::Repeated,
+        std::time::Duration::from_secs(1),
+        move || {
+            if let Some(app) = app_weak_forwarder.upgrade() {
+                refresh_forwarder(&app);
             }
-        }).unwrap();
+        },
+    );
+
+    let app_weak_forwarder = app.as_weak();
+    app.on_add_forward(move || {
+        let app = app_weak_forwarder.unwrap();
+        let target_label = app.get_forward_target();
+        let Some(target) = app
+            .get_probe_sources()
+            .iter()
+            .find(|source| source.label == target_label)
+            .map(|source| from_slint_probe_source(&source))
+        else {
+            app.set_forward_status(format!("Unknown target: {target_label}").into());
+            return;
+        };
+        let ports = (
+            app.get_forward_listen_port().trim().parse::<u16>(),
+            app.get_forward_target_port().trim().parse::<u16>(),
+        );
+        let (Ok(listen_port), Ok(target_port)) = ports else {
+            app.set_forward_status("Ports must be numbers from 1 to 65535".into());
+            return;
+        };
+        let listen_address = app.get_forward_listen_address();
+        let result = FORWARDER.with_borrow_mut(|forwarder| {
+            forwarder.add(&listen_address, listen_port, &target, target_port)
+        });
+        let status = match result {
+            Ok(rule) => format!("Forwarding {rule}"),
+            Err(e) => format!("Could not add forwarding: {e}"),
+        };
+        app.set_forward_status(status.into());
+        refresh_forwarder(&app);
+    });
+
+    let app_weak_forwarder = app.as_weak();
+    app.on_set_forward_enabled(move |id, enabled| {
+        let app = app_weak_forwarder.unwrap();
+        let result =
+            FORWARDER.with_borrow_mut(|forwarder| forwarder.set_enabled(id as u64, enabled));
+        if let Err(e) = result {
+            app.set_forward_status(e.to_string().into());
+        } else {
+            app.set_forward_status("".into());
+        }
+        refresh_forwarder(&app);
+    });
+
+    let app_weak_forwarder = app.as_weak();
+    app.on_remove_forward(move |id| {
+        let app = app_weak_forwarder.unwrap();
+        if let Err(e) = FORWARDER.with_borrow_mut(|forwarder| forwarder.remove(id as u64)) {
+            app.set_forward_status(format!("Error saving forwarding rules: {e}").into());
+        }
+        refresh_forwarder(&app);
     });
 
     // Handle copy to clipboard
@@ -309,52 +3168,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     });
 
-    // Handle docker network selection
-    let _app_weak = app.as_weak();
-    app.on_kill_process(move |process_id| {
-        let process_id_str = process_id.to_string();
+    // Ask before terminating the process or container behind a port
+    let app_weak_kill = app.as_weak();
+    app.on_kill_process(move |port| {
+        let app = app_weak_kill.unwrap();
+        open_kill_dialog(&app, from_slint_port(&port));
+    });
 
-        // Safety check - don't allow killing critical system processes
-        if process_id_str == "0"
-            || process_id_str == "1"
-            || process_id_str == "4"
-            || process_id_str == "N/A"
-        {
-            eprintln!("Cannot kill system process with PID: {process_id_str}");
+    let app_weak_details = app.as_weak();
+    app.on_show_process_details(move |port| {
+        let app = app_weak_details.unwrap();
+        open_process_details(&app, from_slint_port(&port));
+    });
+
+    let app_weak_kill = app.as_weak();
+    app.on_confirm_kill(move |mode| {
+        let app = app_weak_kill.unwrap();
+        let Some(target) = PENDING_KILL.with_borrow(|pending| pending.clone()) else {
             return;
-        }
+        };
+        let mode = match mode.parse::<KillMode>() {
+            Ok(mode) => mode,
+            Err(e) => {
+                app.set_kill_status(e.into());
+                return;
+            }
+        };
 
-        // Parse PID to ensure it's valid
-        match process_id_str.parse::<u32>() {
-            Ok(pid) => {
-                if pid < 10 {
-                    eprintln!("Cannot kill system process with PID: {pid}");
+        app.set_kill_busy(true);
+        app.set_kill_status(format!("{}…", target.describe(mode)).into());
+        let app_weak = app.as_weak();
+        // Graceful termination waits for the process to exit
+        std::thread::spawn(move || {
+            let result = target.terminate(mode).map_err(|e| e.to_string());
+            let _ = slint::invoke_from_event_loop(move || {
+                let Some(app) = app_weak.upgrade() else {
                     return;
-                }
-
-                // Kill the process using Windows taskkill
-                let result = std::process::Command::new("taskkill")
-                    .args(["/F", "/PID", &process_id_str])
-                    .output();
-
-                match result {
-                    Ok(output) => {
-                        if output.status.success() {
-                            println!("Successfully killed process with PID: {process_id_str}");
-                        } else {
-                            let stderr = String::from_utf8_lossy(&output.stderr);
-                            eprintln!("Failed to kill process {process_id_str}: {stderr}");
-                        }
+                };
+                let status = match result {
+                    Ok(outcome) => {
+                        println!("{outcome}");
+                        outcome
                     }
                     Err(e) => {
-                        eprintln!("Error executing kill command for PID {process_id_str}: {e}");
+                        eprintln!("{e}");
+                        e
                     }
-                }
-            }
-            Err(_) => {
-                eprintln!("Invalid process ID format: {process_id_str}");
-            }
-        }
+                };
+                app.set_kill_busy(false);
+                app.set_kill_done(true);
+                app.set_kill_status(status.into());
+            });
+        });
     });
 
     // Handle filter ports from modal search
@@ -367,83 +3232,120 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let all_ports = app.get_ports();
 
         // Convert Slint NetworkInterface back to Rust type
-        let rust_interface = network::NetworkInterface {
-            name: selected_interface.name.to_string(),
-            ipv4_addresses: selected_interface
-                .ipv4_addresses
-                .iter()
-                .map(|ip| ip.to_string())
-                .collect(),
-            ipv6_addresses: selected_interface
-                .ipv6_addresses
-                .iter()
-                .map(|ip| ip.to_string())
-                .collect(),
-            mac_address: if selected_interface.mac_address == "N/A" {
-                None
-            } else {
-                Some(selected_interface.mac_address.to_string())
-            },
-            is_up: selected_interface.is_up,
-            is_loopback: selected_interface.is_loopback,
-            environment: match selected_interface.environment.as_str() {
-                "Windows" => network::NetworkEnvironment::Windows,
-                _ => network::NetworkEnvironment::Wsl,
-            },
-        };
+        let rust_interface = from_slint_interface(&selected_interface);
 
         // Convert all ports to Rust ports for filtering
         let rust_ports: Vec<network::PortInfo> = all_ports
             .iter()
-            .map(|port| network::PortInfo {
-                process_id: port.process_id.to_string(),
-                process_name: port.process_name.to_string(),
-                protocol: port.protocol.to_string(),
-                port: port.port.to_string(),
-                direction: port.direction.to_string(),
-                network: port.network.to_string(),
-                environment: match port.environment.as_str() {
-                    "Windows" => network::NetworkEnvironment::Windows,
-                    _ => network::NetworkEnvironment::Wsl,
-                },
-            })
+            .map(|port| from_slint_port(&port))
             .collect();
 
-        // First filter ports for the selected interface as before
-        let mut filtered = filter_ports_for_interface(&rust_interface, &rust_ports);
-
-        // Apply search filter if any
-        let q = query.to_string();
-        let q_lc = q.to_lowercase();
-        if !q.is_empty() {
-            filtered.retain(|p| {
-                let name = p.process_name.to_lowercase();
-                let pid = p.process_id.to_lowercase();
-                let port_s = p.port.to_lowercase();
-                name.contains(&q_lc) || pid.contains(&q_lc) || port_s.contains(&q_lc)
-            });
+        // First filter ports for the selected interface and Docker Compose project as before
+        let mut filtered = filter_ports_for_compose_project(
+            filter_ports_for_interface(&rust_interface, &rust_ports),
+            &app.get_compose_project_filter(),
+        );
+
+        // Apply the search, in the query language of the ports view
+        match query.parse::<PortQuery>() {
+            Ok(query) => {
+                let interfaces = std::slice::from_ref(&rust_interface);
+                filtered.retain(|port| {
+                    query.matches(
+                        network::search::LIVE_SOURCE,
+                        &PortRecord::new(port, interfaces),
+                    )
+                })
+            }
+            Err(e) => eprintln!("Invalid port search: {e}"),
         }
 
         // Convert back to Slint type
-        let slint_filtered: Vec<slint_generatedMainWindow::PortInfo> = filtered
-            .into_iter()
-            .map(|port| slint_generatedMainWindow::PortInfo {
-                process_id: port.process_id.into(),
-                process_name: port.process_name.into(),
-                protocol: port.protocol.into(),
-                port: port.port.into(),
-                direction: port.direction.into(),
-                network: port.network.into(),
-                environment: match port.environment {
-                    network::NetworkEnvironment::Windows => "Windows".into(),
-                    network::NetworkEnvironment::Wsl => "WSL".into(),
-                },
-            })
-            .collect();
+        let slint_filtered: Vec<slint_generatedMainWindow::PortInfo> =
+            filtered.into_iter().map(to_slint_port).collect();
 
         app.set_filtered_ports(slint_filtered.as_slice().into());
     });
 
+    // Global ports view with saved filters and snapshots
+    refresh_port_filters(&app);
+    refresh_port_search(&app);
+
+    let app_weak_ports = app.as_weak();
+    app.on_search_ports(move || {
+        refresh_port_search(&app_weak_ports.unwrap());
+    });
+
+    let app_weak_ports = app.as_weak();
+    app.on_sort_ports(move |column| {
+        let app = app_weak_ports.unwrap();
+        // Clicking the sorted column again reverses the order
+        if app.get_port_sort_column() == column {
+            app.set_port_sort_ascending(!app.get_port_sort_ascending());
+        } else {
+            app.set_port_sort_column(column);
+            app.set_port_sort_ascending(true);
+        }
+        refresh_port_search(&app);
+    });
+
+    let app_weak_ports = app.as_weak();
+    app.on_save_port_filter(move |name| {
+        let app = app_weak_ports.unwrap();
+        let query = app.get_port_query();
+        if let Err(e) = query.parse::<PortQuery>() {
+            app.set_port_query_error(e.into());
+            return;
+        }
+        PORT_FILTERS.with_borrow_mut(|filters| {
+            filters.save_filter(&name, &query);
+            if let Err(e) = filters.save() {
+                eprintln!("Error saving port filters: {e}");
+            }
+        });
+        refresh_port_filters(&app);
+    });
+
+    let app_weak_ports = app.as_weak();
+    app.on_delete_port_filter(move |name| {
+        let app = app_weak_ports.unwrap();
+        PORT_FILTERS.with_borrow_mut(|filters| {
+            filters.remove(&name);
+            if let Err(e) = filters.save() {
+                eprintln!("Error saving port filters: {e}");
+            }
+        });
+        refresh_port_filters(&app);
+    });
+
+    let app_weak_ports = app.as_weak();
+    app.on_take_port_snapshot(move || {
+        let app = app_weak_ports.unwrap();
+        let live = live_port_records(&app);
+        let status = PORT_SNAPSHOTS.with_borrow_mut(|snapshots| {
+            let snapshot = snapshots.take(live);
+            let status = format!("{} with {} ports", snapshot.label(), snapshot.ports.len());
+            match snapshots.save() {
+                Ok(()) => status,
+                Err(e) => format!("{status}, not saved: {e}"),
+            }
+        });
+        refresh_port_search(&app);
+        app.set_port_search_status(status.into());
+    });
+
+    let app_weak_ports = app.as_weak();
+    app.on_clear_port_snapshots(move || {
+        let app = app_weak_ports.unwrap();
+        PORT_SNAPSHOTS.with_borrow_mut(|snapshots| {
+            snapshots.clear();
+            if let Err(e) = snapshots.save() {
+                eprintln!("Error saving port snapshots: {e}");
+            }
+        });
+        refresh_port_search(&app);
+    });
+
     let app_weak_docker = app.as_weak();
     app.on_docker_network_selected(move |index| {
         let app = app_weak_docker.unwrap();
@@ -454,39 +3356,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         if let Some(selected_network) = docker_networks.iter().nth(index as usize) {
             let network_name = selected_network.name.to_string();
+            let runtime = selected_network
+                .runtime
+                .parse()
+                .unwrap_or(ContainerRuntime::Docker);
 
             // Fetch containers for this network synchronously
-            match get_containers_for_network(&network_name) {
-                Ok(containers) => {
-                    // Convert to Slint-compatible format
-                    let slint_containers: Vec<slint_generatedMainWindow::DockerContainer> =
-                        containers
-                            .into_iter()
-                            .map(|container| slint_generatedMainWindow::DockerContainer {
-                                name: container.name.into(),
-                                image: container.image.into(),
-                                status: container.status.into(),
-                                ports: container.ports.into(),
-                                id: container.id.into(),
-                            })
-                            .collect();
+            load_docker_containers(&app, runtime, &selected_network.context, &network_name);
 
-                    // Set the selected docker network details and containers
-                    app.set_selected_docker_network(selected_network.clone());
-                    app.set_docker_containers(slint_containers.as_slice().into());
+            // Set the selected docker network details and show the docker network detail modal
+            app.set_selected_docker_network(selected_network.clone());
+            app.set_show_docker_network_detail(true);
+        }
+    });
 
-                    // Show the docker network detail modal
-                    app.set_show_docker_network_detail(true);
-                }
-                Err(e) => {
-                    eprintln!("Error getting containers for network {network_name}: {e}");
-                    // Still show the modal but with empty containers
-                    app.set_selected_docker_network(selected_network.clone());
-                    app.set_docker_containers(slint::ModelRc::new(slint::VecModel::default()));
-                    app.set_show_docker_network_detail(true);
-                }
+    // Follow Docker events to keep the Docker views and the port list current
+    let app_weak_events = app.as_weak();
+    events::subscribe(move |events| {
+        let update = DockerEventUpdate::fetch(events);
+        let app_weak = app_weak_events.clone();
+        let _ = slint::invoke_from_event_loop(move || {
+            if let Some(app) = app_weak.upgrade() {
+                apply_docker_events(&app, update);
             }
-        }
+        });
     });
 
     app.run()?;
@@ -0,0 +1,664 @@
+//! Port conflict detection
+//!
+//! Listeners that get in each other's way are easy to miss when Windows, WSL and the container
+//! runtimes each show only their own: a Windows process takes `localhost` from WSL's localhost
+//! forwarding, a specific bind takes connections from a wildcard one, two daemons publish the
+//! same port, or a server listens on IPv4 only while `localhost` resolves to `::1` first.
+
+use crate::network::docker::daemon::DockerDaemonKind;
+use crate::network::{bind_host, NetworkEnvironment, PortInfo, WSL_RELAY_FORWARDERS};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// How two or more listeners of a port conflict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConflictKind {
+    /// The port is listened on in WSL and directly on Windows
+    WslAndWindows,
+    /// A bind to a specific address takes that address's connections from a wildcard bind
+    Shadowed,
+    /// More than one container publishes the port
+    DuplicatePublish,
+    /// The port is listened on over one address family only, or by other processes per family
+    AddressFamily,
+}
+
+impl std::fmt::Display for ConflictKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConflictKind::WslAndWindows => write!(f, "WSL and Windows"),
+            ConflictKind::Shadowed => write!(f, "Shadowed bind"),
+            ConflictKind::DuplicatePublish => write!(f, "Duplicate publish"),
+            ConflictKind::AddressFamily => write!(f, "IPv4/IPv6 split"),
+        }
+    }
+}
+
+/// Listeners of a port that get in each other's way, and what that means for clients
+#[derive(Debug, Clone)]
+pub struct PortConflict {
+    pub kind: ConflictKind,
+    pub protocol: String,
+    pub port: u16,
+    pub summary: String,
+    pub explanation: String,
+    pub listeners: Vec<PortInfo>,
+}
+
+/// Address families a listener accepts connections over
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Family {
+    V4,
+    V6,
+    Both,
+}
+
+impl Family {
+    fn overlaps(self, other: Family) -> bool {
+        self == Family::Both || other == Family::Both || self == other
+    }
+}
+
+/// The address a listener is bound to
+struct Bind<'a> {
+    port: &'a PortInfo,
+    host: String,
+    family: Family,
+    wildcard: bool,
+    loopback: bool,
+}
+
+impl<'a> Bind<'a> {
+    /// Read the bind address of a port, e.g. "0.0.0.0:80", "[::1]:80", ":::80" or "*:80"
+    fn of(port: &'a PortInfo) -> Option<Self> {
+        let host = bind_host(port);
+
+        // ss shows a dual-stack socket as "*" and one accepting IPv6 only as "[::]"
+        if host == "*" {
+            return Some(Bind {
+                port,
+                host: host.to_string(),
+                family: Family::Both,
+                wildcard: true,
+                loopback: false,
+            });
+        }
+        let ip = match host.parse::<IpAddr>().ok()? {
+            IpAddr::V6(ip) => ip
+                .to_ipv4_mapped()
+                .map(IpAddr::V4)
+                .unwrap_or(IpAddr::V6(ip)),
+            ip => ip,
+        };
+        Some(Bind {
+            port,
+            host: ip.to_string(),
+            family: if ip.is_ipv4() { Family::V4 } else { Family::V6 },
+            wildcard: ip.is_unspecified(),
+            loopback: ip.is_loopback(),
+        })
+    }
+
+    /// Whether WSL's localhost forwarding or a `localhost` client on Windows reaches the bind
+    fn takes_localhost(&self) -> bool {
+        self.wildcard || self.loopback
+    }
+
+    fn is_relay(&self) -> bool {
+        let process_name = self.port.process_name.to_lowercase();
+        WSL_RELAY_FORWARDERS
+            .iter()
+            .any(|relay| process_name.starts_with(relay))
+    }
+
+    /// Whether two binds belong to the same process, or publish the same container
+    fn same_owner(&self, other: &Bind) -> bool {
+        match (&self.port.container, &other.port.container) {
+            (Some(a), Some(b)) => a.container_name == b.container_name,
+            (None, None) => {
+                self.port.environment == other.port.environment
+                    && self.port.process_id == other.port.process_id
+                    && self.port.process_name == other.port.process_name
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this WSL bind is probably Docker Desktop's own proxy for a container that
+    /// `windows` publishes
+    ///
+    /// The proxy's sockets show up in the other WSL distros without an owning process. They are
+    /// not attributed to the container, but they do not conflict with it either.
+    fn is_desktop_proxy_for(&self, windows: &Bind) -> bool {
+        self.port.container.is_none()
+            && self.port.process_id.parse::<u32>().is_err()
+            && windows
+                .port
+                .container
+                .as_ref()
+                .is_some_and(|container| container.daemon_kind == Some(DockerDaemonKind::Desktop))
+    }
+}
+
+/// Describe who holds a port, e.g. "node (PID 1234) in WSL"
+pub fn port_owner(port: &PortInfo) -> String {
+    let environment = match port.environment {
+        NetworkEnvironment::Windows => "Windows",
+        NetworkEnvironment::Wsl => "WSL",
+    };
+    match &port.container {
+        Some(container) => format!(
+            "container {} ({}) in {environment}",
+            container.container_name,
+            container.daemon()
+        ),
+        None => format!(
+            "{} (PID {}) in {environment}",
+            port.process_name, port.process_id
+        ),
+    }
+}
+
+/// Find the listeners that conflict with each other
+///
+/// Ports are compared per protocol and port number across both environments, so pass every
+/// collected port rather than those of one interface.
+pub fn find_port_conflicts(ports: &[PortInfo]) -> Vec<PortConflict> {
+    let mut groups: BTreeMap<(u16, String), Vec<Bind>> = BTreeMap::new();
+    for port in ports {
+        let (Ok(number), Some(bind)) = (port.port.parse::<u16>(), Bind::of(port)) else {
+            continue;
+        };
+        groups
+            .entry((number, port.protocol.to_uppercase()))
+            .or_default()
+            .push(bind);
+    }
+
+    let mut conflicts = Vec::new();
+    for ((port, protocol), binds) in &groups {
+        let mut found = Vec::new();
+        // WSL's localhost forwarding only relays TCP
+        if protocol == "TCP" {
+            found.extend(wsl_and_windows(*port, &binds[..]));
+        }
+        found.extend(shadowed(*port, &binds[..]));
+        found.extend(duplicate_publish(*port, &binds[..]));
+        for environment in [NetworkEnvironment::Windows, NetworkEnvironment::Wsl] {
+            let binds: Vec<&Bind> = binds
+                .iter()
+                .filter(|bind| bind.port.environment == environment)
+                .collect();
+            found.extend(address_family(*port, &binds));
+        }
+
+        for (kind, summary, explanation, listeners) in found {
+            conflicts.push(PortConflict {
+                kind,
+                protocol: protocol.clone(),
+                port: *port,
+                summary,
+                explanation,
+                listeners: listeners.into_iter().cloned().collect(),
+            });
+        }
+    }
+    conflicts.sort_by_key(|conflict| (conflict.port, conflict.kind));
+    conflicts
+}
+
+/// A conflict before it is tied to its protocol and port
+type Found<'a> = (ConflictKind, String, String, Vec<&'a PortInfo>);
+
+/// A port listened on in WSL and on Windows, where `localhost` reaches only one of them
+fn wsl_and_windows<'a>(port: u16, binds: &[Bind<'a>]) -> Option<Found<'a>> {
+    let windows: Vec<&Bind> = binds
+        .iter()
+        .filter(|bind| bind.port.environment == NetworkEnvironment::Windows)
+        .filter(|bind| bind.takes_localhost() && !bind.is_relay())
+        .collect();
+    let wsl: Vec<&Bind> = binds
+        .iter()
+        .filter(|bind| bind.port.environment == NetworkEnvironment::Wsl)
+        .filter(|bind| bind.takes_localhost())
+        // Docker Desktop publishes a container on both sides
+        .filter(|bind| {
+            !windows
+                .iter()
+                .any(|windows| windows.same_owner(bind) || bind.is_desktop_proxy_for(windows))
+        })
+        .collect();
+    let (windows_bind, wsl_bind) = (windows.first()?, wsl.first()?);
+    let relays: Vec<&Bind> = binds.iter().filter(|bind| bind.is_relay()).collect();
+
+    // Windows hands a connection to the most specific bind, so a relay on the loopback address
+    // still takes localhost from a wildcard Windows listener
+    let explanation = if relays.is_empty() || !windows_bind.wildcard {
+        format!(
+            "localhost:{port} on Windows reaches {}, not {}: the Windows listener holds the port, \
+             so WSL's localhost forwarding cannot. Stop one of them or move it to another port.",
+            port_owner(windows_bind.port),
+            port_owner(wsl_bind.port)
+        )
+    } else {
+        format!(
+            "localhost:{port} on Windows reaches {} through WSL's localhost forwarding, while \
+             the LAN and the other Windows addresses reach {}. Stop one of them or move it to \
+             another port.",
+            port_owner(wsl_bind.port),
+            port_owner(windows_bind.port)
+        )
+    };
+
+    Some((
+        ConflictKind::WslAndWindows,
+        format!("Port {port} is listened on in WSL and on Windows"),
+        explanation,
+        windows
+            .iter()
+            .chain(&relays)
+            .chain(&wsl)
+            .map(|bind| bind.port)
+            .collect(),
+    ))
+}
+
+/// Wildcard binds whose connections to an address are taken by a bind to that address
+fn shadowed<'a>(port: u16, binds: &[Bind<'a>]) -> Vec<Found<'a>> {
+    let mut found = Vec::new();
+    for wildcard in binds.iter().filter(|bind| bind.wildcard) {
+        for specific in binds.iter().filter(|bind| !bind.wildcard) {
+            // The relay taking localhost is reported as a WSL and Windows conflict
+            if specific.port.environment != wildcard.port.environment
+                || !specific.family.overlaps(wildcard.family)
+                || specific.same_owner(wildcard)
+                || specific.is_relay()
+            {
+                continue;
+            }
+            found.push((
+                ConflictKind::Shadowed,
+                format!("{}:{port} shadows {}:{port}", specific.host, wildcard.host),
+                format!(
+                    "Connections to {}:{port} reach {}, while every other address reaches {}.",
+                    specific.host,
+                    port_owner(specific.port),
+                    port_owner(wildcard.port)
+                ),
+                vec![specific.port, wildcard.port],
+            ));
+        }
+    }
+    found
+}
+
+/// A port published by more than one container
+fn duplicate_publish<'a>(port: u16, binds: &[Bind<'a>]) -> Option<Found<'a>> {
+    let publishers: Vec<&Bind> = binds
+        .iter()
+        .filter(|bind| bind.port.container.is_some())
+        .collect();
+    let mut names: Vec<&str> = publishers
+        .iter()
+        .filter_map(|bind| bind.port.container.as_ref())
+        .map(|container| container.container_name.as_str())
+        .collect();
+    names.sort();
+    names.dedup();
+    if names.len() < 2 {
+        return None;
+    }
+
+    let mut places: Vec<String> = publishers
+        .iter()
+        .map(|bind| {
+            let address = if bind.family == Family::V6 {
+                format!("[{}]:{port}", bind.host)
+            } else {
+                format!("{}:{port}", bind.host)
+            };
+            format!("{address} by {}", port_owner(bind.port))
+        })
+        .collect();
+    places.dedup();
+    let environment = &publishers[0].port.environment;
+    let depends_on = if publishers
+        .iter()
+        .all(|bind| &bind.port.environment == environment)
+    {
+        "which address the client uses"
+    } else {
+        "where the client runs and which address it uses"
+    };
+
+    Some((
+        ConflictKind::DuplicatePublish,
+        format!("Port {port} is published by {}", names.join(", ")),
+        format!(
+            "The port is published on {}, so which container answers depends on {depends_on}.",
+            places.join(", ")
+        ),
+        publishers.iter().map(|bind| bind.port).collect(),
+    ))
+}
+
+/// Listeners of one environment that answer over IPv4 or IPv6 only, or split by family
+fn address_family<'a>(port: u16, binds: &[&Bind<'a>]) -> Option<Found<'a>> {
+    let v4: Vec<&Bind> = binds
+        .iter()
+        .copied()
+        .filter(|bind| bind.family.overlaps(Family::V4))
+        .collect();
+    let v6: Vec<&Bind> = binds
+        .iter()
+        .copied()
+        .filter(|bind| bind.family.overlaps(Family::V6))
+        .collect();
+    let is_localhost = |bind: &&Bind| matches!(bind.host.as_str(), "127.0.0.1" | "::1");
+
+    let environment = match binds.first()?.port.environment {
+        NetworkEnvironment::Windows => "Windows",
+        NetworkEnvironment::Wsl => "WSL",
+    };
+
+    let (summary, explanation) = match (v4.as_slice(), v6.as_slice()) {
+        (v4, []) if v4.iter().any(is_localhost) => (
+            format!("Port {port} is listened on over IPv4 only in {environment}"),
+            format!(
+                "127.0.0.1:{port} works while localhost:{port} fails for clients that resolve \
+                 localhost to ::1 first (Node.js, curl in WSL). Listen on ::1 or :: too, or \
+                 connect to 127.0.0.1."
+            ),
+        ),
+        ([], v6) if v6.iter().any(is_localhost) => (
+            format!("Port {port} is listened on over IPv6 only in {environment}"),
+            format!(
+                "[::1]:{port} works while 127.0.0.1:{port} fails, as does localhost:{port} for \
+                 clients that resolve it to 127.0.0.1. Listen on 127.0.0.1 or 0.0.0.0 too, or \
+                 connect to [::1]."
+            ),
+        ),
+        ([first_v4, ..], [first_v6, ..])
+            if v4.iter().all(|bind| bind.family == Family::V4)
+                && v6.iter().all(|bind| bind.family == Family::V6)
+                && !first_v4.same_owner(first_v6) =>
+        {
+            (
+                format!("Port {port} is held per address family in {environment}"),
+                format!(
+                    "127.0.0.1:{port} reaches {} while [::1]:{port} reaches {}, so \
+                     localhost:{port} reaches either depending on how the client resolves it.",
+                    port_owner(first_v4.port),
+                    port_owner(first_v6.port)
+                ),
+            )
+        }
+        _ => return None,
+    };
+
+    Some((
+        ConflictKind::AddressFamily,
+        summary,
+        explanation,
+        binds.iter().map(|bind| bind.port).collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::ContainerPortOwner;
+
+    fn port(
+        environment: NetworkEnvironment,
+        protocol: &str,
+        network: &str,
+        process: &str,
+        pid: &str,
+    ) -> PortInfo {
+        PortInfo {
+            process_id: pid.to_string(),
+            process_name: process.to_string(),
+            protocol: protocol.to_string(),
+            port: network.rsplit(':').next().unwrap().to_string(),
+            direction: "Listening".to_string(),
+            network: network.to_string(),
+            environment,
+            container: None,
+        }
+    }
+
+    fn published(mut port: PortInfo, container: &str, daemon: DockerDaemonKind) -> PortInfo {
+        port.container = Some(ContainerPortOwner {
+            container_name: container.to_string(),
+            daemon_kind: Some(daemon),
+            ..Default::default()
+        });
+        port
+    }
+
+    fn windows(network: &str, process: &str, pid: &str) -> PortInfo {
+        port(NetworkEnvironment::Windows, "TCP", network, process, pid)
+    }
+
+    fn wsl(network: &str, process: &str, pid: &str) -> PortInfo {
+        port(NetworkEnvironment::Wsl, "TCP", network, process, pid)
+    }
+
+    fn kinds(ports: &[PortInfo]) -> Vec<ConflictKind> {
+        find_port_conflicts(ports)
+            .into_iter()
+            .map(|conflict| conflict.kind)
+            .collect()
+    }
+
+    #[test]
+    fn windows_listener_takes_localhost_from_wsl() {
+        let conflicts = find_port_conflicts(&[
+            windows("127.0.0.1:3000", "node.exe", "10"),
+            windows("[::1]:3000", "node.exe", "10"),
+            wsl("0.0.0.0:3000", "python3", "20"),
+        ]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::WslAndWindows);
+        assert!(conflicts[0]
+            .explanation
+            .starts_with("localhost:3000 on Windows reaches node.exe (PID 10) in Windows"));
+        assert_eq!(conflicts[0].listeners.len(), 3);
+    }
+
+    #[test]
+    fn relay_on_loopback_takes_localhost_from_wildcard_windows_listener() {
+        let conflicts = find_port_conflicts(&[
+            windows("0.0.0.0:3000", "node.exe", "10"),
+            windows("[::]:3000", "node.exe", "10"),
+            windows("127.0.0.1:3000", "wslrelay.exe", "11"),
+            wsl("0.0.0.0:3000", "python3", "20"),
+        ]);
+        assert_eq!(
+            conflicts
+                .iter()
+                .map(|conflict| conflict.kind)
+                .collect::<Vec<_>>(),
+            [ConflictKind::WslAndWindows]
+        );
+        assert!(conflicts[0].explanation.starts_with(
+            "localhost:3000 on Windows reaches python3 (PID 20) in WSL through WSL's localhost \
+             forwarding"
+        ));
+        assert_eq!(conflicts[0].listeners.len(), 4);
+    }
+
+    #[test]
+    fn relay_on_loopback_alone_is_no_conflict() {
+        assert!(kinds(&[
+            windows("127.0.0.1:3000", "wslrelay.exe", "11"),
+            windows("[::1]:3000", "wslrelay.exe", "11"),
+            wsl("0.0.0.0:3000", "python3", "20"),
+            wsl("[::]:3000", "python3", "20"),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn udp_is_not_relayed_to_wsl() {
+        assert!(kinds(&[
+            port(
+                NetworkEnvironment::Windows,
+                "UDP",
+                "0.0.0.0:5353",
+                "svchost.exe",
+                "3"
+            ),
+            port(
+                NetworkEnvironment::Wsl,
+                "UDP",
+                "0.0.0.0:5353",
+                "avahi-daemon",
+                "4"
+            ),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn specific_bind_shadows_wildcard_bind() {
+        let conflicts = find_port_conflicts(&[
+            windows("0.0.0.0:80", "nginx.exe", "1"),
+            windows("192.168.1.5:80", "httpd.exe", "2"),
+        ]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Shadowed);
+        assert_eq!(conflicts[0].summary, "192.168.1.5:80 shadows 0.0.0.0:80");
+        assert_eq!(
+            conflicts[0].explanation,
+            "Connections to 192.168.1.5:80 reach httpd.exe (PID 2) in Windows, while every other \
+             address reaches nginx.exe (PID 1) in Windows."
+        );
+    }
+
+    #[test]
+    fn containers_publishing_the_same_port() {
+        let conflicts = find_port_conflicts(&[
+            published(
+                wsl("0.0.0.0:8080", "docker-proxy", "30"),
+                "web",
+                DockerDaemonKind::Engine,
+            ),
+            published(
+                wsl("10.0.0.2:8080", "docker-proxy", "31"),
+                "api",
+                DockerDaemonKind::Engine,
+            ),
+        ]);
+        let duplicate = conflicts
+            .iter()
+            .find(|conflict| conflict.kind == ConflictKind::DuplicatePublish)
+            .unwrap();
+        assert_eq!(duplicate.summary, "Port 8080 is published by api, web");
+        assert_eq!(
+            duplicate.explanation,
+            "The port is published on 0.0.0.0:8080 by container web (Docker Engine) in WSL, \
+             10.0.0.2:8080 by container api (Docker Engine) in WSL, so which container answers \
+             depends on which address the client uses."
+        );
+    }
+
+    #[test]
+    fn containers_publishing_on_both_sides() {
+        let conflicts = find_port_conflicts(&[
+            published(
+                windows("0.0.0.0:8080", "com.docker.backend.exe", "40"),
+                "web",
+                DockerDaemonKind::Desktop,
+            ),
+            published(
+                wsl("[::]:8080", "docker-proxy", "41"),
+                "api",
+                DockerDaemonKind::Engine,
+            ),
+        ]);
+        let duplicate = conflicts
+            .iter()
+            .find(|conflict| conflict.kind == ConflictKind::DuplicatePublish)
+            .unwrap();
+        assert!(duplicate
+            .explanation
+            .contains("[::]:8080 by container api (Docker Engine) in WSL"));
+        assert!(duplicate
+            .explanation
+            .ends_with("depends on where the client runs and which address it uses."));
+    }
+
+    #[test]
+    fn docker_desktop_publishing_on_both_sides_is_no_conflict() {
+        assert!(kinds(&[
+            published(
+                windows("0.0.0.0:8080", "com.docker.backend.exe", "40"),
+                "web",
+                DockerDaemonKind::Desktop,
+            ),
+            published(
+                wsl("0.0.0.0:8080", "docker-proxy", "41"),
+                "web",
+                DockerDaemonKind::Desktop,
+            ),
+        ])
+        .is_empty());
+        // The proxy as seen from another distro, without an owning process
+        assert!(kinds(&[
+            published(
+                windows("0.0.0.0:8080", "com.docker.backend.exe", "40"),
+                "web",
+                DockerDaemonKind::Desktop,
+            ),
+            wsl("0.0.0.0:8080", "", ""),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn ipv4_only_loopback_listener() {
+        let conflicts = find_port_conflicts(&[wsl("127.0.0.1:5173", "node", "50")]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::AddressFamily);
+        assert_eq!(
+            conflicts[0].summary,
+            "Port 5173 is listened on over IPv4 only in WSL"
+        );
+    }
+
+    #[test]
+    fn ipv6_only_loopback_listener() {
+        let conflicts = find_port_conflicts(&[windows("[::1]:5173", "node.exe", "50")]);
+        assert_eq!(
+            conflicts[0].summary,
+            "Port 5173 is listened on over IPv6 only in Windows"
+        );
+    }
+
+    #[test]
+    fn port_held_per_address_family() {
+        let conflicts = find_port_conflicts(&[
+            wsl("0.0.0.0:3000", "node", "60"),
+            wsl("[::]:3000", "python3", "61"),
+        ]);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::AddressFamily);
+        assert_eq!(
+            conflicts[0].summary,
+            "Port 3000 is held per address family in WSL"
+        );
+    }
+
+    #[test]
+    fn same_process_on_ipv4_and_ipv6_is_no_conflict() {
+        assert!(kinds(&[
+            wsl("0.0.0.0:3000", "node", "60"),
+            wsl("[::]:3000", "node", "60"),
+            windows("127.0.0.1:9000", "app.exe", "70"),
+            windows("[::1]:9000", "app.exe", "70"),
+        ])
+        .is_empty());
+    }
+}
@@ -0,0 +1,869 @@
+//! Docker Engine API client
+//!
+//! This module talks to the Docker daemon over its HTTP API instead of spawning the docker CLI.
+//! The endpoints are those of the docker contexts (see [`contexts`]), with `DOCKER_HOST` or the
+//! platform default socket as the default context.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Docker API version requested by the client (supported by Docker Engine 20.10 and later)
+const API_VERSION: &str = "v1.41";
+
+/// Default timeout for connecting to and reading from the daemon
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Represents how to reach a Docker daemon
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockerEndpoint {
+    /// Unix domain socket, e.g. `unix:///var/run/docker.sock`
+    Unix(PathBuf),
+    /// Plain TCP, e.g. `tcp://127.0.0.1:2375`
+    Tcp(String),
+    /// Windows named pipe, e.g. `npipe:////./pipe/docker_engine`
+    NamedPipe(String),
+}
+
+impl DockerEndpoint {
+    /// Parse a docker host string as used by `DOCKER_HOST` and docker contexts
+    pub fn parse(host: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(path) = host.strip_prefix("unix://") {
+            Ok(DockerEndpoint::Unix(PathBuf::from(path)))
+        } else if let Some(address) = host
+            .strip_prefix("tcp://")
+            .or_else(|| host.strip_prefix("http://"))
+        {
+            Ok(DockerEndpoint::Tcp(
+                address.trim_end_matches('/').to_string(),
+            ))
+        } else if let Some(pipe) = host.strip_prefix("npipe://") {
+            // npipe:////./pipe/docker_engine -> \\.\pipe\docker_engine
+            Ok(DockerEndpoint::NamedPipe(pipe.replace('/', "\\")))
+        } else {
+            Err(format!("Unsupported docker host: {host}").into())
+        }
+    }
+}
+
+impl Default for DockerEndpoint {
+    fn default() -> Self {
+        if cfg!(windows) {
+            DockerEndpoint::NamedPipe(r"\\.\pipe\docker_engine".to_string())
+        } else {
+            DockerEndpoint::Unix(PathBuf::from("/var/run/docker.sock"))
+        }
+    }
+}
+
+impl std::fmt::Display for DockerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DockerEndpoint::Unix(path) => write!(f, "unix://{}", path.display()),
+            DockerEndpoint::Tcp(address) => write!(f, "tcp://{address}"),
+            DockerEndpoint::NamedPipe(pipe) => write!(f, "npipe://{}", pipe.replace('\\', "/")),
+        }
+    }
+}
+
+/// Docker CLI configuration directory (`DOCKER_CONFIG` or `~/.docker`)
+fn docker_config_dir() -> Option<PathBuf> {
+    if let Ok(dir) = std::env::var("DOCKER_CONFIG") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .ok()
+        .map(|home| PathBuf::from(home).join(".docker"))
+}
+
+/// Represents a docker context and the daemon endpoint it points at
+#[derive(Debug, Clone, PartialEq)]
+pub struct DockerContext {
+    pub name: String,
+    pub endpoint: DockerEndpoint,
+}
+
+/// All docker contexts, starting with the default context
+///
+/// The default context uses `DOCKER_HOST` when set and the platform default endpoint otherwise.
+pub fn contexts() -> Vec<DockerContext> {
+    let default_endpoint = std::env::var("DOCKER_HOST")
+        .ok()
+        .and_then(|host| DockerEndpoint::parse(&host).ok())
+        .unwrap_or_default();
+    let mut contexts = vec![DockerContext {
+        name: "default".to_string(),
+        endpoint: default_endpoint,
+    }];
+
+    for meta in context_metadata() {
+        let name = meta.get("Name").and_then(|v| v.as_str());
+        let host = meta
+            .pointer("/Endpoints/docker/Host")
+            .and_then(|v| v.as_str());
+        if let (Some(name), Some(host)) = (name, host) {
+            match DockerEndpoint::parse(host) {
+                Ok(endpoint) => contexts.push(DockerContext {
+                    name: name.to_string(),
+                    endpoint,
+                }),
+                Err(e) => eprintln!("Ignoring docker context {name}: {e}"),
+            }
+        }
+    }
+
+    contexts
+}
+
+/// Metadata of all docker contexts (the parsed `meta.json` files)
+fn context_metadata() -> Vec<serde_json::Value> {
+    let Some(config_dir) = docker_config_dir() else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(config_dir.join("contexts").join("meta")) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| std::fs::read(entry.path().join("meta.json")).ok())
+        .filter_map(|meta| serde_json::from_slice(&meta).ok())
+        .collect()
+}
+
+/// System information of a daemon as returned by `GET /info` (and `docker info --format '{{json .}}'`)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiInfo {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+    /// Host name of the daemon, "docker-desktop" for Docker Desktop
+    #[serde(default)]
+    pub name: String,
+    /// "Docker Desktop" for Docker Desktop, the distribution name for Docker Engine
+    #[serde(default)]
+    pub operating_system: String,
+    #[serde(default)]
+    pub server_version: String,
+}
+
+/// A Docker network as returned by `GET /networks`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiNetwork {
+    pub name: String,
+    pub driver: String,
+    pub scope: String,
+    #[serde(rename = "IPAM")]
+    pub ipam: Option<ApiIpam>,
+    pub labels: Option<HashMap<String, String>>,
+}
+
+/// IP address management settings of a network
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiIpam {
+    pub config: Option<Vec<ApiIpamConfig>>,
+}
+
+/// A single subnet of a network
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiIpamConfig {
+    pub subnet: Option<String>,
+}
+
+/// A container as returned by `GET /containers/json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiContainer {
+    pub id: String,
+    pub names: Vec<String>,
+    pub image: String,
+    pub status: String,
+    #[serde(default)]
+    pub ports: Vec<ApiPort>,
+    pub labels: Option<HashMap<String, String>>,
+    /// Only the `Networks` map is reported by the list endpoint
+    pub network_settings: Option<ApiNetworkSettings>,
+}
+
+/// A port of a container as returned by `GET /containers/json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiPort {
+    #[serde(rename = "IP")]
+    pub ip: Option<String>,
+    pub private_port: u16,
+    pub public_port: Option<u16>,
+    #[serde(rename = "Type")]
+    pub protocol: String,
+}
+
+/// A container as returned by `GET /containers/{id}/json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiContainerInspect {
+    pub network_settings: ApiNetworkSettings,
+}
+
+/// Network settings of an inspected container
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiNetworkSettings {
+    #[serde(default)]
+    pub networks: HashMap<String, ApiEndpointSettings>,
+    /// Container port ("80/tcp") to host bindings; `None` for exposed but unpublished ports
+    pub ports: Option<HashMap<String, Option<Vec<ApiPortBinding>>>>,
+}
+
+/// A container's endpoint on one network
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiEndpointSettings {
+    #[serde(rename = "IPAddress", default)]
+    pub ip_address: String,
+    #[serde(rename = "GlobalIPv6Address", default)]
+    pub global_ipv6_address: String,
+    #[serde(default)]
+    pub mac_address: String,
+    #[serde(default)]
+    pub gateway: String,
+    pub aliases: Option<Vec<String>>,
+    #[serde(rename = "DNSNames")]
+    pub dns_names: Option<Vec<String>>,
+}
+
+/// A host binding of a published port
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiPortBinding {
+    #[serde(default)]
+    pub host_ip: String,
+    #[serde(default)]
+    pub host_port: String,
+}
+
+/// An event as returned by `GET /events` (and `docker events --format '{{json .}}'`)
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiEvent {
+    #[serde(rename = "Type")]
+    pub event_type: String,
+    #[serde(rename = "Action")]
+    pub action: String,
+    #[serde(rename = "Actor")]
+    pub actor: ApiEventActor,
+    /// Unix timestamp in seconds
+    #[serde(default)]
+    pub time: i64,
+}
+
+/// The object an event is about
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+pub struct ApiEventActor {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+}
+
+/// A readable and writable connection to the daemon
+trait Connection: Read + Write + Send {}
+impl<T: Read + Write + Send> Connection for T {}
+
+/// Client for the Docker Engine HTTP API
+#[derive(Debug, Clone)]
+pub struct DockerClient {
+    endpoint: DockerEndpoint,
+    timeout: Duration,
+}
+
+impl DockerClient {
+    /// Create a client for a specific endpoint
+    pub fn new(endpoint: DockerEndpoint) -> Self {
+        DockerClient {
+            endpoint,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Use `timeout` for connecting and for reading responses instead of the default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Get system information of the daemon
+    pub fn info(&self) -> Result<ApiInfo, Box<dyn std::error::Error>> {
+        self.get_json("/info")
+    }
+
+    /// List all networks
+    pub fn networks(&self) -> Result<Vec<ApiNetwork>, Box<dyn std::error::Error>> {
+        self.get_json("/networks")
+    }
+
+    /// Inspect a single network by ID or name
+    pub fn inspect_network(&self, id: &str) -> Result<ApiNetwork, Box<dyn std::error::Error>> {
+        self.get_json(&format!("/networks/{}", url_encode(id)))
+    }
+
+    /// List running containers, optionally only those connected to `network`
+    pub fn containers(
+        &self,
+        network: Option<&str>,
+    ) -> Result<Vec<ApiContainer>, Box<dyn std::error::Error>> {
+        match network {
+            Some(network) => {
+                let filters = serde_json::json!({ "network": [network] }).to_string();
+                self.get_json(&format!(
+                    "/containers/json?filters={}",
+                    url_encode(&filters)
+                ))
+            }
+            None => self.get_json("/containers/json"),
+        }
+    }
+
+    /// Inspect a single container
+    pub fn inspect_container(
+        &self,
+        id: &str,
+    ) -> Result<ApiContainerInspect, Box<dyn std::error::Error>> {
+        self.get_json(&format!("/containers/{}/json", url_encode(id)))
+    }
+
+    /// Stream daemon events matching `filters`
+    ///
+    /// Blocks and calls `on_event` for every event until the stream ends or `on_event` returns
+    /// `false`.
+    pub fn events(
+        &self,
+        filters: &serde_json::Value,
+        mut on_event: impl FnMut(ApiEvent) -> bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let path = format!("/events?filters={}", url_encode(&filters.to_string()));
+        let mut reader = self.send_request(&path, None)?;
+        let (status, headers) = read_response_head(&mut reader)?;
+        if !(200..300).contains(&status) {
+            let body = read_response_body(&mut reader, &headers)?;
+            let body = String::from_utf8_lossy(&body);
+            return Err(format!("Docker API returned {status} for /events: {body}").into());
+        }
+
+        // Events are newline-delimited JSON objects; chunk boundaries need not match them
+        let chunked = is_chunked(&headers);
+        let mut pending = Vec::new();
+        loop {
+            let data = if chunked {
+                match read_chunk(&mut reader)? {
+                    Some(chunk) => chunk,
+                    None => return Ok(()),
+                }
+            } else {
+                let mut line = Vec::new();
+                if reader.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(());
+                }
+                line
+            };
+            pending.extend_from_slice(&data);
+
+            while let Some(newline) = pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = pending.drain(..=newline).collect();
+                if line.iter().all(|b| b.is_ascii_whitespace()) {
+                    continue;
+                }
+                match serde_json::from_slice::<ApiEvent>(&line) {
+                    Ok(event) => {
+                        if !on_event(event) {
+                            return Ok(());
+                        }
+                    }
+                    Err(e) => eprintln!("Could not parse Docker event: {e}"),
+                }
+            }
+        }
+    }
+
+    /// Send a GET request and deserialize the JSON response body
+    fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let (status, body) = self.get(path)?;
+        if !(200..300).contains(&status) {
+            // Errors are returned as {"message": "..."}
+            let message = serde_json::from_slice::<serde_json::Value>(&body)
+                .ok()
+                .and_then(|v| v.get("message").and_then(|m| m.as_str()).map(String::from))
+                .unwrap_or_else(|| String::from_utf8_lossy(&body).to_string());
+            return Err(format!("Docker API returned {status} for {path}: {message}").into());
+        }
+        Ok(serde_json::from_slice(&body)?)
+    }
+
+    /// Send a GET request and return the status code and the complete body
+    fn get(&self, path: &str) -> Result<(u16, Vec<u8>), Box<dyn std::error::Error>> {
+        let mut reader = self.send_request(path, Some(self.timeout))?;
+        let (status, headers) = read_response_head(&mut reader)?;
+        let body = read_response_body(&mut reader, &headers)?;
+        Ok((status, body))
+    }
+
+    /// Open a connection, send a GET request and return a reader positioned at the response
+    fn send_request(
+        &self,
+        path: &str,
+        read_timeout: Option<Duration>,
+    ) -> Result<BufReader<Box<dyn Read + Send>>, Box<dyn std::error::Error>> {
+        let mut connection = self.connect(read_timeout)?;
+        let request = format!(
+            "GET /{API_VERSION}{path} HTTP/1.1\r\nHost: docker\r\nUser-Agent: wslnetman\r\nConnection: close\r\n\r\n"
+        );
+        connection.write_all(request.as_bytes())?;
+        connection.flush()?;
+
+        // Named pipes have no read timeout of their own. The reader thread only starts once the
+        // request is written, as a pending read on a synchronous pipe would block the write.
+        let reader: Box<dyn Read + Send> = match &self.endpoint {
+            DockerEndpoint::NamedPipe(_) => Box::new(TimedReader::spawn(connection, read_timeout)),
+            _ => Box::new(connection),
+        };
+        Ok(BufReader::new(reader))
+    }
+
+    /// Open a connection to the daemon
+    ///
+    /// `read_timeout` of `None` blocks indefinitely, which is what streaming endpoints need.
+    fn connect(
+        &self,
+        read_timeout: Option<Duration>,
+    ) -> Result<Box<dyn Connection>, Box<dyn std::error::Error>> {
+        match &self.endpoint {
+            DockerEndpoint::Tcp(address) => {
+                let socket_address = address
+                    .to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| format!("Could not resolve docker host {address}"))?;
+                let stream = TcpStream::connect_timeout(&socket_address, self.timeout)?;
+                stream.set_read_timeout(read_timeout)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(unix)]
+            DockerEndpoint::Unix(path) => {
+                let stream = std::os::unix::net::UnixStream::connect(path)?;
+                stream.set_read_timeout(read_timeout)?;
+                Ok(Box::new(stream))
+            }
+            #[cfg(not(unix))]
+            DockerEndpoint::Unix(path) => Err(format!(
+                "Unix socket {} is not reachable from this platform",
+                path.display()
+            )
+            .into()),
+            DockerEndpoint::NamedPipe(pipe) => {
+                // Named pipes open like regular files on Windows
+                let file = std::fs::OpenOptions::new()
+                    .read(true)
+                    .write(true)
+                    .open(pipe)?;
+                Ok(Box::new(file))
+            }
+        }
+    }
+}
+
+/// Reader that enforces a read timeout on connections without native support for one
+///
+/// Reads happen on a helper thread. After a timeout that thread stays blocked until the daemon
+/// answers or closes the connection, and then exits because the receiving side is gone.
+struct TimedReader {
+    chunks: mpsc::Receiver<std::io::Result<Vec<u8>>>,
+    timeout: Option<Duration>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl TimedReader {
+    fn spawn(mut inner: impl Read + Send + 'static, timeout: Option<Duration>) -> Self {
+        let (sender, chunks) = mpsc::sync_channel(4);
+        std::thread::spawn(move || {
+            let mut buffer = [0u8; 8192];
+            loop {
+                let chunk = match inner.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => Ok(buffer[..n].to_vec()),
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => Err(e),
+                };
+                let failed = chunk.is_err();
+                if sender.send(chunk).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        TimedReader {
+            chunks,
+            timeout,
+            buffer: Vec::new(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for TimedReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.position == self.buffer.len() {
+            let chunk = match self.timeout {
+                Some(timeout) => match self.chunks.recv_timeout(timeout) {
+                    Ok(chunk) => Some(chunk),
+                    Err(mpsc::RecvTimeoutError::Timeout) => {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "Timed out waiting for the Docker daemon",
+                        ))
+                    }
+                    Err(mpsc::RecvTimeoutError::Disconnected) => None,
+                },
+                None => self.chunks.recv().ok(),
+            };
+            match chunk {
+                Some(chunk) => {
+                    self.buffer = chunk?;
+                    self.position = 0;
+                }
+                // The connection was closed
+                None => return Ok(0),
+            }
+        }
+
+        let count = buf.len().min(self.buffer.len() - self.position);
+        buf[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Read the status line and headers of an HTTP response
+///
+/// Header names are lowercased.
+fn read_response_head(
+    reader: &mut impl BufRead,
+) -> Result<(u16, HashMap<String, String>), Box<dyn std::error::Error>> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+
+    // Format: "HTTP/1.1 200 OK"
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or_else(|| format!("Invalid HTTP status line: {}", status_line.trim()))?;
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    Ok((status, headers))
+}
+
+/// Read an HTTP response body using `Content-Length`, chunked encoding, or until EOF
+fn read_response_body(
+    reader: &mut impl BufRead,
+    headers: &HashMap<String, String>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut body = Vec::new();
+
+    if is_chunked(headers) {
+        while let Some(chunk) = read_chunk(reader)? {
+            body.extend_from_slice(&chunk);
+        }
+    } else if let Some(length) = headers
+        .get("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+    {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    } else {
+        reader.read_to_end(&mut body)?;
+    }
+
+    Ok(body)
+}
+
+/// Whether the response uses chunked transfer encoding
+fn is_chunked(headers: &HashMap<String, String>) -> bool {
+    headers
+        .get("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+}
+
+/// Read one chunk of a chunked body; returns `None` after the last chunk
+fn read_chunk(reader: &mut impl BufRead) -> Result<Option<Vec<u8>>, Box<dyn std::error::Error>> {
+    let mut size_line = String::new();
+    if reader.read_line(&mut size_line)? == 0 {
+        return Ok(None);
+    }
+
+    // Format: "1a3f" optionally followed by ";extension"
+    let size_hex = size_line.trim().split(';').next().unwrap_or("");
+    let size = usize::from_str_radix(size_hex, 16)
+        .map_err(|_| format!("Invalid chunk size: {}", size_line.trim()))?;
+    if size == 0 {
+        return Ok(None);
+    }
+
+    let mut chunk = vec![0; size];
+    reader.read_exact(&mut chunk)?;
+
+    // Each chunk is followed by CRLF
+    let mut crlf = String::new();
+    reader.read_line(&mut crlf)?;
+
+    Ok(Some(chunk))
+}
+
+/// Percent-encode a string for use in a URL path or query
+fn url_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::JoinHandle;
+
+    /// Serve one canned response per connection and return the request lines that were received
+    fn serve(responses: Vec<String>) -> (DockerEndpoint, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = DockerEndpoint::Tcp(listener.local_addr().unwrap().to_string());
+        let handle = std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for response in responses {
+                let (stream, _) = listener.accept().unwrap();
+                requests.push(answer(stream, &response));
+            }
+            requests
+        });
+        (endpoint, handle)
+    }
+
+    /// Read a request head from `stream`, write `response` and return the request line
+    fn answer(mut stream: impl Read + Write, response: &str) -> String {
+        let mut reader = BufReader::new(&mut stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line).unwrap() == 0 || line.trim_end().is_empty() {
+                break;
+            }
+        }
+        stream.write_all(response.as_bytes()).unwrap();
+        request_line.trim_end().to_string()
+    }
+
+    fn json_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    fn chunked_response(chunks: &[&str]) -> String {
+        let mut response =
+            String::from("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n");
+        for chunk in chunks {
+            response.push_str(&format!("{:x}\r\n{chunk}\r\n", chunk.len()));
+        }
+        response.push_str("0\r\n\r\n");
+        response
+    }
+
+    const NETWORKS: &str = r#"[{"Name":"bridge","Id":"f2de39df4171","Driver":"bridge","Scope":"local",
+        "IPAM":{"Driver":"default","Config":[{"Subnet":"172.17.0.0/16","Gateway":"172.17.0.1"}]}}]"#;
+
+    const CONTAINERS: &str = r#"[{"Id":"8dfafdbc3a40","Names":["/web"],"Image":"nginx:latest",
+        "State":"running","Status":"Up 2 minutes",
+        "Ports":[{"IP":"0.0.0.0","PrivatePort":80,"PublicPort":8080,"Type":"tcp"}],
+        "Labels":{"com.docker.compose.project":"shop"}}]"#;
+
+    #[test]
+    fn lists_networks() {
+        let (endpoint, server) = serve(vec![json_response("200 OK", NETWORKS)]);
+        let networks = DockerClient::new(endpoint).networks().unwrap();
+
+        assert_eq!(server.join().unwrap(), ["GET /v1.41/networks HTTP/1.1"]);
+        assert_eq!(networks.len(), 1);
+        assert_eq!(networks[0].name, "bridge");
+        assert_eq!(networks[0].driver, "bridge");
+        let config = networks[0].ipam.clone().unwrap().config.unwrap();
+        assert_eq!(config[0].subnet.as_deref(), Some("172.17.0.0/16"));
+    }
+
+    #[test]
+    fn lists_containers_from_a_chunked_response() {
+        let (first, rest) = CONTAINERS.split_at(40);
+        let (endpoint, server) = serve(vec![chunked_response(&[first, rest])]);
+        let containers = DockerClient::new(endpoint).containers(None).unwrap();
+
+        assert_eq!(
+            server.join().unwrap(),
+            ["GET /v1.41/containers/json HTTP/1.1"]
+        );
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].names, ["/web"]);
+        assert_eq!(containers[0].ports[0].public_port, Some(8080));
+    }
+
+    #[test]
+    fn filters_containers_by_network() {
+        let (endpoint, server) = serve(vec![json_response("200 OK", "[]")]);
+        DockerClient::new(endpoint)
+            .containers(Some("bridge"))
+            .unwrap();
+
+        let expected = format!(
+            "GET /v1.41/containers/json?filters={} HTTP/1.1",
+            url_encode(r#"{"network":["bridge"]}"#)
+        );
+        assert_eq!(server.join().unwrap(), [expected]);
+    }
+
+    #[test]
+    fn reports_api_errors() {
+        let (endpoint, server) = serve(vec![json_response(
+            "404 Not Found",
+            r#"{"message":"network missing not found"}"#,
+        )]);
+        let error = DockerClient::new(endpoint)
+            .inspect_network("missing")
+            .unwrap_err();
+        server.join().unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Docker API returned 404 for /networks/missing: network missing not found"
+        );
+    }
+
+    #[test]
+    fn streams_events_split_across_chunks() {
+        let start = r#"{"Type":"container","Action":"start","Actor":{"ID":"8dfafdbc3a40","Attributes":{"name":"web"}},"time":1700000000}"#;
+        let stop = r#"{"Type":"container","Action":"stop","Actor":{"ID":"8dfafdbc3a40","Attributes":{"name":"web"}},"time":1700000005}"#;
+        let stream = format!("{start}\n{stop}\n");
+        let (first, rest) = stream.split_at(start.len() / 2);
+        let (endpoint, server) = serve(vec![chunked_response(&[first, rest])]);
+
+        let mut events = Vec::new();
+        DockerClient::new(endpoint)
+            .events(&serde_json::json!({ "type": ["container"] }), |event| {
+                events.push(event);
+                true
+            })
+            .unwrap();
+
+        assert!(server.join().unwrap()[0].starts_with("GET /v1.41/events?filters="));
+        let actions: Vec<&str> = events.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["start", "stop"]);
+        assert_eq!(events[0].actor.attributes["name"], "web");
+        assert_eq!(events[1].time, 1700000005);
+    }
+
+    #[test]
+    fn stops_streaming_events_when_asked() {
+        let event = r#"{"Type":"network","Action":"connect","Actor":{"ID":"f2de39df4171"}}"#;
+        let (endpoint, server) = serve(vec![chunked_response(&[
+            &format!("{event}\n"),
+            &format!("{event}\n"),
+        ])]);
+
+        let mut count = 0;
+        DockerClient::new(endpoint)
+            .events(&serde_json::json!({}), |_| {
+                count += 1;
+                false
+            })
+            .unwrap();
+        server.join().unwrap();
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn times_out_when_the_daemon_does_not_answer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = DockerEndpoint::Tcp(listener.local_addr().unwrap().to_string());
+
+        let result = DockerClient::new(endpoint)
+            .with_timeout(Duration::from_millis(200))
+            .info();
+
+        assert!(result.is_err());
+        drop(listener);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timed_reader_times_out_and_forwards_data() {
+        let (mut writer, reader) = std::os::unix::net::UnixStream::pair().unwrap();
+        let mut reader = TimedReader::spawn(reader, Some(Duration::from_millis(100)));
+
+        let mut buffer = [0u8; 16];
+        let error = reader.read(&mut buffer).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+
+        writer.write_all(b"HTTP/1.1").unwrap();
+        drop(writer);
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"HTTP/1.1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn lists_networks_over_a_unix_socket() {
+        let path =
+            std::env::temp_dir().join(format!("wslnetman-docker-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            answer(stream, &json_response("200 OK", NETWORKS))
+        });
+
+        let networks = DockerClient::new(DockerEndpoint::Unix(path.clone()))
+            .networks()
+            .unwrap();
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(networks[0].name, "bridge");
+    }
+}
@@ -0,0 +1,250 @@
+//! Docker Compose project grouping
+//!
+//! This module groups containers and networks by the labels Docker Compose puts on them,
+//! giving a per-project view of services, the networks they share and the ports they publish.
+
+use super::{DockerContainer, DockerNetwork, PortMapping};
+use std::collections::BTreeMap;
+
+/// Label set by Docker Compose on the containers and networks of a project
+pub const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+
+/// Label set by Docker Compose on the containers of a service
+pub const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+
+/// Represents a Docker Compose project with its services and networks
+#[derive(Debug, Clone)]
+pub struct ComposeProject {
+    pub name: String,
+    pub services: Vec<ComposeService>,
+    pub networks: Vec<ComposeNetwork>,
+}
+
+/// Represents a service of a Docker Compose project
+#[derive(Debug, Clone)]
+pub struct ComposeService {
+    pub name: String,
+    pub containers: Vec<String>,
+    pub networks: Vec<String>,
+    pub published_ports: Vec<PortMapping>,
+}
+
+/// Represents a network used by a Docker Compose project, with the services attached to it
+#[derive(Debug, Clone)]
+pub struct ComposeNetwork {
+    pub name: String,
+    pub services: Vec<String>,
+}
+
+/// Group containers and networks by Docker Compose project
+///
+/// Projects are sorted by name. A project's networks are the networks labelled with the project
+/// plus any other network its services are attached to (e.g. an external network).
+pub fn group_by_project(
+    networks: &[DockerNetwork],
+    containers: &[DockerContainer],
+) -> Vec<ComposeProject> {
+    let mut projects: BTreeMap<String, BTreeMap<String, ComposeService>> = BTreeMap::new();
+
+    for container in containers {
+        if container.compose_project.is_empty() {
+            continue;
+        }
+
+        let services = projects
+            .entry(container.compose_project.clone())
+            .or_default();
+        let service = services
+            .entry(container.compose_service.clone())
+            .or_insert_with(|| ComposeService {
+                name: container.compose_service.clone(),
+                containers: Vec::new(),
+                networks: Vec::new(),
+                published_ports: Vec::new(),
+            });
+
+        // Scaled services have several containers
+        service.containers.push(container.name.clone());
+        for network in &container.networks {
+            if !service.networks.contains(network) {
+                service.networks.push(network.clone());
+            }
+        }
+        for mapping in &container.port_mappings {
+            if !service.published_ports.contains(mapping) {
+                service.published_ports.push(mapping.clone());
+            }
+        }
+    }
+
+    // Projects whose networks exist but have no running containers
+    for network in networks {
+        if !network.compose_project.is_empty() {
+            projects.entry(network.compose_project.clone()).or_default();
+        }
+    }
+
+    projects
+        .into_iter()
+        .map(|(name, services)| {
+            let services: Vec<ComposeService> = services.into_values().collect();
+
+            let mut network_names: Vec<String> = networks
+                .iter()
+                .filter(|network| network.compose_project == name)
+                .map(|network| network.name.clone())
+                .collect();
+            for service in &services {
+                for network in &service.networks {
+                    if !network_names.contains(network) {
+                        network_names.push(network.clone());
+                    }
+                }
+            }
+            network_names.sort();
+
+            let project_networks = network_names
+                .into_iter()
+                .map(|network| ComposeNetwork {
+                    services: services
+                        .iter()
+                        .filter(|service| service.networks.contains(&network))
+                        .map(|service| service.name.clone())
+                        .collect(),
+                    name: network,
+                })
+                .collect();
+
+            ComposeProject {
+                name,
+                services,
+                networks: project_networks,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::ContainerRuntime;
+
+    fn network(name: &str, project: &str) -> DockerNetwork {
+        DockerNetwork {
+            name: name.to_string(),
+            driver: "bridge".to_string(),
+            scope: "local".to_string(),
+            subnet: String::new(),
+            compose_project: project.to_string(),
+            runtime: ContainerRuntime::Docker,
+            daemon: None,
+        }
+    }
+
+    fn container(
+        name: &str,
+        project: &str,
+        service: &str,
+        networks: &[&str],
+        ports: &[&str],
+    ) -> DockerContainer {
+        DockerContainer {
+            name: name.to_string(),
+            image: format!("{service}:latest"),
+            status: "running".to_string(),
+            port_mappings: ports.iter().map(|port| mapping(port)).collect(),
+            id: String::new(),
+            endpoint: None,
+            networks: networks.iter().map(|network| network.to_string()).collect(),
+            compose_project: project.to_string(),
+            compose_service: service.to_string(),
+            pod: String::new(),
+            runtime: ContainerRuntime::Docker,
+            daemon: None,
+        }
+    }
+
+    fn mapping(host_port: &str) -> PortMapping {
+        PortMapping {
+            host_ip: "0.0.0.0".to_string(),
+            host_port: host_port.to_string(),
+            container_port: "80".to_string(),
+            protocol: "tcp".to_string(),
+        }
+    }
+
+    fn names<T>(items: &[T], name: impl Fn(&T) -> &str) -> Vec<&str> {
+        items.iter().map(name).collect()
+    }
+
+    #[test]
+    fn skips_containers_without_compose_labels() {
+        let projects = group_by_project(
+            &[network("bridge", "")],
+            &[container("adminer", "", "", &["bridge"], &["8081"])],
+        );
+        assert!(projects.is_empty());
+    }
+
+    #[test]
+    fn lists_networks_shared_between_projects_in_each() {
+        let networks = [
+            network("shop_default", "shop"),
+            network("proxy", "gateway"),
+            network("reports_default", "reports"),
+        ];
+        let containers = [
+            container("gateway-traefik-1", "gateway", "traefik", &["proxy"], &[]),
+            container("shop-web-1", "shop", "web", &["shop_default", "proxy"], &[]),
+            container("shop-db-1", "shop", "db", &["shop_default"], &[]),
+        ];
+        let projects = group_by_project(&networks, &containers);
+
+        // Sorted by name, including the project that only has a network left
+        assert_eq!(
+            names(&projects, |project| &project.name),
+            ["gateway", "reports", "shop"]
+        );
+        let gateway = &projects[0];
+        assert_eq!(names(&gateway.networks, |network| &network.name), ["proxy"]);
+        assert_eq!(gateway.networks[0].services, ["traefik"]);
+        assert!(projects[1].services.is_empty());
+        assert_eq!(
+            names(&projects[1].networks, |network| &network.name),
+            ["reports_default"]
+        );
+
+        // The external network only lists the project's own services
+        let shop = &projects[2];
+        assert_eq!(
+            names(&shop.services, |service| &service.name),
+            ["db", "web"]
+        );
+        assert_eq!(
+            names(&shop.networks, |network| &network.name),
+            ["proxy", "shop_default"]
+        );
+        assert_eq!(shop.networks[0].services, ["web"]);
+        assert_eq!(shop.networks[1].services, ["db", "web"]);
+    }
+
+    #[test]
+    fn aggregates_the_ports_of_scaled_services() {
+        let containers = [
+            container("api-worker-1", "api", "worker", &["api_default"], &["8080"]),
+            container(
+                "api-worker-2",
+                "api",
+                "worker",
+                &["api_default"],
+                &["8080", "8081"],
+            ),
+        ];
+        let projects = group_by_project(&[network("api_default", "api")], &containers);
+
+        let worker = &projects[0].services[0];
+        assert_eq!(worker.containers, ["api-worker-1", "api-worker-2"]);
+        assert_eq!(worker.networks, ["api_default"]);
+        assert_eq!(worker.published_ports, [mapping("8080"), mapping("8081")]);
+    }
+}
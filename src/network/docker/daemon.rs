@@ -0,0 +1,192 @@
+//! Docker daemon detection
+//!
+//! A machine can run Docker Desktop, whose daemon lives in the `docker-desktop` WSL distro and
+//! publishes ports on Windows through `com.docker.backend.exe`, and Docker Engine installed in a
+//! regular distro, whose ports are held by `docker-proxy` and forwarded to Windows by wslrelay.
+//! Both can be in use at once through docker contexts. This module finds the daemons behind the
+//! docker contexts and tells the two setups apart.
+
+use super::api::{self, ApiInfo, DockerClient, DockerEndpoint};
+use crate::network::wsl;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long detected daemons are reused before the contexts are probed again
+const DETECTION_TTL: Duration = Duration::from_secs(10);
+
+/// Timeout for probing a docker context, so that a stale context does not hold up detection
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Name of the WSL distribution Docker Desktop runs its daemon in
+pub const DESKTOP_DISTRO: &str = "docker-desktop";
+
+/// The kinds of Docker setups the application distinguishes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DockerDaemonKind {
+    /// Docker Desktop with the WSL 2 backend
+    Desktop,
+    /// Docker Engine installed in a WSL distribution
+    Engine,
+}
+
+impl std::fmt::Display for DockerDaemonKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DockerDaemonKind::Desktop => write!(f, "Docker Desktop"),
+            DockerDaemonKind::Engine => write!(f, "Docker Engine"),
+        }
+    }
+}
+
+impl std::str::FromStr for DockerDaemonKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Docker Desktop" => Ok(DockerDaemonKind::Desktop),
+            "Docker Engine" => Ok(DockerDaemonKind::Engine),
+            _ => Err(format!("Unknown Docker daemon: {s}")),
+        }
+    }
+}
+
+/// Represents a reachable Docker daemon and the context it was found through
+#[derive(Debug, Clone)]
+pub struct DockerDaemon {
+    /// Docker context name
+    pub context: String,
+    /// API endpoint, `None` when the daemon is only reachable through the docker CLI in WSL
+    pub endpoint: Option<DockerEndpoint>,
+    pub kind: DockerDaemonKind,
+    pub id: String,
+    pub operating_system: String,
+    pub version: String,
+}
+
+impl DockerDaemon {
+    /// Create a daemon description from its system information
+    fn from_info(context: String, endpoint: Option<DockerEndpoint>, info: ApiInfo) -> Self {
+        // Docker Desktop reports itself as the operating system and names its VM docker-desktop
+        let kind =
+            if info.operating_system.contains("Docker Desktop") || info.name == DESKTOP_DISTRO {
+                DockerDaemonKind::Desktop
+            } else {
+                DockerDaemonKind::Engine
+            };
+
+        DockerDaemon {
+            context,
+            endpoint,
+            kind,
+            id: info.id,
+            operating_system: info.operating_system,
+            version: info.server_version,
+        }
+    }
+
+    /// API client for this daemon, `None` when it must be reached through the docker CLI
+    pub fn client(&self) -> Option<DockerClient> {
+        self.endpoint.clone().map(DockerClient::new)
+    }
+}
+
+impl std::fmt::Display for DockerDaemon {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (context {})", self.kind, self.context)
+    }
+}
+
+/// Detect the Docker daemons behind all docker contexts
+///
+/// Every context is probed with `GET /info`; contexts that point at the same daemon are reported
+/// once. When no daemon answers over the API, the docker CLI in WSL is asked instead. Results
+/// are cached for a few seconds since every Docker query needs them.
+pub fn detect_daemons() -> Vec<DockerDaemon> {
+    static CACHE: Mutex<Option<(Instant, Vec<DockerDaemon>)>> = Mutex::new(None);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((detected_at, daemons)) = cache.as_ref() {
+        if detected_at.elapsed() < DETECTION_TTL {
+            return daemons.clone();
+        }
+    }
+
+    let daemons = probe_daemons();
+    *cache = Some((Instant::now(), daemons.clone()));
+    daemons
+}
+
+/// Probe all docker contexts without using the cache
+fn probe_daemons() -> Vec<DockerDaemon> {
+    let mut daemons: Vec<DockerDaemon> = Vec::new();
+
+    for context in api::contexts() {
+        match DockerClient::new(context.endpoint.clone())
+            .with_timeout(PROBE_TIMEOUT)
+            .info()
+        {
+            Ok(info) => {
+                if daemons.iter().any(|daemon| daemon.id == info.id) {
+                    continue;
+                }
+                daemons.push(DockerDaemon::from_info(
+                    context.name,
+                    Some(context.endpoint),
+                    info,
+                ));
+            }
+            Err(e) => println!(
+                "Docker context {} at {} unavailable ({e})",
+                context.name, context.endpoint
+            ),
+        }
+    }
+
+    if daemons.is_empty() {
+        match detect_daemon_cli() {
+            Ok(daemon) => daemons.push(daemon),
+            Err(e) => println!("Docker CLI in WSL unavailable ({e})"),
+        }
+    }
+
+    daemons
+}
+
+/// Get a detected daemon by context name
+pub fn daemon_for_context(context: &str) -> Option<DockerDaemon> {
+    detect_daemons()
+        .into_iter()
+        .find(|daemon| daemon.context == context)
+}
+
+/// Detect the daemon used by the docker CLI in the default WSL distribution
+fn detect_daemon_cli() -> Result<DockerDaemon, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["-e", "docker", "info", "--format", "{{json .}}"])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("docker info failed: {stderr}").into());
+    }
+    let info: ApiInfo = serde_json::from_slice(&output.stdout)?;
+
+    // The CLI in WSL has its own contexts; report the one it used
+    let context = Command::new("wsl.exe")
+        .args(["-e", "docker", "context", "show"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|context| !context.is_empty())
+        .unwrap_or_else(|| "default".to_string());
+
+    Ok(DockerDaemon::from_info(context, None, info))
+}
+
+/// Whether Docker Desktop's WSL distribution is installed
+pub fn desktop_distro_installed() -> bool {
+    wsl::get_distributions()
+        .map(|distros| distros.iter().any(|distro| distro == DESKTOP_DISTRO))
+        .unwrap_or(false)
+}
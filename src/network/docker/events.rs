@@ -0,0 +1,291 @@
+//! Docker event streaming
+//!
+//! This module subscribes to the event stream of every detected Docker daemon so that the UI
+//! can follow containers and networks as they come and go. It uses the Docker Engine API, or
+//! `docker events` via `wsl.exe` for a daemon that is only reachable through the CLI.
+
+use super::api::ApiEvent;
+use super::daemon::{self, DockerDaemon};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Delay before the first reconnect after the event stream ended or failed
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Upper bound for the reconnect delay, which doubles after every failed attempt
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Interval at which daemons are detected again, to follow daemons that started later
+const DAEMON_RESCAN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Events arriving within this window after the first one are delivered together
+const COALESCE_WINDOW: Duration = Duration::from_millis(500);
+
+/// Object types whose events are streamed
+const EVENT_TYPES: [&str; 2] = ["network", "container"];
+
+/// Actions whose events are streamed
+const EVENT_ACTIONS: [&str; 7] = [
+    "create",
+    "destroy",
+    "connect",
+    "disconnect",
+    "start",
+    "stop",
+    "die",
+];
+
+/// The kinds of Docker events the application reacts to
+#[derive(Debug, Clone, PartialEq)]
+pub enum DockerEventKind {
+    NetworkCreated,
+    NetworkDestroyed,
+    NetworkConnected,
+    NetworkDisconnected,
+    ContainerStarted,
+    ContainerStopped,
+    ContainerDied,
+}
+
+impl std::fmt::Display for DockerEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            DockerEventKind::NetworkCreated => "network create",
+            DockerEventKind::NetworkDestroyed => "network destroy",
+            DockerEventKind::NetworkConnected => "network connect",
+            DockerEventKind::NetworkDisconnected => "network disconnect",
+            DockerEventKind::ContainerStarted => "container start",
+            DockerEventKind::ContainerStopped => "container stop",
+            DockerEventKind::ContainerDied => "container die",
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// Represents a Docker event relevant to the networks and containers views
+#[derive(Debug, Clone)]
+pub struct DockerEvent {
+    pub kind: DockerEventKind,
+    /// Network name for network events, container name for container events
+    pub name: String,
+    /// Network ID for network events, container ID for container events
+    pub id: String,
+    /// Container ID for network connect/disconnect events
+    pub container_id: Option<String>,
+    /// Unix timestamp in seconds
+    pub time: i64,
+    /// Docker context of the daemon the event came from
+    pub context: String,
+}
+
+impl DockerEvent {
+    /// Convert a raw event of the daemon behind `context`, ignoring actions we do not track
+    fn from_api(event: ApiEvent, context: &str) -> Option<Self> {
+        let kind = match (event.event_type.as_str(), event.action.as_str()) {
+            ("network", "create") => DockerEventKind::NetworkCreated,
+            ("network", "destroy") => DockerEventKind::NetworkDestroyed,
+            ("network", "connect") => DockerEventKind::NetworkConnected,
+            ("network", "disconnect") => DockerEventKind::NetworkDisconnected,
+            ("container", "start") => DockerEventKind::ContainerStarted,
+            ("container", "stop") => DockerEventKind::ContainerStopped,
+            ("container", "die") => DockerEventKind::ContainerDied,
+            _ => return None,
+        };
+
+        Some(DockerEvent {
+            kind,
+            name: event
+                .actor
+                .attributes
+                .get("name")
+                .cloned()
+                .unwrap_or_default(),
+            id: event.actor.id,
+            container_id: event.actor.attributes.get("container").cloned(),
+            time: event.time,
+            context: context.to_string(),
+        })
+    }
+}
+
+/// Event filters in the format of the `filters` query parameter of `GET /events`
+fn event_filters() -> serde_json::Value {
+    serde_json::json!({
+        "type": EVENT_TYPES,
+        "event": EVENT_ACTIONS,
+    })
+}
+
+/// Where the event stream currently comes from, used to log changes only once
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum StreamSource {
+    Api,
+    Cli,
+    Unavailable,
+}
+
+/// Subscribe to Docker events
+///
+/// Spawns background threads that follow the event stream of every detected daemon (see
+/// [`daemon::detect_daemons`]) and call `on_events` with the relevant events, coalesced so that
+/// a burst (e.g. `docker compose up`) is delivered as one batch. Daemons are detected again
+/// periodically, so one that starts later (e.g. Docker Desktop) is followed too. Each stream
+/// reconnects with exponential backoff, so events resume after its daemon restarts.
+pub fn subscribe(
+    mut on_events: impl FnMut(Vec<DockerEvent>) + Send + 'static,
+) -> std::thread::JoinHandle<()> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || follow_daemons(sender));
+
+    std::thread::spawn(move || {
+        while let Ok(first) = receiver.recv() {
+            let mut batch = vec![first];
+            let deadline = Instant::now() + COALESCE_WINDOW;
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                match receiver.recv_timeout(remaining) {
+                    Ok(event) => batch.push(event),
+                    Err(_) => break,
+                }
+            }
+            on_events(batch);
+        }
+    })
+}
+
+/// Follow the events of every detected daemon, starting a stream for each new one
+///
+/// Daemons are told apart by their ID, since several contexts may point at the same daemon.
+fn follow_daemons(sender: mpsc::Sender<DockerEvent>) {
+    let mut followed: Vec<String> = Vec::new();
+
+    loop {
+        for daemon in daemon::detect_daemons() {
+            if followed.contains(&daemon.id) {
+                continue;
+            }
+            followed.push(daemon.id.clone());
+            let sender = sender.clone();
+            std::thread::spawn(move || follow_events(daemon, sender));
+        }
+        std::thread::sleep(DAEMON_RESCAN_INTERVAL);
+    }
+}
+
+/// Stream the events of `daemon` into `sender`, reconnecting whenever the stream ends or fails
+///
+/// A daemon with an API endpoint is followed through the API only, as the docker CLI in WSL
+/// may talk to another daemon. Failures are logged when the source of the stream changes
+/// rather than on every retry.
+fn follow_events(daemon: DockerDaemon, sender: mpsc::Sender<DockerEvent>) {
+    let mut source = None;
+    let mut delay = RECONNECT_DELAY;
+
+    loop {
+        let mut delivered = false;
+        let mut deliver = |event: ApiEvent| {
+            delivered = true;
+            match DockerEvent::from_api(event, &daemon.context) {
+                Some(event) => sender.send(event).is_ok(),
+                None => true,
+            }
+        };
+
+        let started = Instant::now();
+        let result = match daemon.client() {
+            Some(client) => client
+                .events(&event_filters(), &mut deliver)
+                .map(|()| StreamSource::Api),
+            None => stream_events_cli(&mut deliver).map(|()| StreamSource::Cli),
+        };
+        let current = match result {
+            Ok(current) => current,
+            Err(e) => {
+                if e.downcast_ref::<std::io::Error>()
+                    .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+                {
+                    eprintln!("Docker CLI not found ({e}), no longer following {daemon}");
+                    return;
+                }
+                if source != Some(StreamSource::Unavailable) {
+                    eprintln!("Docker event stream of {daemon} unavailable ({e})");
+                }
+                StreamSource::Unavailable
+            }
+        };
+        source = Some(current);
+
+        // A stream that delivered events or stayed open for a while was healthy, so reconnect
+        // quickly; otherwise back off
+        if delivered || started.elapsed() >= MAX_RECONNECT_DELAY {
+            delay = RECONNECT_DELAY;
+        } else {
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+        std::thread::sleep(delay);
+    }
+}
+
+/// Stream events using `docker events` via WSL
+fn stream_events_cli(
+    on_event: &mut impl FnMut(ApiEvent) -> bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "-e".to_string(),
+        "docker".to_string(),
+        "events".to_string(),
+        "--format".to_string(),
+        "{{json .}}".to_string(),
+    ];
+    for event_type in EVENT_TYPES {
+        args.extend(["--filter".to_string(), format!("type={event_type}")]);
+    }
+    for action in EVENT_ACTIONS {
+        args.extend(["--filter".to_string(), format!("event={action}")]);
+    }
+
+    let mut child = Command::new("wsl.exe")
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Could not capture docker events output")?;
+
+    // Each line is a JSON object with the same fields as the API events
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<ApiEvent>(&line) {
+            Ok(event) => {
+                if !on_event(event) {
+                    break;
+                }
+            }
+            Err(e) => eprintln!("Could not parse Docker event: {e}"),
+        }
+    }
+
+    let _ = child.kill();
+    let mut stderr = Vec::new();
+    if let Some(mut pipe) = child.stderr.take() {
+        let _ = pipe.read_to_end(&mut stderr);
+    }
+    let status = child.wait()?;
+    if status.success() {
+        return Ok(());
+    }
+
+    // wsl.exe may report its own errors in UTF-16
+    let stderr = String::from_utf8_lossy(&stderr).replace('\0', "");
+    let stderr = stderr.trim();
+    if stderr.contains("not found") || stderr.contains("No such file or directory") {
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, stderr.to_string()).into());
+    }
+    Err(format!("docker events exited with {status}: {stderr}").into())
+}
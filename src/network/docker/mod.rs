@@ -0,0 +1,691 @@
+//! Docker network discovery implementation
+//!
+//! This module provides functionality to discover Docker networks and their containers.
+//! It queries the Docker Engine API (see [`api`]) and falls back to the docker CLI via
+//! `wsl.exe` when the daemon is not reachable from this process.
+
+pub mod api;
+pub mod compose;
+pub mod daemon;
+pub mod events;
+
+use super::ContainerRuntime;
+use api::{ApiContainer, ApiContainerInspect, ApiNetwork};
+use compose::{COMPOSE_PROJECT_LABEL, COMPOSE_SERVICE_LABEL};
+use daemon::{DockerDaemon, DockerDaemonKind};
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Represents a Docker network with its properties and source environment
+///
+/// Also used for networks of other container runtimes such as Podman.
+#[derive(Debug, Clone)]
+pub struct DockerNetwork {
+    pub name: String,
+    pub driver: String,
+    pub scope: String,
+    pub subnet: String,
+    pub compose_project: String, // Empty when not created by Docker Compose
+    pub runtime: ContainerRuntime,
+    pub daemon: Option<DockerDaemon>, // Docker daemon the network belongs to, None for Podman
+}
+
+/// Represents a Docker container with its properties
+///
+/// Also used for containers of other container runtimes such as Podman.
+#[derive(Debug, Clone)]
+pub struct DockerContainer {
+    pub name: String,
+    pub image: String,
+    pub status: String,
+    pub port_mappings: Vec<PortMapping>,
+    pub id: String,
+    pub endpoint: Option<ContainerEndpoint>, // Endpoint on the queried network
+    pub networks: Vec<String>,
+    pub compose_project: String, // Empty when not created by Docker Compose
+    pub compose_service: String,
+    pub pod: String, // Podman pod name, empty for Docker
+    pub runtime: ContainerRuntime,
+    pub daemon: Option<DockerDaemon>, // Docker daemon running the container, None for Podman
+}
+
+/// Represents a container's endpoint on a single Docker network
+#[derive(Debug, Clone, Default)]
+pub struct ContainerEndpoint {
+    pub ipv4_address: String,
+    pub ipv6_address: String,
+    pub mac_address: String,
+    pub gateway: String,
+    pub aliases: Vec<String>,
+    pub dns_names: Vec<String>,
+}
+
+/// Represents a published port of a container
+/// (e.g. `0.0.0.0:8080->80/tcp` in `docker ps` output)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortMapping {
+    pub host_ip: String,
+    pub host_port: String,
+    pub container_port: String,
+    pub protocol: String,
+}
+
+/// Represents a published port together with the container it belongs to
+#[derive(Debug, Clone)]
+pub struct PublishedPort {
+    pub container_name: String,
+    pub container_id: String,
+    pub image: String,
+    pub compose_project: Option<String>,
+    pub mapping: PortMapping,
+    pub runtime: ContainerRuntime,
+    pub daemon_kind: Option<DockerDaemonKind>,
+}
+
+/// Get Docker networks
+///
+/// This function queries every detected Docker daemon (see [`daemon`]) through the Docker
+/// Engine API, or with `docker network ls` when a daemon is only reachable through the CLI.
+/// Each network is labelled with its daemon.
+pub fn get_docker_networks() -> Result<Vec<DockerNetwork>, Box<dyn std::error::Error>> {
+    let mut all_networks = Vec::new();
+
+    for daemon in daemon::detect_daemons() {
+        let networks = match daemon.client() {
+            Some(client) => client
+                .networks()
+                .map(|networks| networks.into_iter().map(network_from_api).collect()),
+            None => get_docker_networks_cli(),
+        };
+
+        match networks {
+            Ok(networks) => all_networks.extend(networks.into_iter().map(|mut network| {
+                network.daemon = Some(daemon.clone());
+                network
+            })),
+            Err(e) => eprintln!("Error getting Docker networks from {daemon}: {e}"),
+        }
+    }
+
+    Ok(all_networks)
+}
+
+/// Get a single Docker network by ID or name from the daemon behind a docker context
+///
+/// This function queries the daemon through the Docker Engine API, or with
+/// `docker network inspect` when the daemon is only reachable through the CLI.
+pub fn get_docker_network(
+    context: &str,
+    id: &str,
+) -> Result<DockerNetwork, Box<dyn std::error::Error>> {
+    let daemon = daemon::daemon_for_context(context)
+        .ok_or_else(|| format!("No Docker daemon found for context {context}"))?;
+
+    let mut network = match daemon.client() {
+        Some(client) => network_from_api(client.inspect_network(id)?),
+        None => {
+            let output = Command::new("wsl.exe")
+                .args(["-e", "docker", "network", "inspect", id])
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(format!("docker network inspect failed: {stderr}").into());
+            }
+
+            // docker network inspect prints the API representation wrapped in an array
+            let networks: Vec<ApiNetwork> = serde_json::from_slice(&output.stdout)?;
+            networks
+                .into_iter()
+                .next()
+                .map(network_from_api)
+                .ok_or_else(|| format!("Docker network {id} not found"))?
+        }
+    };
+    network.daemon = Some(daemon);
+    Ok(network)
+}
+
+/// Get Docker containers for a specific network of the daemon behind a docker context
+///
+/// This function queries the Docker Engine API for the containers connected to a specific
+/// network and inspects each one, falling back to the docker CLI when the daemon is only
+/// reachable through it.
+pub fn get_containers_for_network(
+    context: &str,
+    network_name: &str,
+) -> Result<Vec<DockerContainer>, Box<dyn std::error::Error>> {
+    let daemon = daemon::daemon_for_context(context)
+        .ok_or_else(|| format!("No Docker daemon found for context {context}"))?;
+
+    let containers = match daemon.client() {
+        Some(client) => client
+            .containers(Some(network_name))?
+            .into_iter()
+            .map(|container| {
+                // Keep the list data if inspecting a single container fails
+                let inspect = client
+                    .inspect_container(&container.id)
+                    .map_err(|e| println!("Docker inspect error {e}"))
+                    .ok();
+                container_from_api(container, inspect.as_ref(), network_name)
+            })
+            .collect(),
+        None => get_containers_cli(Some(network_name))?,
+    };
+
+    Ok(with_daemon(containers, &daemon))
+}
+
+/// Get all running Docker containers
+///
+/// This function queries every detected Docker daemon through the Docker Engine API, or with
+/// `docker ps` when a daemon is only reachable through the CLI. Endpoints are not filled in
+/// since no network was selected.
+pub fn get_all_containers() -> Result<Vec<DockerContainer>, Box<dyn std::error::Error>> {
+    let mut all_containers = Vec::new();
+
+    for daemon in daemon::detect_daemons() {
+        let containers = match daemon.client() {
+            Some(client) => client.containers(None).map(|containers| {
+                containers
+                    .into_iter()
+                    .map(|container| container_from_api(container, None, ""))
+                    .collect()
+            }),
+            None => get_containers_cli(None),
+        };
+
+        match containers {
+            Ok(containers) => all_containers.extend(with_daemon(containers, &daemon)),
+            Err(e) => eprintln!("Error getting Docker containers from {daemon}: {e}"),
+        }
+    }
+
+    Ok(all_containers)
+}
+
+/// Label containers with the daemon they were listed from
+fn with_daemon(containers: Vec<DockerContainer>, daemon: &DockerDaemon) -> Vec<DockerContainer> {
+    containers
+        .into_iter()
+        .map(|mut container| {
+            container.daemon = Some(daemon.clone());
+            container
+        })
+        .collect()
+}
+
+/// Get the published ports of containers
+pub fn published_ports(containers: Vec<DockerContainer>) -> Vec<PublishedPort> {
+    containers
+        .into_iter()
+        .flat_map(|container| {
+            let compose_project =
+                Some(container.compose_project.clone()).filter(|project| !project.is_empty());
+            let daemon_kind = container.daemon.as_ref().map(|daemon| daemon.kind);
+            container
+                .port_mappings
+                .into_iter()
+                .map(move |mapping| PublishedPort {
+                    container_name: container.name.clone(),
+                    container_id: container.id.clone(),
+                    image: container.image.clone(),
+                    compose_project: compose_project.clone(),
+                    mapping,
+                    runtime: container.runtime,
+                    daemon_kind,
+                })
+        })
+        .collect()
+}
+
+/// Convert a network from the Docker Engine API
+fn network_from_api(network: ApiNetwork) -> DockerNetwork {
+    // A network can have several subnets (e.g. IPv4 and IPv6)
+    let subnet = network
+        .ipam
+        .and_then(|ipam| ipam.config)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|config| config.subnet)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    DockerNetwork {
+        name: network.name,
+        driver: network.driver,
+        scope: network.scope,
+        subnet,
+        compose_project: network
+            .labels
+            .and_then(|labels| labels.get(COMPOSE_PROJECT_LABEL).cloned())
+            .unwrap_or_default(),
+        runtime: ContainerRuntime::Docker,
+        daemon: None,
+    }
+}
+
+/// Convert a container from the Docker Engine API, using inspect data when available
+fn container_from_api(
+    container: ApiContainer,
+    inspect: Option<&ApiContainerInspect>,
+    network_name: &str,
+) -> DockerContainer {
+    let endpoint = inspect
+        .and_then(|inspect| inspect.network_settings.networks.get(network_name))
+        .map(|endpoint| ContainerEndpoint {
+            ipv4_address: endpoint.ip_address.clone(),
+            ipv6_address: endpoint.global_ipv6_address.clone(),
+            mac_address: endpoint.mac_address.clone(),
+            gateway: endpoint.gateway.clone(),
+            aliases: endpoint.aliases.clone().unwrap_or_default(),
+            dns_names: endpoint.dns_names.clone().unwrap_or_default(),
+        });
+
+    // Prefer the inspect port bindings; the list reports the same data in a flatter form
+    let port_mappings = match inspect.and_then(|inspect| inspect.network_settings.ports.as_ref()) {
+        Some(ports) => ports
+            .iter()
+            .flat_map(|(container_port, bindings)| {
+                let (port, protocol) = split_port_protocol(container_port);
+                // Exposed but unpublished ports have no bindings
+                bindings.iter().flatten().map(move |binding| PortMapping {
+                    host_ip: binding.host_ip.clone(),
+                    host_port: binding.host_port.clone(),
+                    container_port: port.clone(),
+                    protocol: protocol.clone(),
+                })
+            })
+            .collect(),
+        None => container
+            .ports
+            .iter()
+            .filter_map(|port| {
+                // Unpublished ports are listed without a public port
+                Some(PortMapping {
+                    host_ip: port.ip.clone().unwrap_or_default(),
+                    host_port: port.public_port?.to_string(),
+                    container_port: port.private_port.to_string(),
+                    protocol: port.protocol.clone(),
+                })
+            })
+            .collect(),
+    };
+
+    // The list reports network names too, but only inspect data is complete for one container
+    let mut networks: Vec<String> = inspect
+        .map(|inspect| &inspect.network_settings)
+        .or(container.network_settings.as_ref())
+        .map(|settings| settings.networks.keys().cloned().collect())
+        .unwrap_or_default();
+    networks.sort();
+
+    let labels = container.labels.unwrap_or_default();
+    let label = |key: &str| labels.get(key).cloned().unwrap_or_default();
+
+    DockerContainer {
+        // Names are reported with a leading slash
+        name: container
+            .names
+            .first()
+            .map(|name| name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| "unknown".to_string()),
+        image: container.image,
+        status: container.status,
+        port_mappings,
+        id: container.id.chars().take(12).collect(),
+        endpoint,
+        networks,
+        compose_project: label(COMPOSE_PROJECT_LABEL),
+        compose_service: label(COMPOSE_SERVICE_LABEL),
+        pod: String::new(),
+        runtime: ContainerRuntime::Docker,
+        daemon: None,
+    }
+}
+
+/// Get Docker networks using the docker CLI
+///
+/// This function uses the `docker network ls` command to get Docker network information.
+fn get_docker_networks_cli() -> Result<Vec<DockerNetwork>, Box<dyn std::error::Error>> {
+    // Execute docker network ls command via WSL
+    let output = Command::new("wsl.exe")
+        .args(["-e", "docker", "network", "ls", "--format", "json"])
+        .output();
+
+    let mut networks = Vec::new();
+
+    // Process output if available
+    if let Ok(output) = output {
+        if output.status.success() {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+
+            // Parse the output to extract network information
+            // Each line is a JSON object representing a network
+            for line in output_str.lines() {
+                if !line.trim().is_empty() {
+                    // Try to parse each line as JSON
+                    if let Ok(network_info) = serde_json::from_str::<serde_json::Value>(line) {
+                        // Extract fields from the JSON object
+                        let name = network_info
+                            .get("Name")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let driver = network_info
+                            .get("Driver")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let scope = network_info
+                            .get("Scope")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+
+                        // For subnet information, we would need to run docker network inspect
+                        // For now, we'll leave it empty
+                        let subnet = "".to_string();
+
+                        let labels = parse_cli_labels(
+                            network_info
+                                .get("Labels")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or(""),
+                        );
+
+                        networks.push(DockerNetwork {
+                            name,
+                            driver,
+                            scope,
+                            subnet,
+                            compose_project: labels
+                                .get(COMPOSE_PROJECT_LABEL)
+                                .cloned()
+                                .unwrap_or_default(),
+                            runtime: ContainerRuntime::Docker,
+                            daemon: None,
+                        });
+                    }
+                }
+            }
+        }
+    } else if let Err(x) = output {
+        println!("Docker network error {x}");
+    }
+
+    // If we couldn't get data, provide some default networks
+    if networks.is_empty() {
+        println!("Could not load any networks for Docker!");
+    }
+
+    Ok(networks)
+}
+
+/// Get Docker containers using the docker CLI
+///
+/// This function uses the `docker ps` command to get running containers, optionally only those
+/// connected to a specific network. For a network, `docker inspect` then fills in each
+/// container's endpoint and published ports.
+fn get_containers_cli(
+    network_name: Option<&str>,
+) -> Result<Vec<DockerContainer>, Box<dyn std::error::Error>> {
+    // Execute docker ps command, with network filter if given, via WSL
+    let mut args = vec!["-e".to_string(), "docker".to_string(), "ps".to_string()];
+    if let Some(network_name) = network_name {
+        args.extend(["--filter".to_string(), format!("network={network_name}")]);
+    }
+    args.extend(["--format".to_string(), "json".to_string()]);
+    let output = Command::new("wsl.exe").args(&args).output();
+
+    let mut containers = Vec::new();
+
+    // Process output if available
+    if let Ok(output) = output {
+        if output.status.success() {
+            let output_str = String::from_utf8_lossy(&output.stdout);
+
+            // Parse the output to extract container information
+            // Each line is a JSON object representing a container
+            for line in output_str.lines() {
+                if !line.trim().is_empty() {
+                    // Try to parse each line as JSON
+                    if let Ok(container_info) = serde_json::from_str::<serde_json::Value>(line) {
+                        // Extract fields from the JSON object
+                        let name = container_info
+                            .get("Names")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let image = container_info
+                            .get("Image")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let status = container_info
+                            .get("Status")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        let ports = container_info
+                            .get("Ports")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("");
+                        let id = container_info
+                            .get("ID")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("unknown")
+                            .to_string();
+                        // Networks are reported as "a,b"
+                        let networks = container_info
+                            .get("Networks")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or("")
+                            .split(',')
+                            .filter(|network| !network.is_empty())
+                            .map(|network| network.to_string())
+                            .collect();
+                        let labels = parse_cli_labels(
+                            container_info
+                                .get("Labels")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or(""),
+                        );
+                        let label = |key: &str| labels.get(key).cloned().unwrap_or_default();
+
+                        containers.push(DockerContainer {
+                            name,
+                            image,
+                            status,
+                            port_mappings: parse_ports_field(ports),
+                            id,
+                            endpoint: None,
+                            networks,
+                            compose_project: label(COMPOSE_PROJECT_LABEL),
+                            compose_service: label(COMPOSE_SERVICE_LABEL),
+                            pod: String::new(),
+                            runtime: ContainerRuntime::Docker,
+                            daemon: None,
+                        });
+                    }
+                }
+            }
+        }
+    } else if let Err(x) = output {
+        println!("Docker container query error {x}");
+    }
+
+    // Enrich the containers with inspect data; keep the `docker ps` data if inspect fails
+    if let (false, Some(network_name)) = (containers.is_empty(), network_name) {
+        let ids: Vec<&str> = containers.iter().map(|c| c.id.as_str()).collect();
+        match inspect_containers("docker", &ids, network_name) {
+            Ok(details) => {
+                for container in containers.iter_mut() {
+                    if let Some((endpoint, port_mappings)) = details
+                        .iter()
+                        .find(|(id, _)| id.starts_with(&container.id))
+                        .map(|(_, detail)| detail.clone())
+                    {
+                        container.endpoint = endpoint;
+                        container.port_mappings = port_mappings;
+                    }
+                }
+            }
+            Err(e) => println!("Docker inspect error {e}"),
+        }
+    }
+
+    Ok(containers)
+}
+
+/// Parse the `Labels` field from the docker CLI
+/// Format: "com.docker.compose.project=shop,com.docker.compose.service=api"
+fn parse_cli_labels(labels: &str) -> HashMap<String, String> {
+    labels
+        .split(',')
+        .filter_map(|label| label.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Endpoint and published ports of one inspected container
+pub(super) type ContainerDetail = (Option<ContainerEndpoint>, Vec<PortMapping>);
+
+/// Inspect containers and extract their endpoint on `network_name` and their published ports
+///
+/// `binary` is the container CLI to run in WSL; Podman reports the same inspect format as Docker.
+/// Returns pairs of full container ID and details, in the order reported by `docker inspect`.
+pub(super) fn inspect_containers(
+    binary: &str,
+    ids: &[&str],
+    network_name: &str,
+) -> Result<Vec<(String, ContainerDetail)>, Box<dyn std::error::Error>> {
+    let mut args = vec!["-e", binary, "inspect", "--type", "container"];
+    args.extend_from_slice(ids);
+
+    let output = Command::new("wsl.exe").args(&args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("{binary} inspect failed: {stderr}").into());
+    }
+
+    // docker inspect prints a single JSON array with one object per container
+    let inspected: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout)?;
+
+    let details = inspected
+        .iter()
+        .map(|info| {
+            let id = info
+                .get("Id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .to_string();
+            let network_settings = info.get("NetworkSettings");
+            let endpoint = network_settings
+                .and_then(|settings| settings.get("Networks"))
+                .and_then(|networks| networks.get(network_name))
+                .map(parse_endpoint);
+            let port_mappings = network_settings
+                .and_then(|settings| settings.get("Ports"))
+                .map(parse_port_bindings)
+                .unwrap_or_default();
+            (id, (endpoint, port_mappings))
+        })
+        .collect();
+
+    Ok(details)
+}
+
+/// Parse an entry of `NetworkSettings.Networks` from `docker inspect`
+fn parse_endpoint(endpoint: &serde_json::Value) -> ContainerEndpoint {
+    let string_field = |key: &str| {
+        endpoint
+            .get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+    // Aliases and DNSNames are null rather than empty when unset
+    let string_list = |key: &str| {
+        endpoint
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|values| {
+                values
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|v| v.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    ContainerEndpoint {
+        ipv4_address: string_field("IPAddress"),
+        ipv6_address: string_field("GlobalIPv6Address"),
+        mac_address: string_field("MacAddress"),
+        gateway: string_field("Gateway"),
+        aliases: string_list("Aliases"),
+        dns_names: string_list("DNSNames"),
+    }
+}
+
+/// Parse `NetworkSettings.Ports` from `docker inspect`
+/// Format: `{"80/tcp": [{"HostIp": "0.0.0.0", "HostPort": "8080"}], "443/tcp": null}`
+fn parse_port_bindings(ports: &serde_json::Value) -> Vec<PortMapping> {
+    let mut mappings = Vec::new();
+
+    if let Some(ports) = ports.as_object() {
+        for (container_port, bindings) in ports {
+            let (port, protocol) = split_port_protocol(container_port);
+
+            // Exposed but unpublished ports have no bindings
+            for binding in bindings.as_array().into_iter().flatten() {
+                mappings.push(PortMapping {
+                    host_ip: binding
+                        .get("HostIp")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    host_port: binding
+                        .get("HostPort")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string(),
+                    container_port: port.clone(),
+                    protocol: protocol.clone(),
+                });
+            }
+        }
+    }
+
+    mappings
+}
+
+/// Parse the `Ports` field from `docker ps`
+/// Format: "0.0.0.0:8080->80/tcp, :::8080->80/tcp, 5432/tcp"
+fn parse_ports_field(ports: &str) -> Vec<PortMapping> {
+    ports
+        .split(", ")
+        .filter_map(|entry| {
+            // Entries without "->" are exposed but not published
+            let (host, container) = entry.trim().split_once("->")?;
+            let colon_pos = host.rfind(':')?;
+            let (container_port, protocol) = split_port_protocol(container);
+            Some(PortMapping {
+                host_ip: host[..colon_pos].to_string(),
+                host_port: host[colon_pos + 1..].to_string(),
+                container_port,
+                protocol,
+            })
+        })
+        .collect()
+}
+
+/// Split a container port like "80/tcp" into port and protocol
+fn split_port_protocol(container_port: &str) -> (String, String) {
+    match container_port.split_once('/') {
+        Some((port, protocol)) => (port.to_string(), protocol.to_string()),
+        None => (container_port.to_string(), "tcp".to_string()),
+    }
+}
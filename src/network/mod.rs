@@ -1,5 +1,9 @@
 // Network module
+pub mod conflicts;
 pub mod docker;
+pub mod podman;
+pub mod process;
+pub mod search;
 pub mod windows;
 pub mod wsl;
 
@@ -10,6 +14,35 @@ pub enum NetworkEnvironment {
     Wsl,
 }
 
+/// Represents the container runtime that owns a container network or container
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl std::fmt::Display for ContainerRuntime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContainerRuntime::Docker => write!(f, "Docker"),
+            ContainerRuntime::Podman => write!(f, "Podman"),
+        }
+    }
+}
+
+impl std::str::FromStr for ContainerRuntime {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Docker" => Ok(ContainerRuntime::Docker),
+            "Podman" => Ok(ContainerRuntime::Podman),
+            _ => Err(format!("Unknown container runtime: {s}")),
+        }
+    }
+}
+
 /// Represents a network interface with its properties and source environment
 #[derive(Debug, Clone)]
 pub struct NetworkInterface {
@@ -22,6 +55,16 @@ pub struct NetworkInterface {
     pub environment: NetworkEnvironment, // New field to identify source
 }
 
+/// Represents an address assigned to an interface, with its subnet
+#[derive(Debug, Clone)]
+pub struct InterfaceAddress {
+    pub interface: String,
+    pub description: String, // Adapter description, empty in WSL
+    pub address: String,
+    pub prefix_length: u8,
+    pub environment: NetworkEnvironment,
+}
+
 /// Represents an active port with its associated process information
 #[derive(Debug, Clone)]
 pub struct PortInfo {
@@ -32,15 +75,112 @@ pub struct PortInfo {
     pub direction: String,
     pub network: String,
     pub environment: NetworkEnvironment,
+    pub container: Option<ContainerPortOwner>, // Set when the port is published by a container
+}
+
+/// Represents the container behind a port that is held by a forwarding process
+#[derive(Debug, Clone, Default)]
+pub struct ContainerPortOwner {
+    pub container_name: String,
+    pub container_id: String,
+    pub image: String,
+    pub compose_project: String,
+    pub container_port: String,
+    pub runtime: ContainerRuntime,
+    pub daemon_kind: Option<docker::daemon::DockerDaemonKind>, // None for Podman
+    pub reachability: String, // Where the published port can be reached from
+}
+
+impl ContainerPortOwner {
+    /// Name of what runs the container: "Docker Desktop", "Docker Engine" or "Podman"
+    pub fn daemon(&self) -> String {
+        match self.daemon_kind {
+            Some(kind) => kind.to_string(),
+            None => self.runtime.to_string(),
+        }
+    }
+}
+
+/// Represents a range of ports Windows reserved, which nothing on Windows can listen on
+///
+/// Hyper-V and WinNAT reserve ranges dynamically (often a different set after every reboot);
+/// administered ranges were added with `netsh int ipv4 add excludedportrange`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExcludedPortRange {
+    pub protocol: String, // "TCP" or "UDP"
+    pub ip_version: u8,   // 4 or 6
+    pub start: u16,
+    pub end: u16,
+    pub administered: bool,
 }
 
-/// Represents a Docker network with its properties and source environment
+impl ExcludedPortRange {
+    /// Whether the range reserves `port` for `protocol` ("TCP" or "UDP", any case) on `address`
+    ///
+    /// Ranges are reserved per IP version. The unspecified IPv6 address also listens on IPv4,
+    /// and `None` stands for an address of either version.
+    pub fn contains(&self, protocol: &str, address: Option<std::net::IpAddr>, port: u16) -> bool {
+        use std::net::IpAddr;
+
+        let ip_version_matches = match address {
+            Some(IpAddr::V4(_)) => self.ip_version == 4,
+            Some(IpAddr::V6(ip)) if ip.to_ipv4_mapped().is_some() => self.ip_version == 4,
+            Some(IpAddr::V6(ip)) if !ip.is_unspecified() => self.ip_version == 6,
+            _ => true,
+        };
+        ip_version_matches
+            && self.protocol.eq_ignore_ascii_case(protocol)
+            && (self.start..=self.end).contains(&port)
+    }
+}
+
+impl std::fmt::Display for ExcludedPortRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}-{} IPv{}",
+            self.protocol, self.start, self.end, self.ip_version
+        )?;
+        if self.administered {
+            write!(f, ", administered")?;
+        }
+        Ok(())
+    }
+}
+
+/// How long the excluded port ranges are reused before netsh is asked again
+const EXCLUDED_RANGES_TTL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Processes that hold Docker Desktop's published ports on Windows (vpnkit on older versions)
+const DESKTOP_WINDOWS_FORWARDERS: [&str; 2] = ["com.docker.backend", "vpnkit"];
+
+/// Processes that hold Docker Desktop's published ports inside WSL
+///
+/// Desktop's docker-proxy runs in the docker-desktop distro, which shares the network namespace
+/// with the other WSL 2 distros. They see its sockets without an owning process, and such
+/// sockets are left unattributed since any other process outside the distro looks the same.
+const DESKTOP_WSL_FORWARDERS: [&str; 1] = ["docker-proxy"];
+
+/// Processes that hold Docker Engine's published ports inside WSL
+const ENGINE_WSL_FORWARDERS: [&str; 1] = ["docker-proxy"];
+
+/// Processes that hold rootless Podman's published ports inside WSL
+const PODMAN_WSL_FORWARDERS: [&str; 4] = ["rootlessport", "pasta", "slirp4netns", "conmon"];
+
+/// Processes that forward localhost ports from WSL to Windows
+const WSL_RELAY_FORWARDERS: [&str; 1] = ["wslrelay"];
+
+/// Represents a container network with its properties and owning runtime
 #[derive(Debug, Clone)]
 pub struct DockerNetwork {
     pub name: String,
     pub driver: String,
     pub scope: String,
     pub subnet: String,
+    pub compose_project: String,
+    pub runtime: ContainerRuntime,
+    pub daemon: String,  // "Docker Desktop" or "Docker Engine", empty for Podman
+    pub context: String, // Docker context the network was found through, empty for Podman
 }
 
 /// Get network interfaces from all environments
@@ -64,6 +204,156 @@ pub fn get_all_network_interfaces() -> Result<Vec<NetworkInterface>, Box<dyn std
     Ok(all_interfaces)
 }
 
+/// Words in adapter names and descriptions of VPN clients
+const VPN_ADAPTER_KEYWORDS: [&str; 16] = [
+    "vpn",
+    "tap-windows",
+    "wintun",
+    "wireguard",
+    "anyconnect",
+    "globalprotect",
+    "pangp",
+    "fortinet",
+    "forticlient",
+    "zscaler",
+    "juniper",
+    "pulse secure",
+    "nordlynx",
+    "tailscale",
+    "zerotier",
+    "openconnect",
+];
+
+/// Prefixes of VPN tunnel interface names in WSL
+const VPN_INTERFACE_PREFIXES: [&str; 5] = ["tun", "tap", "wg", "ppp", "tailscale"];
+
+/// Represents the part a hop on a network path plays in the local WSL setup
+#[derive(Debug, Clone, PartialEq)]
+pub enum HopRole {
+    /// The default gateway of WSL
+    WslNatGateway,
+    /// A Windows vEthernet adapter, named after its virtual switch
+    VEthernet(String),
+    /// The network of a VPN adapter
+    VpnTunnel(String),
+}
+
+impl std::fmt::Display for HopRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HopRole::WslNatGateway => write!(f, "WSL NAT gateway"),
+            HopRole::VEthernet(adapter) => write!(f, "{adapter} adapter"),
+            HopRole::VpnTunnel(adapter) => write!(f, "VPN tunnel ({adapter})"),
+        }
+    }
+}
+
+/// Local addresses that identify hops of a path leaving WSL or Windows
+#[derive(Debug, Clone, Default)]
+pub struct PathLandmarks {
+    pub wsl_gateway: Option<String>,
+    pub addresses: Vec<InterfaceAddress>,
+}
+
+impl PathLandmarks {
+    /// Get the roles of a hop address in the local setup
+    ///
+    /// A hop is a VPN tunnel hop when it lies in the subnet of a VPN adapter, since the remote
+    /// end of a tunnel is rarely one of the local addresses. Link-local addresses are skipped, as
+    /// every adapter has an fe80::/64 subnet and the hop may be on any of them.
+    pub fn classify(&self, hop: &str) -> Vec<HopRole> {
+        let Ok(hop_address) = hop.parse::<std::net::IpAddr>() else {
+            return Vec::new();
+        };
+
+        let mut roles = Vec::new();
+        if self.wsl_gateway.as_deref() == Some(hop) {
+            roles.push(HopRole::WslNatGateway);
+        }
+        for address in &self.addresses {
+            if address.environment == NetworkEnvironment::Windows
+                && address.interface.starts_with("vEthernet")
+                && address.address == hop
+            {
+                roles.push(HopRole::VEthernet(address.interface.clone()));
+            }
+            if is_vpn_adapter(address)
+                && !is_link_local(hop_address)
+                && in_subnet(hop_address, &address.address, address.prefix_length)
+            {
+                roles.push(HopRole::VpnTunnel(address.interface.clone()));
+            }
+        }
+        roles.dedup();
+        roles
+    }
+}
+
+/// Whether an interface belongs to a VPN client
+fn is_vpn_adapter(address: &InterfaceAddress) -> bool {
+    let name = address.interface.to_lowercase();
+    let description = address.description.to_lowercase();
+    VPN_ADAPTER_KEYWORDS
+        .iter()
+        .any(|keyword| name.contains(keyword) || description.contains(keyword))
+        || (address.environment == NetworkEnvironment::Wsl
+            && VPN_INTERFACE_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix)))
+}
+
+/// Whether an address is link-local (169.254.0.0/16 or fe80::/10)
+fn is_link_local(address: std::net::IpAddr) -> bool {
+    match address {
+        std::net::IpAddr::V4(address) => address.is_link_local(),
+        std::net::IpAddr::V6(address) => address.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+/// Whether `address` lies in the subnet of `network`/`prefix_length`
+fn in_subnet(address: std::net::IpAddr, network: &str, prefix_length: u8) -> bool {
+    use std::net::IpAddr;
+
+    match (address, network.parse::<IpAddr>()) {
+        (IpAddr::V4(address), Ok(IpAddr::V4(network))) => {
+            let mask = u32::MAX
+                .checked_shl(32 - u32::from(prefix_length.min(32)))
+                .unwrap_or(0);
+            u32::from(address) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(address), Ok(IpAddr::V6(network))) => {
+            let mask = u128::MAX
+                .checked_shl(128 - u32::from(prefix_length.min(128)))
+                .unwrap_or(0);
+            u128::from(address) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// Get the local addresses used to label traceroute hops
+///
+/// This function combines the Windows adapter addresses, the WSL interface addresses and the
+/// WSL default gateway. Environments that cannot be queried are skipped.
+pub fn get_path_landmarks() -> PathLandmarks {
+    let mut landmarks = PathLandmarks::default();
+
+    match windows::get_adapter_addresses() {
+        Ok(addresses) => landmarks.addresses.extend(addresses),
+        Err(e) => eprintln!("Error getting Windows adapter addresses: {e}"),
+    }
+    match wsl::get_interface_addresses() {
+        Ok(addresses) => landmarks.addresses.extend(addresses),
+        Err(e) => eprintln!("Error getting WSL interface addresses: {e}"),
+    }
+    match wsl::get_default_gateway() {
+        Ok(gateway) => landmarks.wsl_gateway = gateway,
+        Err(e) => eprintln!("Error getting WSL default gateway: {e}"),
+    }
+
+    landmarks
+}
+
 /// Get active ports from all environments
 ///
 /// This function returns active ports from both Windows and WSL.
@@ -82,9 +372,178 @@ pub fn get_active_ports() -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
         Err(e) => eprintln!("Error getting WSL ports: {e}"),
     }
 
+    // Attribute ports held by docker-proxy and friends to their containers
+    let published = docker::published_ports(get_all_containers());
+    attribute_container_ports(&mut all_ports, &published);
+
     Ok(all_ports)
 }
 
+/// Get the Windows excluded port ranges, cached for a few seconds
+///
+/// The port views and every tool that binds or connects on Windows ask for them, so they are
+/// not looked up with netsh each time. Nothing is reserved when netsh is unavailable.
+pub fn excluded_port_ranges() -> Vec<ExcludedPortRange> {
+    static CACHE: std::sync::Mutex<Option<(std::time::Instant, Vec<ExcludedPortRange>)>> =
+        std::sync::Mutex::new(None);
+
+    let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((looked_up_at, ranges)) = cache.as_ref() {
+        if looked_up_at.elapsed() < EXCLUDED_RANGES_TTL {
+            return ranges.clone();
+        }
+    }
+
+    let ranges = windows::get_excluded_port_ranges().unwrap_or_else(|e| {
+        eprintln!("Error getting excluded port ranges: {e}");
+        Vec::new()
+    });
+    *cache = Some((std::time::Instant::now(), ranges.clone()));
+    ranges
+}
+
+/// Explain why `port` may not be usable on Windows when it is in an excluded range
+///
+/// Returns `None` when the port is not reserved for `protocol` on `address`, see
+/// [`ExcludedPortRange::contains`].
+pub fn excluded_port_warning(
+    protocol: &str,
+    address: Option<std::net::IpAddr>,
+    port: u16,
+) -> Option<String> {
+    let ranges: Vec<String> = excluded_port_ranges()
+        .iter()
+        .filter(|range| range.contains(protocol, address, port))
+        .map(|range| range.to_string())
+        .collect();
+    (!ranges.is_empty()).then(|| {
+        format!(
+            "Port {port} is in a Windows excluded port range ({}): Windows refuses to listen on \
+             it, and so does the WSL localhost relay",
+            ranges.join("; ")
+        )
+    })
+}
+
+/// Attribute ports held by container port forwarders to the containers that published them
+///
+/// A port matches a published port when the host port and protocol are equal and the owning
+/// process is a forwarder of the runtime and daemon that published it (see
+/// [`container_port_forwarders`]). Inside WSL the bind address must also be compatible, while on
+/// Windows the forwarders listen on their own addresses regardless of the published host IP.
+pub fn attribute_container_ports(ports: &mut [PortInfo], published: &[docker::PublishedPort]) {
+    for port in ports.iter_mut() {
+        let process_name = port.process_name.to_lowercase();
+
+        let port_ip = port
+            .network
+            .rfind(':')
+            .map(|colon_pos| &port.network[..colon_pos])
+            .unwrap_or(&port.network);
+
+        let owner = published.iter().find(|published| {
+            published.mapping.host_port == port.port
+                && published
+                    .mapping
+                    .protocol
+                    .eq_ignore_ascii_case(&port.protocol)
+                && container_port_forwarders(published, &port.environment)
+                    .iter()
+                    .any(|forwarder| process_name.starts_with(&forwarder.to_lowercase()))
+                && (port.environment == NetworkEnvironment::Windows
+                    || bind_addresses_overlap(port_ip, &published.mapping.host_ip))
+        });
+
+        if let Some(owner) = owner {
+            port.container = Some(ContainerPortOwner {
+                container_name: owner.container_name.clone(),
+                container_id: owner.container_id.clone(),
+                image: owner.image.clone(),
+                compose_project: owner.compose_project.clone().unwrap_or_default(),
+                container_port: owner.mapping.container_port.clone(),
+                runtime: owner.runtime,
+                daemon_kind: owner.daemon_kind,
+                reachability: container_port_reachability(owner),
+            });
+        }
+    }
+}
+
+/// Processes that hold a published container port in an environment
+///
+/// Docker Desktop publishes ports on Windows itself, while Docker Engine and Podman publish them
+/// inside WSL and rely on WSL's localhost forwarding (wslrelay) to reach Windows.
+fn container_port_forwarders(
+    published: &docker::PublishedPort,
+    environment: &NetworkEnvironment,
+) -> &'static [&'static str] {
+    use docker::daemon::DockerDaemonKind;
+
+    match (published.runtime, published.daemon_kind, environment) {
+        (
+            ContainerRuntime::Docker,
+            Some(DockerDaemonKind::Desktop),
+            NetworkEnvironment::Windows,
+        ) => &DESKTOP_WINDOWS_FORWARDERS,
+        (ContainerRuntime::Docker, Some(DockerDaemonKind::Desktop), NetworkEnvironment::Wsl) => {
+            &DESKTOP_WSL_FORWARDERS
+        }
+        (ContainerRuntime::Docker, _, NetworkEnvironment::Wsl) => &ENGINE_WSL_FORWARDERS,
+        (ContainerRuntime::Podman, _, NetworkEnvironment::Wsl) => &PODMAN_WSL_FORWARDERS,
+        (_, _, NetworkEnvironment::Windows) => &WSL_RELAY_FORWARDERS,
+    }
+}
+
+/// Describe where a published container port can be reached from
+fn container_port_reachability(published: &docker::PublishedPort) -> String {
+    use docker::daemon::DockerDaemonKind;
+
+    let host_ip = published
+        .mapping
+        .host_ip
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let loopback_only = host_ip == "127.0.0.1" || host_ip == "::1";
+    let port = &published.mapping.host_port;
+
+    match (published.daemon_kind, loopback_only) {
+        (Some(DockerDaemonKind::Desktop), true) => {
+            format!("Windows localhost:{port} and WSL localhost:{port} only")
+        }
+        (Some(DockerDaemonKind::Desktop), false) => {
+            format!("Windows on all interfaces (including the LAN) and WSL, port {port}")
+        }
+        (_, true) => format!(
+            "WSL localhost:{port}; Windows localhost:{port} only with WSL localhost forwarding"
+        ),
+        (_, false) => format!(
+            "WSL on all addresses; Windows via localhost forwarding or the WSL IP, port {port}"
+        ),
+    }
+}
+
+/// Whether two bind addresses can refer to the same socket (equal, or either is a wildcard)
+fn bind_addresses_overlap(a: &str, b: &str) -> bool {
+    let normalize = |ip: &str| ip.trim_start_matches('[').trim_end_matches(']').to_string();
+    let is_wildcard = |ip: &str| matches!(ip, "" | "*" | "0.0.0.0" | "::");
+    let (a, b) = (normalize(a), normalize(b));
+    is_wildcard(&a) || is_wildcard(&b) || a == b
+}
+
+/// Address a port is bound to, without its port number, brackets and interface
+///
+/// Handles each way the port lists write it, e.g. "0.0.0.0:80", "[::1]:80", ":::80", "*:80" and
+/// ss's "127.0.0.53%lo:53".
+pub fn bind_host(port: &PortInfo) -> &str {
+    let host = port
+        .network
+        .strip_suffix(port.port.as_str())
+        .and_then(|host| host.strip_suffix(':'))
+        .unwrap_or(&port.network);
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    host.split('%').next().unwrap_or(host)
+}
+
 /// Log error messages to a file for debugging
 /// Filter ports associated with a specific network interface
 ///
@@ -116,11 +575,7 @@ pub fn filter_ports_for_interface(
         }
 
         // Second check: IP address matching
-        let port_ip = if let Some(colon_pos) = port.network.rfind(':') {
-            port.network[..colon_pos].to_string()
-        } else {
-            port.network.clone()
-        };
+        let port_ip = bind_host(port).to_string();
 
         // Check if the port's network address matches any of the interface's IPs
         // Also include ports bound to 0.0.0.0 or :: (all interfaces within the same environment)
@@ -139,29 +594,138 @@ pub fn filter_ports_for_interface(
     filtered_ports
 }
 
-/// Get Docker networks
+/// Get container networks from all runtimes
 ///
-/// This function returns Docker network information.
-pub fn get_all_docker_networks() -> Result<Vec<DockerNetwork>, Box<dyn std::error::Error>> {
+/// This function returns Docker networks and, when Podman is installed in WSL, Podman networks.
+pub fn get_all_container_networks() -> Result<Vec<DockerNetwork>, Box<dyn std::error::Error>> {
+    let all_networks = get_runtime_networks()
+        .into_iter()
+        // Convert docker::DockerNetwork to crate::network::DockerNetwork
+        .map(|network| DockerNetwork {
+            daemon: network
+                .daemon
+                .as_ref()
+                .map(|daemon| daemon.kind.to_string())
+                .unwrap_or_default(),
+            context: network
+                .daemon
+                .map(|daemon| daemon.context)
+                .unwrap_or_default(),
+            name: network.name,
+            driver: network.driver,
+            scope: network.scope,
+            subnet: network.subnet,
+            compose_project: network.compose_project,
+            runtime: network.runtime,
+        })
+        .collect();
+
+    Ok(all_networks)
+}
+
+/// Get the networks of every available container runtime
+fn get_runtime_networks() -> Vec<docker::DockerNetwork> {
     let mut all_networks = Vec::new();
 
-    // Get Docker networks
     match docker::get_docker_networks() {
-        Ok(networks) => {
-            // Convert docker::DockerNetwork to crate::network::DockerNetwork
-            let converted_networks: Vec<DockerNetwork> = networks
-                .into_iter()
-                .map(|network| DockerNetwork {
-                    name: network.name,
-                    driver: network.driver,
-                    scope: network.scope,
-                    subnet: network.subnet,
-                })
-                .collect();
-            all_networks.extend(converted_networks);
-        }
+        Ok(networks) => all_networks.extend(networks),
         Err(e) => eprintln!("Error getting Docker networks: {e}"),
     }
 
-    Ok(all_networks)
+    if podman::is_available() {
+        match podman::get_podman_networks() {
+            Ok(networks) => all_networks.extend(networks),
+            Err(e) => eprintln!("Error getting Podman networks: {e}"),
+        }
+    }
+
+    all_networks
+}
+
+/// Get running containers from all runtimes
+pub fn get_all_containers() -> Vec<docker::DockerContainer> {
+    let mut all_containers = Vec::new();
+
+    match docker::get_all_containers() {
+        Ok(containers) => all_containers.extend(containers),
+        Err(e) => eprintln!("Error getting Docker containers: {e}"),
+    }
+
+    if podman::is_available() {
+        match podman::get_all_containers() {
+            Ok(containers) => all_containers.extend(containers),
+            Err(e) => eprintln!("Error getting Podman containers: {e}"),
+        }
+    }
+
+    all_containers
+}
+
+/// Get the containers attached to a network of the given runtime
+///
+/// For Docker, `context` selects the daemon since networks of different daemons can share a name.
+pub fn get_containers_for_network(
+    runtime: ContainerRuntime,
+    context: &str,
+    network_name: &str,
+) -> Result<Vec<docker::DockerContainer>, Box<dyn std::error::Error>> {
+    match runtime {
+        ContainerRuntime::Docker => docker::get_containers_for_network(context, network_name),
+        ContainerRuntime::Podman => podman::get_containers_for_network(network_name),
+    }
+}
+
+/// Get Docker Compose projects
+///
+/// This function groups the container networks and running containers of all runtimes by
+/// Compose project (podman-compose uses the same labels as Docker Compose).
+pub fn get_compose_projects(
+) -> Result<Vec<docker::compose::ComposeProject>, Box<dyn std::error::Error>> {
+    let networks = get_runtime_networks();
+    let containers = get_all_containers();
+    Ok(docker::compose::group_by_project(&networks, &containers))
+}
+
+/// Filter ports published by containers of a Docker Compose project
+///
+/// An empty project name keeps all ports.
+pub fn filter_ports_for_compose_project(ports: Vec<PortInfo>, project: &str) -> Vec<PortInfo> {
+    if project.is_empty() {
+        return ports;
+    }
+
+    ports
+        .into_iter()
+        .filter(|port| {
+            port.container
+                .as_ref()
+                .is_some_and(|container| container.compose_project == project)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn excluded_range_matches_the_ip_version_of_the_address() {
+        let range = ExcludedPortRange {
+            protocol: "TCP".to_string(),
+            ip_version: 4,
+            start: 50000,
+            end: 50059,
+            administered: false,
+        };
+        let contains = |address: Option<&str>| {
+            range.contains("tcp", address.map(|a| a.parse().unwrap()), 50000)
+        };
+        assert!(contains(Some("127.0.0.1")));
+        assert!(contains(Some("::ffff:127.0.0.1")));
+        assert!(contains(Some("::")));
+        assert!(contains(None));
+        assert!(!contains(Some("::1")));
+        assert!(!range.contains("udp", None, 50000));
+        assert!(!range.contains("tcp", None, 50060));
+    }
 }
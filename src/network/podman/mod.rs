@@ -0,0 +1,310 @@
+//! Podman container runtime
+//!
+//! This module lists Podman networks, containers and pods by running the podman CLI inside WSL.
+//! Podman is usually rootless, in which case published ports are forwarded by `rootlessport`,
+//! `pasta` or `slirp4netns` instead of a daemon-owned proxy. The results use the same types as
+//! the Docker module so both runtimes can be shown side by side.
+
+use super::docker::compose::{COMPOSE_PROJECT_LABEL, COMPOSE_SERVICE_LABEL};
+use super::docker::{self, DockerContainer, DockerNetwork, PortMapping};
+use super::ContainerRuntime;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Represents the Podman installation and how it networks containers
+#[derive(Debug, Clone, Default)]
+pub struct PodmanInfo {
+    pub version: String,
+    pub rootless: bool,
+    /// "netavark" or "cni"
+    pub network_backend: String,
+    /// Network command used for rootless containers: "pasta" or "slirp4netns"
+    pub rootless_network_cmd: String,
+}
+
+/// Represents a Podman pod, whose containers share one network namespace
+#[derive(Debug, Clone)]
+pub struct PodmanPod {
+    pub name: String,
+    pub id: String,
+    pub status: String,
+    pub containers: Vec<String>,
+    pub networks: Vec<String>,
+}
+
+/// A network as printed by `podman network ls --format json`
+#[derive(Deserialize, Debug, Clone)]
+struct CliNetwork {
+    name: String,
+    #[serde(default)]
+    driver: String,
+    subnets: Option<Vec<CliSubnet>>,
+    labels: Option<HashMap<String, String>>,
+}
+
+/// A subnet of a Podman network
+#[derive(Deserialize, Debug, Clone)]
+struct CliSubnet {
+    subnet: String,
+}
+
+/// A container as printed by `podman ps --format json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct CliContainer {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    #[serde(default)]
+    image: String,
+    #[serde(default)]
+    status: String,
+    ports: Option<Vec<CliPort>>,
+    labels: Option<HashMap<String, String>>,
+    networks: Option<Vec<String>>,
+    #[serde(default)]
+    pod_name: String,
+}
+
+/// A published port as printed by `podman ps --format json`
+///
+/// `range` consecutive ports starting at `host_port` map to ports starting at `container_port`.
+#[derive(Deserialize, Debug, Clone)]
+struct CliPort {
+    #[serde(default)]
+    host_ip: String,
+    container_port: u16,
+    host_port: u16,
+    #[serde(default = "default_port_range")]
+    range: u16,
+    #[serde(default)]
+    protocol: String,
+}
+
+fn default_port_range() -> u16 {
+    1
+}
+
+/// A pod as printed by `podman pod ps --format json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct CliPod {
+    name: String,
+    id: String,
+    #[serde(default)]
+    status: String,
+    containers: Option<Vec<CliPodContainer>>,
+    networks: Option<Vec<String>>,
+}
+
+/// A container of a pod as printed by `podman pod ps --format json`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct CliPodContainer {
+    names: String,
+}
+
+/// The parts of `podman info --format json` the application uses
+#[derive(Deserialize, Debug, Clone)]
+struct CliInfo {
+    host: CliInfoHost,
+    version: CliInfoVersion,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct CliInfoHost {
+    #[serde(default)]
+    network_backend: String,
+    #[serde(default)]
+    rootless_network_cmd: String,
+    security: CliInfoSecurity,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct CliInfoSecurity {
+    #[serde(default)]
+    rootless: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct CliInfoVersion {
+    version: String,
+}
+
+/// Whether the podman CLI is installed in the default WSL distribution
+///
+/// The result is cached, so Docker-only setups pay for the check once.
+pub fn is_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("wsl.exe")
+            .args(["-e", "podman", "--version"])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false)
+    })
+}
+
+/// Run a podman command via WSL and parse its JSON output
+fn podman_json<T: DeserializeOwned>(args: &[&str]) -> Result<T, Box<dyn std::error::Error>> {
+    let mut wsl_args = vec!["-e", "podman"];
+    wsl_args.extend_from_slice(args);
+
+    let output = Command::new("wsl.exe").args(&wsl_args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("podman {} failed: {stderr}", args.join(" ")).into());
+    }
+
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
+
+/// Get information about the Podman installation
+pub fn get_podman_info() -> Result<PodmanInfo, Box<dyn std::error::Error>> {
+    let info: CliInfo = podman_json(&["info", "--format", "json"])?;
+    Ok(PodmanInfo {
+        version: info.version.version,
+        rootless: info.host.security.rootless,
+        network_backend: info.host.network_backend,
+        rootless_network_cmd: info.host.rootless_network_cmd,
+    })
+}
+
+/// Get Podman networks
+///
+/// Podman networks are always local to the host, so the scope is reported as "local".
+pub fn get_podman_networks() -> Result<Vec<DockerNetwork>, Box<dyn std::error::Error>> {
+    let networks: Vec<CliNetwork> = podman_json(&["network", "ls", "--format", "json"])?;
+
+    Ok(networks
+        .into_iter()
+        .map(|network| DockerNetwork {
+            name: network.name,
+            driver: network.driver,
+            scope: "local".to_string(),
+            subnet: network
+                .subnets
+                .unwrap_or_default()
+                .into_iter()
+                .map(|subnet| subnet.subnet)
+                .collect::<Vec<_>>()
+                .join(", "),
+            compose_project: network
+                .labels
+                .and_then(|labels| labels.get(COMPOSE_PROJECT_LABEL).cloned())
+                .unwrap_or_default(),
+            runtime: ContainerRuntime::Podman,
+            daemon: None,
+        })
+        .collect())
+}
+
+/// Get all running Podman containers
+///
+/// Endpoints are not filled in since no network was selected.
+pub fn get_all_containers() -> Result<Vec<DockerContainer>, Box<dyn std::error::Error>> {
+    let containers: Vec<CliContainer> = podman_json(&["ps", "--format", "json"])?;
+    Ok(containers.into_iter().map(container_from_cli).collect())
+}
+
+/// Get Podman containers connected to a specific network
+///
+/// This function uses `podman ps` with a network filter and then `podman inspect`, which
+/// reports the same format as `docker inspect`, to fill in each container's endpoint.
+pub fn get_containers_for_network(
+    network_name: &str,
+) -> Result<Vec<DockerContainer>, Box<dyn std::error::Error>> {
+    let filter = format!("network={network_name}");
+    let listed: Vec<CliContainer> = podman_json(&["ps", "--filter", &filter, "--format", "json"])?;
+    let mut containers: Vec<DockerContainer> = listed.into_iter().map(container_from_cli).collect();
+
+    if !containers.is_empty() {
+        let ids: Vec<&str> = containers.iter().map(|c| c.id.as_str()).collect();
+        match docker::inspect_containers("podman", &ids, network_name) {
+            Ok(details) => {
+                for container in &mut containers {
+                    if let Some((endpoint, port_mappings)) = details
+                        .iter()
+                        .find(|(id, _)| id.starts_with(&container.id))
+                        .map(|(_, detail)| detail.clone())
+                    {
+                        container.endpoint = endpoint;
+                        // Rootless containers report no bindings in inspect; keep the ps ports
+                        if !port_mappings.is_empty() {
+                            container.port_mappings = port_mappings;
+                        }
+                    }
+                }
+            }
+            Err(e) => println!("Podman inspect error {e}"),
+        }
+    }
+
+    Ok(containers)
+}
+
+/// Get Podman pods
+pub fn get_podman_pods() -> Result<Vec<PodmanPod>, Box<dyn std::error::Error>> {
+    let pods: Vec<CliPod> = podman_json(&["pod", "ps", "--format", "json"])?;
+
+    Ok(pods
+        .into_iter()
+        .map(|pod| PodmanPod {
+            name: pod.name,
+            id: pod.id,
+            status: pod.status,
+            containers: pod
+                .containers
+                .unwrap_or_default()
+                .into_iter()
+                .map(|container| container.names)
+                .collect(),
+            networks: pod.networks.unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Convert a container from `podman ps` to the shared container type
+fn container_from_cli(container: CliContainer) -> DockerContainer {
+    let labels = container.labels.unwrap_or_default();
+    let label = |key: &str| labels.get(key).cloned().unwrap_or_default();
+
+    let port_mappings = container
+        .ports
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|port| {
+            (0..port.range.max(1)).map(move |offset| PortMapping {
+                host_ip: port.host_ip.clone(),
+                host_port: port.host_port.saturating_add(offset).to_string(),
+                container_port: port.container_port.saturating_add(offset).to_string(),
+                protocol: if port.protocol.is_empty() {
+                    "tcp".to_string()
+                } else {
+                    port.protocol.clone()
+                },
+            })
+        })
+        .collect();
+
+    DockerContainer {
+        name: container.names.into_iter().next().unwrap_or_default(),
+        image: container.image,
+        status: container.status,
+        port_mappings,
+        // Shorten to the 12 characters docker and podman print by default
+        id: container.id.chars().take(12).collect(),
+        endpoint: None,
+        networks: container.networks.unwrap_or_default(),
+        compose_project: label(COMPOSE_PROJECT_LABEL),
+        compose_service: label(COMPOSE_SERVICE_LABEL),
+        pod: container.pod_name,
+        runtime: ContainerRuntime::Podman,
+        daemon: None,
+    }
+}
@@ -0,0 +1,787 @@
+//! Process details and termination
+//!
+//! The owner of a port is described with what identifies it: command line, user, parents,
+//! cgroup with its systemd unit or container in WSL, and the services it hosts on Windows (most
+//! system ports belong to an svchost hosting several).
+//!
+//! A port is held by a Windows process, a Linux process in WSL, or a container through
+//! docker-proxy and similar forwarders. Each is stopped its own way: `taskkill` on Windows,
+//! `kill` as root in WSL (a WSL PID means nothing to `taskkill`, or worse, names an unrelated
+//! Windows process), and the container runtime for published ports, since killing the
+//! forwarder only breaks the publication while the container keeps running.
+
+use crate::network::docker::daemon::DockerDaemonKind;
+use crate::network::{ContainerRuntime, NetworkEnvironment, PortInfo};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Time a process gets to exit after a graceful request before it is killed
+const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Lowest PID that may be terminated; lower ones belong to the system (System is 4, init is 1)
+const MIN_PID: u32 = 10;
+
+/// How to terminate a process or container
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KillMode {
+    /// Ask it to exit, and kill it when it does not within the grace period
+    Graceful,
+    /// Kill it right away
+    Forced,
+}
+
+impl std::fmt::Display for KillMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KillMode::Graceful => write!(f, "Graceful"),
+            KillMode::Forced => write!(f, "Forced"),
+        }
+    }
+}
+
+impl std::str::FromStr for KillMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Graceful" => Ok(KillMode::Graceful),
+            "Forced" => Ok(KillMode::Forced),
+            _ => Err(format!("Unknown kill mode: {s}")),
+        }
+    }
+}
+
+/// What to terminate to free a port
+#[derive(Debug, Clone, PartialEq)]
+pub enum KillTarget {
+    /// A Windows process, by Windows PID
+    Windows { pid: u32 },
+    /// A process in WSL, by Linux PID
+    Wsl { pid: u32 },
+    /// A container that publishes the port
+    Container {
+        runtime: ContainerRuntime,
+        /// Whether the container runs in Docker Desktop, whose CLI is on Windows
+        desktop: bool,
+        name: String,
+    },
+}
+
+impl KillTarget {
+    /// Find what to terminate for a port: the container publishing it, or the owning process
+    pub fn for_port(port: &PortInfo) -> Result<Self, String> {
+        if let Some(container) = &port.container {
+            return Ok(KillTarget::Container {
+                runtime: container.runtime,
+                desktop: container.daemon_kind == Some(DockerDaemonKind::Desktop),
+                name: container.container_name.clone(),
+            });
+        }
+
+        let pid: u32 = port
+            .process_id
+            .parse()
+            .map_err(|_| format!("No process owns port {}", port.port))?;
+        if pid < MIN_PID {
+            return Err(format!("Cannot kill system process with PID: {pid}"));
+        }
+        Ok(match port.environment {
+            NetworkEnvironment::Windows => KillTarget::Windows { pid },
+            NetworkEnvironment::Wsl => KillTarget::Wsl { pid },
+        })
+    }
+
+    /// What terminating with `mode` will do, for the confirmation
+    pub fn describe(&self, mode: KillMode) -> String {
+        let grace = GRACE_PERIOD.as_secs();
+        match (self, mode) {
+            (KillTarget::Windows { pid }, KillMode::Graceful) => {
+                format!("Ask PID {pid} to close with taskkill, then force it after {grace} seconds")
+            }
+            (KillTarget::Windows { pid }, KillMode::Forced) => {
+                format!("Force PID {pid} to end with taskkill /F")
+            }
+            (KillTarget::Wsl { pid }, KillMode::Graceful) => {
+                format!("Send SIGTERM to PID {pid} in WSL, then SIGKILL after {grace} seconds")
+            }
+            (KillTarget::Wsl { pid }, KillMode::Forced) => {
+                format!("Send SIGKILL to PID {pid} in WSL")
+            }
+            (KillTarget::Container { runtime, name, .. }, KillMode::Graceful) => format!(
+                "Stop {runtime} container {name} ({} stop: SIGTERM, then SIGKILL after 10 seconds)",
+                runtime_cli(*runtime)
+            ),
+            (KillTarget::Container { runtime, name, .. }, KillMode::Forced) => format!(
+                "Kill {runtime} container {name} ({} kill)",
+                runtime_cli(*runtime)
+            ),
+        }
+    }
+
+    /// Terminate the process or container, returning what happened
+    ///
+    /// Graceful termination waits for the process to exit, which takes up to the grace period
+    /// (and `docker stop` up to its own timeout).
+    pub fn terminate(&self, mode: KillMode) -> Result<String, Box<dyn std::error::Error>> {
+        match self {
+            KillTarget::Windows { pid } => terminate_windows(*pid, mode),
+            KillTarget::Wsl { pid } => terminate_wsl(*pid, mode),
+            KillTarget::Container {
+                runtime,
+                desktop,
+                name,
+            } => {
+                let cli = runtime_cli(*runtime);
+                let action = match mode {
+                    KillMode::Graceful => "stop",
+                    KillMode::Forced => "kill",
+                };
+                // Docker Desktop's CLI is on Windows; the others run in the default distro
+                let output = if *desktop {
+                    Command::new("docker.exe").args([action, name]).output()?
+                } else {
+                    Command::new("wsl.exe")
+                        .args(["-e", cli, action, name])
+                        .output()?
+                };
+                if !output.status.success() {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    return Err(format!("{cli} {action} {name} failed: {}", stderr.trim()).into());
+                }
+                Ok(match mode {
+                    KillMode::Graceful => format!("Stopped {runtime} container {name}"),
+                    KillMode::Forced => format!("Killed {runtime} container {name}"),
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for KillTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KillTarget::Windows { pid } => write!(f, "Windows process {pid}"),
+            KillTarget::Wsl { pid } => write!(f, "WSL process {pid}"),
+            KillTarget::Container { runtime, name, .. } => {
+                write!(f, "{runtime} container {name}")
+            }
+        }
+    }
+}
+
+fn runtime_cli(runtime: ContainerRuntime) -> &'static str {
+    match runtime {
+        ContainerRuntime::Docker => "docker",
+        ContainerRuntime::Podman => "podman",
+    }
+}
+
+/// End a Windows process with taskkill, asking it to close first when graceful
+fn terminate_windows(pid: u32, mode: KillMode) -> Result<String, Box<dyn std::error::Error>> {
+    let pid_arg = pid.to_string();
+    if mode == KillMode::Graceful {
+        // Console programs and services cannot be asked to close; they are forced below
+        let _ = Command::new("taskkill").args(["/PID", &pid_arg]).output();
+        let deadline = Instant::now() + GRACE_PERIOD;
+        while Instant::now() < deadline {
+            if !windows_process_exists(pid)? {
+                return Ok(format!("Windows process {pid} closed"));
+            }
+            std::thread::sleep(Duration::from_millis(250));
+        }
+    }
+
+    let output = Command::new("taskkill")
+        .args(["/F", "/PID", &pid_arg])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Failed to kill process {pid}: {}", stderr.trim()).into());
+    }
+    Ok(match mode {
+        KillMode::Graceful => format!(
+            "Windows process {pid} did not close within {} seconds and was forced to end",
+            GRACE_PERIOD.as_secs()
+        ),
+        KillMode::Forced => format!("Windows process {pid} was forced to end"),
+    })
+}
+
+/// Whether a Windows process is still running
+///
+/// This function uses `tasklist`, whose CSV rows quote the PID.
+fn windows_process_exists(pid: u32) -> Result<bool, Box<dyn std::error::Error>> {
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {pid}"), "/NH", "/FO", "CSV"])
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).contains(&format!("\"{pid}\"")))
+}
+
+/// End a WSL process with kill as root, sending SIGTERM first when graceful
+///
+/// Waiting happens inside WSL, so a graceful kill costs one `wsl.exe` call.
+fn terminate_wsl(pid: u32, mode: KillMode) -> Result<String, Box<dyn std::error::Error>> {
+    // Exits 0 when the process ended after SIGTERM, 3 when it needed SIGKILL; a zombie has
+    // ended, only its parent has not collected it yet
+    let script = match mode {
+        KillMode::Graceful => format!(
+            "kill -TERM $1 || exit 1; i=0; while [ $i -lt {} ]; do \
+             s=$(cut -d')' -f2 /proc/$1/stat 2>/dev/null | cut -c2); \
+             [ -z \"$s\" ] || [ \"$s\" = Z ] && exit 0; sleep 0.25; i=$((i+1)); done; \
+             kill -KILL $1 && exit 3",
+            GRACE_PERIOD.as_millis() / 250
+        ),
+        KillMode::Forced => "kill -KILL $1".to_string(),
+    };
+    let output = Command::new("wsl.exe")
+        .args(["--user", "root", "-e", "sh", "-c", &script, "sh"])
+        .arg(pid.to_string())
+        .output()?;
+
+    match (mode, output.status.code()) {
+        (KillMode::Graceful, Some(0)) => Ok(format!("WSL process {pid} exited after SIGTERM")),
+        (KillMode::Graceful, Some(3)) => Ok(format!(
+            "WSL process {pid} ignored SIGTERM for {} seconds and was killed with SIGKILL",
+            GRACE_PERIOD.as_secs()
+        )),
+        (KillMode::Forced, Some(0)) => Ok(format!("WSL process {pid} was killed with SIGKILL")),
+        _ => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(format!("Failed to kill WSL process {pid}: {}", stderr.trim()).into())
+        }
+    }
+}
+
+/// A socket owned by a process
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProcessSocket {
+    pub protocol: String,
+    pub local_address: String,
+    /// Empty for listening and unconnected sockets
+    pub remote_address: String,
+    /// E.g. "LISTEN" or "ESTAB" in WSL and "Listen" or "Established" on Windows, empty for UDP
+    /// on Windows
+    pub state: String,
+}
+
+/// Details of a process, shown in the process details and before it is terminated
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDetails {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub executable: String,
+    pub command_line: String,
+    /// Start time as "YYYY-MM-DD HH:MM:SS" (UTC)
+    pub start_time: String,
+    /// Empty on Windows, which does not expose other processes' working directories
+    pub working_directory: String,
+    /// PIDs and names of the parent, its parent and so on
+    pub parents: Vec<(u32, String)>,
+    /// Control groups, one per line (WSL)
+    pub cgroup: String,
+    /// Systemd unit from the cgroup, e.g. "nginx.service" (WSL)
+    pub systemd_unit: String,
+    /// Container the process runs in, from the cgroup (WSL)
+    pub container: String,
+    /// Services hosted by the process, e.g. "Dnscache (DNS Client)" in an svchost (Windows)
+    pub services: Vec<String>,
+    /// Every socket of the process, listening or connected
+    pub sockets: Vec<ProcessSocket>,
+}
+
+impl ProcessDetails {
+    /// The parent chain, e.g. "bash (812) ← sshd (700) ← systemd (1)"
+    pub fn parent_chain(&self) -> String {
+        self.parents
+            .iter()
+            .map(|(pid, name)| format!("{name} ({pid})"))
+            .collect::<Vec<_>>()
+            .join(" ← ")
+    }
+}
+
+/// Get the details of a process by PID in its environment
+pub fn get_process_details(
+    pid: u32,
+    environment: &NetworkEnvironment,
+) -> Result<ProcessDetails, Box<dyn std::error::Error>> {
+    match environment {
+        NetworkEnvironment::Windows => get_windows_process_details(pid),
+        NetworkEnvironment::Wsl => get_wsl_process_details(pid),
+    }
+}
+
+/// Get the details of a Windows process
+///
+/// This function uses PowerShell: `Win32_Process` with its `GetOwner` method for the process
+/// and its parents, `Win32_Service` for the services it hosts, and `Get-NetTCPConnection` and
+/// `Get-NetUDPEndpoint` for its sockets. A parent that started after its child is a different
+/// process that reused the PID, which ends the chain.
+fn get_windows_process_details(pid: u32) -> Result<ProcessDetails, Box<dyn std::error::Error>> {
+    let script = format!(
+        r#"$p = Get-CimInstance Win32_Process -Filter 'ProcessId={pid}'
+if (-not $p) {{ exit }}
+$o = Invoke-CimMethod -InputObject $p -MethodName GetOwner
+$parents = @(); $child = $p
+while ($child.ParentProcessId -and $parents.Count -lt 32) {{
+    $q = Get-CimInstance Win32_Process -Filter "ProcessId=$($child.ParentProcessId)"
+    if (-not $q -or $q.CreationDate -gt $child.CreationDate) {{ break }}
+    $parents += [pscustomobject]@{{ Pid = $q.ProcessId; Name = $q.Name }}
+    $child = $q
+}}
+$services = @(Get-CimInstance Win32_Service -Filter 'ProcessId={pid}' | ForEach-Object {{ "$($_.Name) ($($_.DisplayName))" }})
+$sockets = @(Get-NetTCPConnection -OwningProcess {pid} -ErrorAction SilentlyContinue | ForEach-Object {{
+    [pscustomobject]@{{ Protocol = 'TCP'; LocalAddress = $_.LocalAddress; LocalPort = $_.LocalPort; RemoteAddress = $_.RemoteAddress; RemotePort = $_.RemotePort; State = "$($_.State)" }} }}) +
+    @(Get-NetUDPEndpoint -OwningProcess {pid} -ErrorAction SilentlyContinue | ForEach-Object {{
+    [pscustomobject]@{{ Protocol = 'UDP'; LocalAddress = $_.LocalAddress; LocalPort = $_.LocalPort; RemoteAddress = ''; RemotePort = 0; State = '' }} }})
+[pscustomobject]@{{
+    Name = $p.Name; Path = $p.ExecutablePath; CommandLine = $p.CommandLine
+    User = if ($o.User) {{ "$($o.Domain)\$($o.User)" }} else {{ '' }}
+    Started = $p.CreationDate.ToUniversalTime().ToString('yyyy-MM-dd HH:mm:ss')
+    Parents = $parents; Services = $services; Sockets = $sockets
+}} | ConvertTo-Json -Compress -Depth 4"#
+    );
+    let output = Command::new("powershell.exe")
+        .args(["-NoProfile", "-Command", &script])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Get-CimInstance failed: {}", stderr.trim()).into());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Err(format!("Windows process {pid} is not running").into());
+    }
+    parse_windows_process_details(pid, stdout.trim())
+}
+
+/// Parse the JSON written by the PowerShell script of [`get_windows_process_details`]
+fn parse_windows_process_details(
+    pid: u32,
+    json: &str,
+) -> Result<ProcessDetails, Box<dyn std::error::Error>> {
+    let value: serde_json::Value = serde_json::from_str(json)?;
+    let text = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    // Windows PowerShell writes an array of one element as that element
+    let items = |value: &serde_json::Value| match value {
+        serde_json::Value::Array(items) => items.clone(),
+        serde_json::Value::Null => Vec::new(),
+        item => vec![item.clone()],
+    };
+
+    Ok(ProcessDetails {
+        pid,
+        name: text(&value["Name"]),
+        user: text(&value["User"]),
+        executable: text(&value["Path"]),
+        command_line: text(&value["CommandLine"]),
+        start_time: text(&value["Started"]),
+        working_directory: String::new(),
+        parents: items(&value["Parents"])
+            .iter()
+            .filter_map(|parent| Some((parent["Pid"].as_u64()? as u32, text(&parent["Name"]))))
+            .collect(),
+        services: items(&value["Services"]).iter().map(text).collect(),
+        sockets: items(&value["Sockets"])
+            .iter()
+            .map(|socket| ProcessSocket {
+                protocol: text(&socket["Protocol"]),
+                local_address: socket_address(
+                    &text(&socket["LocalAddress"]),
+                    socket["LocalPort"].as_u64().unwrap_or(0),
+                ),
+                remote_address: match socket["RemotePort"].as_u64().unwrap_or(0) {
+                    0 => String::new(),
+                    port => socket_address(&text(&socket["RemoteAddress"]), port),
+                },
+                state: text(&socket["State"]),
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// Format an address and port, with brackets around IPv6 addresses
+fn socket_address(address: &str, port: u64) -> String {
+    if address.contains(':') {
+        format!("[{address}]:{port}")
+    } else {
+        format!("{address}:{port}")
+    }
+}
+
+/// Shell script printing the details of the process whose PID is `$1`, as "key=value" lines
+///
+/// The start time is computed from the boot time and the start in clock ticks (field 22 of
+/// `/proc/<pid>/stat`, the 20th after the command name), since `ps` is not always installed.
+const WSL_DETAILS_SCRIPT: &str = r#"p=$1; [ -d /proc/$p ] || exit 1
+echo "name=$(cat /proc/$p/comm)"
+echo "user=$(stat -c %U /proc/$p)"
+echo "exe=$(readlink /proc/$p/exe)"
+echo "cmdline=$(tr '\0' ' ' < /proc/$p/cmdline)"
+echo "cwd=$(readlink /proc/$p/cwd)"
+btime=$(awk '/^btime/ {print $2}' /proc/stat)
+ticks=$(cut -d')' -f2- /proc/$p/stat | awk '{print $20}')
+echo "started=$((btime + ticks / $(getconf CLK_TCK)))"
+while read -r line; do echo "cgroup=$line"; done < /proc/$p/cgroup
+q=$(awk '/^PPid:/ {print $2}' /proc/$p/status)
+while [ -n "$q" ] && [ "$q" -gt 0 ]; do
+    echo "parent=$q $(cat /proc/$q/comm)"
+    q=$(awk '/^PPid:/ {print $2}' /proc/$q/status 2>/dev/null)
+done
+ss -tuanp 2>/dev/null | grep "pid=$p," | while read -r line; do echo "socket=$line"; done
+"#;
+
+/// Get the details of a process in WSL
+///
+/// This function reads `/proc/<pid>` as root, since the process may belong to another user,
+/// and lists its sockets with `ss -tuanp`. The container is named from the docker or podman
+/// scope in the cgroup.
+fn get_wsl_process_details(pid: u32) -> Result<ProcessDetails, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["--user", "root", "-e", "sh", "-c", WSL_DETAILS_SCRIPT, "sh"])
+        .arg(pid.to_string())
+        .output()?;
+    if !output.status.success() {
+        return Err(format!("WSL process {pid} is not running").into());
+    }
+
+    let mut details = parse_wsl_process_details(pid, &String::from_utf8_lossy(&output.stdout));
+    if let Some((runtime, id)) = cgroup_container(&details.cgroup) {
+        let name = crate::network::get_all_containers()
+            .into_iter()
+            .find(|container| container.runtime == runtime && container.id.starts_with(&id))
+            .map(|container| container.name);
+        details.container = match name {
+            Some(name) => format!("{runtime} container {name}"),
+            None => format!("{runtime} container {id}"),
+        };
+    }
+    Ok(details)
+}
+
+/// Parse the output of [`WSL_DETAILS_SCRIPT`]
+fn parse_wsl_process_details(pid: u32, output: &str) -> ProcessDetails {
+    let mut details = ProcessDetails {
+        pid,
+        ..Default::default()
+    };
+    let mut cgroups = Vec::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key {
+            "name" => details.name = value.to_string(),
+            "user" => details.user = value.to_string(),
+            "exe" => details.executable = value.to_string(),
+            "cmdline" => details.command_line = value.to_string(),
+            "cwd" => details.working_directory = value.to_string(),
+            "started" => {
+                details.start_time = value
+                    .parse()
+                    .map(crate::storage::format_timestamp)
+                    .unwrap_or_default()
+            }
+            "cgroup" => cgroups.push(value.to_string()),
+            "parent" => {
+                if let Some((parent, name)) = value.split_once(' ') {
+                    if let Ok(parent) = parent.parse() {
+                        details.parents.push((parent, name.to_string()));
+                    }
+                }
+            }
+            // Format: "tcp ESTAB 0 0 172.20.1.2:22 172.20.1.1:50123 users:(("sshd",pid=812,fd=4))"
+            "socket" => {
+                let parts: Vec<&str> = value.split_whitespace().collect();
+                if parts.len() >= 6 {
+                    let remote = parts[5];
+                    details.sockets.push(ProcessSocket {
+                        protocol: parts[0].to_uppercase(),
+                        local_address: parts[4].to_string(),
+                        remote_address: if remote.ends_with(":*") {
+                            String::new()
+                        } else {
+                            remote.to_string()
+                        },
+                        state: parts[1].to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    details.cgroup = cgroups.join("\n");
+    details.systemd_unit = cgroup_unit(&details.cgroup).unwrap_or_default();
+    details
+}
+
+/// Systemd unit of a process from its cgroup
+///
+/// Format (cgroup v2): "0::/system.slice/nginx.service"; the unit is the last `.service` or
+/// `.scope` in the path, e.g. "session-2.scope" for a login.
+fn cgroup_unit(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .flat_map(|line| {
+            line.rsplit_once(':')
+                .map(|(_, path)| path)
+                .unwrap_or(line)
+                .rsplit('/')
+        })
+        .find(|part| part.ends_with(".service") || part.ends_with(".scope"))
+        .map(|unit| unit.to_string())
+}
+
+/// Container runtime and ID of a process from its cgroup
+///
+/// Formats: "/system.slice/docker-<id>.scope" (systemd driver), "/docker/<id>" (cgroupfs
+/// driver) and "/machine.slice/libpod-<id>.scope" or "libpod-<id>" (Podman).
+fn cgroup_container(cgroup: &str) -> Option<(ContainerRuntime, String)> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.split('/'))
+        .find_map(|part| {
+            let part = part.trim_end_matches(".scope");
+            let (runtime, id) = if let Some(id) = part.strip_prefix("docker-") {
+                (ContainerRuntime::Docker, id)
+            } else if let Some(id) = part.strip_prefix("libpod-") {
+                (ContainerRuntime::Podman, id.trim_start_matches("conmon-"))
+            } else {
+                (ContainerRuntime::Docker, part)
+            };
+            (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit()))
+                .then(|| (runtime, id[..12].to_string()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_ID: &str = "4f1d6c0e8b2a9d7c3e5f1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d";
+
+    #[test]
+    fn kills_published_ports_through_the_container_runtime() {
+        let mut port = PortInfo {
+            process_id: "2140".to_string(),
+            process_name: "com.docker.backend.exe".to_string(),
+            protocol: "TCP".to_string(),
+            port: "8080".to_string(),
+            direction: "Listening".to_string(),
+            network: "0.0.0.0:8080".to_string(),
+            environment: NetworkEnvironment::Windows,
+            container: Some(crate::network::ContainerPortOwner {
+                container_name: "web".to_string(),
+                daemon_kind: Some(DockerDaemonKind::Desktop),
+                ..Default::default()
+            }),
+        };
+        let target = |port: &PortInfo| KillTarget::for_port(port).unwrap();
+        assert_eq!(
+            target(&port),
+            KillTarget::Container {
+                runtime: ContainerRuntime::Docker,
+                desktop: true,
+                name: "web".to_string(),
+            }
+        );
+
+        let container = port.container.as_mut().unwrap();
+        container.daemon_kind = Some(DockerDaemonKind::Engine);
+        assert_eq!(
+            target(&port),
+            KillTarget::Container {
+                runtime: ContainerRuntime::Docker,
+                desktop: false,
+                name: "web".to_string(),
+            }
+        );
+
+        let container = port.container.as_mut().unwrap();
+        container.runtime = ContainerRuntime::Podman;
+        container.daemon_kind = None;
+        assert_eq!(container.daemon(), "Podman");
+        assert_eq!(
+            target(&port),
+            KillTarget::Container {
+                runtime: ContainerRuntime::Podman,
+                desktop: false,
+                name: "web".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn parses_windows_process_details() {
+        let json = r#"{"Name":"svchost.exe","Path":"C:\\Windows\\system32\\svchost.exe","CommandLine":"C:\\Windows\\system32\\svchost.exe -k NetworkService -p","User":"NT AUTHORITY\\NETWORK SERVICE","Started":"2026-10-18 07:12:03","Parents":[{"Pid":1044,"Name":"services.exe"},{"Pid":912,"Name":"wininit.exe"}],"Services":["Dnscache (DNS Client)","NlaSvc (Network Location Awareness)"],"Sockets":[{"Protocol":"TCP","LocalAddress":"::","LocalPort":5357,"RemoteAddress":"::","RemotePort":0,"State":"Listen"},{"Protocol":"TCP","LocalAddress":"192.168.1.20","LocalPort":50123,"RemoteAddress":"20.42.65.90","RemotePort":443,"State":"Established"},{"Protocol":"UDP","LocalAddress":"0.0.0.0","LocalPort":5353,"RemoteAddress":"","RemotePort":0,"State":""}]}"#;
+        let details = parse_windows_process_details(2468, json).unwrap();
+        assert_eq!(details.pid, 2468);
+        assert_eq!(details.name, "svchost.exe");
+        assert_eq!(details.user, "NT AUTHORITY\\NETWORK SERVICE");
+        assert_eq!(details.executable, "C:\\Windows\\system32\\svchost.exe");
+        assert_eq!(details.start_time, "2026-10-18 07:12:03");
+        assert_eq!(
+            details.parent_chain(),
+            "services.exe (1044) ← wininit.exe (912)"
+        );
+        assert_eq!(details.services.len(), 2);
+        assert_eq!(
+            details.sockets,
+            [
+                ProcessSocket {
+                    protocol: "TCP".to_string(),
+                    local_address: "[::]:5357".to_string(),
+                    remote_address: String::new(),
+                    state: "Listen".to_string(),
+                },
+                ProcessSocket {
+                    protocol: "TCP".to_string(),
+                    local_address: "192.168.1.20:50123".to_string(),
+                    remote_address: "20.42.65.90:443".to_string(),
+                    state: "Established".to_string(),
+                },
+                ProcessSocket {
+                    protocol: "UDP".to_string(),
+                    local_address: "0.0.0.0:5353".to_string(),
+                    remote_address: String::new(),
+                    state: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_windows_single_items_and_nulls() {
+        // Windows PowerShell writes an array of one element as that element, and null for no value
+        let json = r#"{"Name":"node.exe","Path":null,"CommandLine":null,"User":"","Started":"2026-10-18 09:00:00","Parents":{"Pid":7300,"Name":"cmd.exe"},"Services":"MyService (My Service)","Sockets":null}"#;
+        let details = parse_windows_process_details(7301, json).unwrap();
+        assert_eq!(details.executable, "");
+        assert_eq!(details.command_line, "");
+        assert_eq!(details.parents, [(7300, "cmd.exe".to_string())]);
+        assert_eq!(details.services, ["MyService (My Service)"]);
+        assert!(details.sockets.is_empty());
+        assert!(parse_windows_process_details(1, "not json").is_err());
+    }
+
+    #[test]
+    fn parses_wsl_process_details() {
+        let output = "name=nginx
+user=root
+exe=/usr/sbin/nginx
+cmdline=nginx: master process /usr/sbin/nginx -g daemon on; master_process on; 
+cwd=/
+started=1760771523
+cgroup=0::/system.slice/nginx.service
+parent=1 systemd
+parent=0 
+socket=tcp   LISTEN 0      511          0.0.0.0:80        0.0.0.0:*    users:((\"nginx\",pid=812,fd=6))
+socket=tcp   ESTAB  0      0         172.20.1.2:80     172.20.1.1:50123 users:((\"nginx\",pid=812,fd=9))
+socket=udp   UNCONN 0      0          [::]:5353            [::]:*    users:((\"nginx\",pid=812,fd=12))
+";
+        let details = parse_wsl_process_details(812, output);
+        assert_eq!(details.name, "nginx");
+        assert_eq!(details.user, "root");
+        assert_eq!(details.executable, "/usr/sbin/nginx");
+        assert_eq!(
+            details.command_line,
+            "nginx: master process /usr/sbin/nginx -g daemon on; master_process on;"
+        );
+        assert_eq!(details.working_directory, "/");
+        assert_eq!(details.start_time, "2025-10-18 07:12:03");
+        assert_eq!(details.parents, [(1, "systemd".to_string())]);
+        assert_eq!(details.cgroup, "0::/system.slice/nginx.service");
+        assert_eq!(details.systemd_unit, "nginx.service");
+        assert_eq!(details.sockets.len(), 3);
+        assert_eq!(details.sockets[0].protocol, "TCP");
+        assert_eq!(details.sockets[0].local_address, "0.0.0.0:80");
+        assert_eq!(details.sockets[0].remote_address, "");
+        assert_eq!(details.sockets[0].state, "LISTEN");
+        assert_eq!(details.sockets[1].remote_address, "172.20.1.1:50123");
+        assert_eq!(details.sockets[2].local_address, "[::]:5353");
+        assert_eq!(details.sockets[2].remote_address, "");
+    }
+
+    #[test]
+    fn keeps_every_cgroup_v1_line() {
+        let output = format!(
+            "name=node\ncgroup=12:pids:/docker/{DOCKER_ID}\n\
+             cgroup=1:name=systemd:/docker/{DOCKER_ID}\ncgroup=0::/\n"
+        );
+        let details = parse_wsl_process_details(4242, &output);
+        assert_eq!(details.cgroup.lines().count(), 3);
+        assert_eq!(details.systemd_unit, "");
+        assert_eq!(
+            cgroup_container(&details.cgroup),
+            Some((ContainerRuntime::Docker, DOCKER_ID[..12].to_string()))
+        );
+    }
+
+    #[test]
+    fn finds_the_systemd_unit_in_cgroups() {
+        assert_eq!(
+            cgroup_unit("0::/system.slice/nginx.service").as_deref(),
+            Some("nginx.service")
+        );
+        assert_eq!(
+            cgroup_unit("0::/user.slice/user-1000.slice/session-2.scope").as_deref(),
+            Some("session-2.scope")
+        );
+        assert_eq!(
+            cgroup_unit("0::/user.slice/user-1000.slice/user@1000.service/app.slice/app.scope")
+                .as_deref(),
+            Some("app.scope")
+        );
+        assert_eq!(
+            cgroup_unit("1:name=systemd:/system.slice/ssh.service\n0::/").as_deref(),
+            Some("ssh.service")
+        );
+        assert_eq!(cgroup_unit("0::/"), None);
+        assert_eq!(cgroup_unit(&format!("12:pids:/docker/{DOCKER_ID}")), None);
+    }
+
+    #[test]
+    fn finds_the_container_in_cgroups() {
+        let docker = Some((ContainerRuntime::Docker, DOCKER_ID[..12].to_string()));
+        let podman = Some((ContainerRuntime::Podman, DOCKER_ID[..12].to_string()));
+        // cgroup v2 with the systemd driver
+        assert_eq!(
+            cgroup_container(&format!("0::/system.slice/docker-{DOCKER_ID}.scope")),
+            docker
+        );
+        // cgroup v2 with the cgroupfs driver, and cgroup v1
+        assert_eq!(cgroup_container(&format!("0::/docker/{DOCKER_ID}")), docker);
+        assert_eq!(
+            cgroup_container(&format!("4:memory:/docker/{DOCKER_ID}")),
+            docker
+        );
+        // Podman, rootful and rootless, and its conmon
+        assert_eq!(
+            cgroup_container(&format!(
+                "0::/machine.slice/libpod-{DOCKER_ID}.scope/container"
+            )),
+            podman
+        );
+        assert_eq!(
+            cgroup_container(&format!(
+                "0::/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{DOCKER_ID}.scope"
+            )),
+            podman
+        );
+        assert_eq!(
+            cgroup_container(&format!(
+                "0::/machine.slice/libpod-conmon-{DOCKER_ID}.scope"
+            )),
+            podman
+        );
+        // Not a container, or not a full ID
+        assert_eq!(cgroup_container("0::/system.slice/nginx.service"), None);
+        assert_eq!(cgroup_container("0::/docker/4f1d6c0e8b2a"), None);
+    }
+}
@@ -0,0 +1,637 @@
+//! Port search
+//!
+//! The ports view searches the ports of both environments and their containers at once, and
+//! optionally snapshots of earlier port lists, with a small query language:
+//!
+//! ```text
+//! port:3000-3999 env:wsl proto:udp proc:node addr:0.0.0.0
+//! ```
+//!
+//! Keys are `port` (a port or range), `env` (windows or wsl), `proto`, `proc` (process name),
+//! `pid`, `addr` (bind address or its start), `container` (name or image), `iface` (interface
+//! name), `project` (Compose project) and `source` (live or a snapshot's time). A value may list
+//! alternatives separated by commas, a leading `-` negates a term, and words without a key
+//! match any column. Every term must match.
+//!
+//! Saved filters and snapshots are stored in `port_filters.json` and `port_snapshots.json` next
+//! to the probe history.
+
+use crate::network::{bind_host, filter_ports_for_interface, NetworkInterface, PortInfo};
+use crate::storage::{data_dir, format_timestamp, load_json, write_json};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Snapshots kept before the oldest is dropped
+const MAX_SNAPSHOTS: usize = 20;
+
+/// Source label of the current ports
+pub const LIVE_SOURCE: &str = "Live";
+
+/// A port as the ports view shows and searches it, flattened so snapshots can store it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortRecord {
+    /// "Windows" or "WSL"
+    pub environment: String,
+    pub protocol: String,
+    pub port: u16,
+    pub address: String,
+    pub process_name: String,
+    pub process_id: String,
+    /// Container publishing the port, with its port, e.g. "api:80"
+    pub container: String,
+    pub image: String,
+    pub compose_project: String,
+    /// Interfaces the port can be reached on
+    pub interfaces: Vec<String>,
+}
+
+impl PortRecord {
+    /// Describe a port, looking up the interfaces of its environment it can be reached on
+    pub fn new(port: &PortInfo, interfaces: &[NetworkInterface]) -> Self {
+        let container = port.container.clone().unwrap_or_default();
+        PortRecord {
+            environment: match port.environment {
+                crate::network::NetworkEnvironment::Windows => "Windows".to_string(),
+                crate::network::NetworkEnvironment::Wsl => "WSL".to_string(),
+            },
+            protocol: port.protocol.to_uppercase(),
+            port: port.port.parse().unwrap_or_default(),
+            address: bind_host(port).to_string(),
+            process_name: port.process_name.clone(),
+            process_id: port.process_id.clone(),
+            container: if container.container_name.is_empty() {
+                String::new()
+            } else {
+                format!("{}:{}", container.container_name, container.container_port)
+            },
+            image: container.image,
+            compose_project: container.compose_project,
+            interfaces: interfaces
+                .iter()
+                .filter(|interface| {
+                    !filter_ports_for_interface(interface, std::slice::from_ref(port)).is_empty()
+                })
+                .map(|interface| interface.name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// What a query term compares
+#[derive(Debug, Clone, PartialEq)]
+enum Field {
+    Port,
+    Environment,
+    Protocol,
+    Process,
+    Pid,
+    Address,
+    Container,
+    Interface,
+    Project,
+    Source,
+    Any,
+}
+
+/// One `key:value` or plain word of a query
+#[derive(Debug, Clone, PartialEq)]
+struct Term {
+    field: Field,
+    negated: bool,
+    /// Lowercase alternatives; for ports each is a range
+    values: Vec<String>,
+    ports: Vec<(u16, u16)>,
+}
+
+impl Term {
+    fn matches(&self, source: &str, record: &PortRecord) -> bool {
+        let contains = |text: &str| {
+            let text = text.to_lowercase();
+            self.values
+                .iter()
+                .any(|value| text.contains(value.as_str()))
+        };
+        let equals = |text: &str| self.values.contains(&text.to_lowercase());
+        let matched = match self.field {
+            Field::Port => self
+                .ports
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&record.port)),
+            Field::Environment => equals(&record.environment),
+            Field::Protocol => equals(&record.protocol),
+            Field::Process => contains(&record.process_name),
+            Field::Pid => equals(&record.process_id),
+            Field::Address => {
+                let address = record.address.to_lowercase();
+                self.values
+                    .iter()
+                    .any(|value| address.starts_with(value.as_str()))
+            }
+            Field::Container => contains(&record.container) || contains(&record.image),
+            Field::Interface => record.interfaces.iter().any(|name| contains(name)),
+            Field::Project => contains(&record.compose_project),
+            Field::Source => contains(source),
+            Field::Any => {
+                contains(&record.port.to_string())
+                    || contains(&record.environment)
+                    || contains(&record.protocol)
+                    || contains(&record.address)
+                    || contains(&record.process_name)
+                    || contains(&record.process_id)
+                    || contains(&record.container)
+                    || contains(&record.image)
+                    || contains(&record.compose_project)
+                    || record.interfaces.iter().any(|name| contains(name))
+            }
+        };
+        matched != self.negated
+    }
+}
+
+/// A parsed port search, e.g. `port:3000-3999 env:wsl -proc:docker-proxy`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PortQuery {
+    terms: Vec<Term>,
+}
+
+impl PortQuery {
+    /// Whether a port of `source` matches every term
+    pub fn matches(&self, source: &str, record: &PortRecord) -> bool {
+        self.terms.iter().all(|term| term.matches(source, record))
+    }
+}
+
+impl std::str::FromStr for PortQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut terms = Vec::new();
+        for word in s.split_whitespace() {
+            let (negated, word) = match word.strip_prefix('-') {
+                Some(rest) if !rest.is_empty() => (true, rest),
+                _ => (false, word),
+            };
+            // A colon in an IPv6 address is not a key, so only known keys split the word
+            let (field, value) = match word.split_once(':') {
+                Some((key, value)) => match parse_field(key) {
+                    Some(field) => (field, value),
+                    None if !key.is_empty()
+                        && key.chars().all(|c| c.is_ascii_alphabetic())
+                        && !value.contains(':') =>
+                    {
+                        return Err(format!(
+                            "Unknown filter key: {key} (use port, env, proto, proc, pid, addr, \
+                             container, iface, project or source)"
+                        ));
+                    }
+                    None => (Field::Any, word),
+                },
+                None => (Field::Any, word),
+            };
+            let values: Vec<String> = value
+                .split(',')
+                .map(|value| match field {
+                    Field::Address => value.trim_start_matches('[').trim_end_matches(']'),
+                    _ => value,
+                })
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_lowercase())
+                .collect();
+            if values.is_empty() {
+                return Err(format!("Missing value for {word}"));
+            }
+
+            let mut ports = Vec::new();
+            if field == Field::Port {
+                for value in &values {
+                    ports.push(parse_port_range(value)?);
+                }
+            }
+            if field == Field::Environment {
+                if let Some(value) = values
+                    .iter()
+                    .find(|value| !matches!(value.as_str(), "windows" | "wsl"))
+                {
+                    return Err(format!("Unknown environment: {value} (use windows or wsl)"));
+                }
+            }
+            terms.push(Term {
+                field,
+                negated,
+                values,
+                ports,
+            });
+        }
+        Ok(PortQuery { terms })
+    }
+}
+
+fn parse_field(key: &str) -> Option<Field> {
+    Some(match key.to_lowercase().as_str() {
+        "port" => Field::Port,
+        "env" => Field::Environment,
+        "proto" => Field::Protocol,
+        "proc" => Field::Process,
+        "pid" => Field::Pid,
+        "addr" => Field::Address,
+        "container" => Field::Container,
+        "iface" => Field::Interface,
+        "project" => Field::Project,
+        "source" => Field::Source,
+        _ => return None,
+    })
+}
+
+/// Parse "3000" or "3000-3999"
+fn parse_port_range(value: &str) -> Result<(u16, u16), String> {
+    let parse = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| format!("Invalid port: {port}"))
+    };
+    match value.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (parse(start)?, parse(end)?);
+            if start > end {
+                return Err(format!("Invalid port range: {value}"));
+            }
+            Ok((start, end))
+        }
+        None => parse(value).map(|port| (port, port)),
+    }
+}
+
+/// A column of the ports view to sort by
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum PortSortColumn {
+    Source,
+    Environment,
+    Protocol,
+    #[default]
+    Port,
+    Address,
+    Process,
+    Pid,
+    Container,
+}
+
+impl std::fmt::Display for PortSortColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortSortColumn::Source => write!(f, "Source"),
+            PortSortColumn::Environment => write!(f, "Environment"),
+            PortSortColumn::Protocol => write!(f, "Protocol"),
+            PortSortColumn::Port => write!(f, "Port"),
+            PortSortColumn::Address => write!(f, "Address"),
+            PortSortColumn::Process => write!(f, "Process"),
+            PortSortColumn::Pid => write!(f, "PID"),
+            PortSortColumn::Container => write!(f, "Container"),
+        }
+    }
+}
+
+impl std::str::FromStr for PortSortColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Source" => Ok(PortSortColumn::Source),
+            "Environment" => Ok(PortSortColumn::Environment),
+            "Protocol" => Ok(PortSortColumn::Protocol),
+            "Port" => Ok(PortSortColumn::Port),
+            "Address" => Ok(PortSortColumn::Address),
+            "Process" => Ok(PortSortColumn::Process),
+            "PID" => Ok(PortSortColumn::Pid),
+            "Container" => Ok(PortSortColumn::Container),
+            _ => Err(format!("Unknown port column: {s}")),
+        }
+    }
+}
+
+/// A port found by a search, with where it was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortMatch {
+    /// [`LIVE_SOURCE`] or the snapshot's label
+    pub source: String,
+    pub record: PortRecord,
+}
+
+/// Search the current ports, and the snapshots when given, sorted by `column`
+///
+/// Rows that compare equal keep the order of their port numbers.
+pub fn search_ports(
+    query: &PortQuery,
+    live: &[PortRecord],
+    snapshots: Option<&PortSnapshots>,
+    column: PortSortColumn,
+    ascending: bool,
+) -> Vec<PortMatch> {
+    let snapshot_records = snapshots
+        .into_iter()
+        .flat_map(|snapshots| snapshots.snapshots.iter().rev())
+        .flat_map(|snapshot| {
+            let source = snapshot.label();
+            snapshot
+                .ports
+                .iter()
+                .map(move |record| (source.clone(), record))
+        });
+    let mut matches: Vec<PortMatch> = live
+        .iter()
+        .map(|record| (LIVE_SOURCE.to_string(), record))
+        .chain(snapshot_records)
+        .filter(|(source, record)| query.matches(source, record))
+        .map(|(source, record)| PortMatch {
+            source,
+            record: record.clone(),
+        })
+        .collect();
+
+    matches.sort_by_key(|found| found.record.port);
+    matches.sort_by(|a, b| {
+        let ordering = match column {
+            PortSortColumn::Source => a.source.cmp(&b.source),
+            PortSortColumn::Environment => a.record.environment.cmp(&b.record.environment),
+            PortSortColumn::Protocol => a.record.protocol.cmp(&b.record.protocol),
+            PortSortColumn::Port => a.record.port.cmp(&b.record.port),
+            PortSortColumn::Address => a.record.address.cmp(&b.record.address),
+            PortSortColumn::Process => a
+                .record
+                .process_name
+                .to_lowercase()
+                .cmp(&b.record.process_name.to_lowercase()),
+            // PIDs sort as numbers, with "N/A" last
+            PortSortColumn::Pid => {
+                let pid = |found: &PortMatch| found.record.process_id.parse::<u32>().ok();
+                match (pid(a), pid(b)) {
+                    (Some(a), Some(b)) => a.cmp(&b),
+                    (a, b) => b.is_some().cmp(&a.is_some()),
+                }
+            }
+            PortSortColumn::Container => a.record.container.cmp(&b.record.container),
+        };
+        if ascending {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    });
+    matches
+}
+
+/// A query saved under a name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedPortFilter {
+    pub name: String,
+    pub query: String,
+}
+
+/// The saved port filters, in the order they were added
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortFilters {
+    pub filters: Vec<SavedPortFilter>,
+}
+
+impl PortFilters {
+    /// Load the filters, starting empty when there are none yet
+    pub fn load() -> Self {
+        load_json(&data_dir().join("port_filters.json"))
+    }
+
+    /// Write the filters to disk
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(&data_dir().join("port_filters.json"), self)
+    }
+
+    /// Save a query under a name, replacing a filter of the same name
+    pub fn save_filter(&mut self, name: &str, query: &str) {
+        let filter = SavedPortFilter {
+            name: name.to_string(),
+            query: query.to_string(),
+        };
+        match self
+            .filters
+            .iter_mut()
+            .find(|existing| existing.name == name)
+        {
+            Some(existing) => *existing = filter,
+            None => self.filters.push(filter),
+        }
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.filters.retain(|filter| filter.name != name);
+    }
+}
+
+/// The ports at one point in time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortSnapshot {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub ports: Vec<PortRecord>,
+}
+
+impl PortSnapshot {
+    /// Source label of the snapshot's ports, e.g. "Snapshot 2024-05-01 12:00:00"
+    pub fn label(&self) -> String {
+        format!("Snapshot {}", format_timestamp(self.timestamp))
+    }
+}
+
+/// Port snapshots, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PortSnapshots {
+    pub snapshots: Vec<PortSnapshot>,
+}
+
+impl PortSnapshots {
+    /// Load the snapshots, starting empty when there are none yet
+    pub fn load() -> Self {
+        load_json(&data_dir().join("port_snapshots.json"))
+    }
+
+    /// Write the snapshots to disk
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        write_json(&data_dir().join("port_snapshots.json"), self)
+    }
+
+    /// Add a snapshot of `ports`, dropping the oldest beyond the limit, and return it
+    pub fn take(&mut self, ports: Vec<PortRecord>) -> &PortSnapshot {
+        self.snapshots.push(PortSnapshot {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            ports,
+        });
+        let excess = self.snapshots.len().saturating_sub(MAX_SNAPSHOTS);
+        self.snapshots.drain(..excess);
+        &self.snapshots[self.snapshots.len() - 1]
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(
+        environment: &str,
+        protocol: &str,
+        port: u16,
+        address: &str,
+        process: &str,
+    ) -> PortRecord {
+        PortRecord {
+            environment: environment.to_string(),
+            protocol: protocol.to_string(),
+            port,
+            address: address.to_string(),
+            process_name: process.to_string(),
+            process_id: "1234".to_string(),
+            container: String::new(),
+            image: String::new(),
+            compose_project: String::new(),
+            interfaces: vec!["eth0".to_string()],
+        }
+    }
+
+    fn query(text: &str) -> PortQuery {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_port_ranges() {
+        assert_eq!(parse_port_range("80"), Ok((80, 80)));
+        assert_eq!(parse_port_range("3000-3999"), Ok((3000, 3999)));
+        assert_eq!(
+            parse_port_range("3999-3000"),
+            Err("Invalid port range: 3999-3000".to_string())
+        );
+        assert_eq!(
+            parse_port_range("70000"),
+            Err("Invalid port: 70000".to_string())
+        );
+        assert!(parse_port_range("80-").is_err());
+    }
+
+    #[test]
+    fn matches_keyed_terms() {
+        let node = record("WSL", "TCP", 3000, "0.0.0.0", "node");
+
+        assert!(
+            query("port:3000-3999 env:wsl proto:tcp proc:no addr:0.0").matches(LIVE_SOURCE, &node)
+        );
+        assert!(query("PORT:80,3000 iface:eth pid:1234").matches(LIVE_SOURCE, &node));
+        assert!(!query("port:80").matches(LIVE_SOURCE, &node));
+        assert!(!query("env:windows").matches(LIVE_SOURCE, &node));
+        assert!(!query("proto:udp").matches(LIVE_SOURCE, &node));
+        assert!(query("source:live").matches(LIVE_SOURCE, &node));
+        assert!(!query("source:snapshot").matches(LIVE_SOURCE, &node));
+    }
+
+    #[test]
+    fn negates_terms() {
+        let node = record("WSL", "TCP", 3000, "0.0.0.0", "node");
+
+        assert!(query("-proc:docker-proxy").matches(LIVE_SOURCE, &node));
+        assert!(!query("-env:wsl").matches(LIVE_SOURCE, &node));
+        // A lone dash is a word, not a negation
+        assert!(!query("-").matches(LIVE_SOURCE, &node));
+    }
+
+    #[test]
+    fn matches_words_against_any_column() {
+        let node = record("Windows", "TCP", 5432, "127.0.0.1", "postgres");
+
+        assert!(query("postgres").matches(LIVE_SOURCE, &node));
+        assert!(query("5432").matches(LIVE_SOURCE, &node));
+        assert!(!query("redis").matches(LIVE_SOURCE, &node));
+    }
+
+    #[test]
+    fn keeps_ipv6_addresses_as_values() {
+        let loopback = record("WSL", "TCP", 8080, "::1", "python3");
+
+        assert!(query("addr:[::1]").matches(LIVE_SOURCE, &loopback));
+        // Without a known key, an address is a plain word
+        assert!(query("::1").matches(LIVE_SOURCE, &loopback));
+        assert!(query("fe80::1").terms[0].field == Field::Any);
+    }
+
+    #[test]
+    fn rejects_invalid_queries() {
+        assert!("bogus:1"
+            .parse::<PortQuery>()
+            .unwrap_err()
+            .starts_with("Unknown filter key: bogus"));
+        assert_eq!(
+            "env:linux".parse::<PortQuery>().unwrap_err(),
+            "Unknown environment: linux (use windows or wsl)"
+        );
+        assert_eq!(
+            "proc:".parse::<PortQuery>().unwrap_err(),
+            "Missing value for proc:"
+        );
+        assert_eq!(
+            "port:abc".parse::<PortQuery>().unwrap_err(),
+            "Invalid port: abc"
+        );
+        assert_eq!("".parse::<PortQuery>(), Ok(PortQuery::default()));
+    }
+
+    #[test]
+    fn sorts_search_results() {
+        let live = vec![
+            record("WSL", "TCP", 8080, "0.0.0.0", "node"),
+            record("Windows", "UDP", 53, "0.0.0.0", "dns"),
+            record("WSL", "TCP", 22, "0.0.0.0", "sshd"),
+        ];
+        let ports = |column, ascending| {
+            search_ports(&PortQuery::default(), &live, None, column, ascending)
+                .iter()
+                .map(|found| found.record.port)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ports(PortSortColumn::Port, true), [22, 53, 8080]);
+        assert_eq!(ports(PortSortColumn::Port, false), [8080, 53, 22]);
+        // Equal environments keep the order of their ports
+        assert_eq!(ports(PortSortColumn::Environment, true), [22, 8080, 53]);
+    }
+
+    #[test]
+    fn searches_snapshots() {
+        let live = vec![record("WSL", "TCP", 8080, "0.0.0.0", "node")];
+        let snapshots = PortSnapshots {
+            snapshots: vec![PortSnapshot {
+                timestamp: 0,
+                ports: vec![record("WSL", "TCP", 9000, "0.0.0.0", "node")],
+            }],
+        };
+
+        let found = search_ports(
+            &query("proc:node"),
+            &live,
+            Some(&snapshots),
+            PortSortColumn::Port,
+            true,
+        );
+        let sources: Vec<&str> = found.iter().map(|found| found.source.as_str()).collect();
+        assert_eq!(
+            sources,
+            [LIVE_SOURCE, snapshots.snapshots[0].label().as_str()]
+        );
+        assert!(search_ports(
+            &query("source:live port:9000"),
+            &live,
+            Some(&snapshots),
+            PortSortColumn::Port,
+            true
+        )
+        .is_empty());
+    }
+}
@@ -3,7 +3,9 @@
 //! This module provides functionality to discover network interfaces and ports on Windows systems.
 //! Since this application runs in WSL, we use WSL's interoperability with Windows to gather data.
 
-use crate::network::{NetworkEnvironment, NetworkInterface, PortInfo};
+use crate::network::{
+    ExcludedPortRange, InterfaceAddress, NetworkEnvironment, NetworkInterface, PortInfo,
+};
 use serde::Deserialize;
 use std::process::Command;
 
@@ -31,6 +33,18 @@ struct WindowsNetAdapter {
     mac_address: Option<String>,
 }
 
+#[derive(Deserialize, Debug)]
+struct WindowsAdapterAddress {
+    #[serde(rename = "InterfaceAlias")]
+    interface_alias: String,
+    #[serde(rename = "InterfaceDescription")]
+    interface_description: Option<String>,
+    #[serde(rename = "IPAddress")]
+    ip_address: String,
+    #[serde(rename = "PrefixLength")]
+    prefix_length: u8,
+}
+
 /// Get network interfaces from Windows system
 ///
 /// This function uses WSL's interoperability with Windows to gather Windows network information.
@@ -166,6 +180,76 @@ pub fn get_network_interfaces() -> Result<Vec<NetworkInterface>, Box<dyn std::er
     Ok(interfaces)
 }
 
+/// Get the addresses of all Windows adapters with their prefix lengths and descriptions
+///
+/// Hidden adapters are included, since the vEthernet adapters of WSL and Hyper-V are hidden.
+pub fn get_adapter_addresses() -> Result<Vec<InterfaceAddress>, Box<dyn std::error::Error>> {
+    let output = Command::new("powershell.exe")
+        .args([
+            "-Command",
+            "$OutputEncoding = [console]::InputEncoding = [console]::OutputEncoding = New-Object System.Text.UTF8Encoding; $descriptions = @{}; Get-NetAdapter -IncludeHidden | ForEach-Object { $descriptions[$_.ifIndex] = $_.InterfaceDescription }; Get-NetIPAddress | ForEach-Object { [pscustomobject]@{ InterfaceAlias = $_.InterfaceAlias; InterfaceDescription = $descriptions[$_.InterfaceIndex]; IPAddress = $_.IPAddress; PrefixLength = $_.PrefixLength } } | ConvertTo-Json -Depth 2"
+        ])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Get-NetIPAddress failed: {stderr}").into());
+    }
+
+    // Handle both single object and array cases
+    let addresses =
+        serde_json::from_slice::<Vec<WindowsAdapterAddress>>(&output.stdout).or_else(|_| {
+            serde_json::from_slice::<WindowsAdapterAddress>(&output.stdout)
+                .map(|single| vec![single])
+        })?;
+
+    Ok(addresses
+        .into_iter()
+        .map(|address| InterfaceAddress {
+            interface: address.interface_alias,
+            description: address.interface_description.unwrap_or_default(),
+            // Link-local IPv6 addresses carry a zone index ("fe80::1%12")
+            address: address
+                .ip_address
+                .split('%')
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            prefix_length: address.prefix_length,
+            environment: NetworkEnvironment::Windows,
+        })
+        .collect())
+}
+
+/// Get the DNS servers configured on the Windows adapters, IPv4 first, without duplicates
+///
+/// This function uses `Get-DnsClientServerAddress`.
+pub fn get_dns_servers() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("powershell.exe")
+        .args([
+            "-Command",
+            "Get-DnsClientServerAddress -AddressFamily IPv4, IPv6 | ForEach-Object { $_.ServerAddresses }",
+        ])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Get-DnsClientServerAddress failed: {stderr}").into());
+    }
+
+    let mut servers: Vec<String> = Vec::new();
+    for server in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        // Site-local IPv6 resolvers (fec0:0:0:ffff::1) are placeholders of adapters without DNS
+        .filter(|server| !server.is_empty() && !server.starts_with("fec0:"))
+    {
+        if !servers.iter().any(|known| known == server) {
+            servers.push(server.to_string());
+        }
+    }
+    servers.sort_by_key(|server| server.contains(':'));
+    Ok(servers)
+}
+
 /// Get active ports from Windows system
 ///
 /// This function uses PowerShell Get-NetTCPConnection and netstat to get active port information with process names.
@@ -207,6 +291,70 @@ pub fn get_active_ports() -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
     Ok(ports)
 }
 
+/// Get the port ranges Windows excluded from use, for TCP and UDP over IPv4 and IPv6
+///
+/// This function uses `netsh int ipv4|ipv6 show excludedportrange protocol=tcp|udp`.
+pub fn get_excluded_port_ranges() -> Result<Vec<ExcludedPortRange>, Box<dyn std::error::Error>> {
+    let mut ranges = Vec::new();
+    for (ip_version, family) in [(4, "ipv4"), (6, "ipv6")] {
+        for protocol in ["tcp", "udp"] {
+            let output = Command::new("netsh")
+                .args([
+                    "int",
+                    family,
+                    "show",
+                    "excludedportrange",
+                    &format!("protocol={protocol}"),
+                ])
+                .output()?;
+            if !output.status.success() {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                return Err(
+                    format!("netsh show excludedportrange failed: {}", stdout.trim()).into(),
+                );
+            }
+            ranges.extend(parse_excluded_port_ranges(
+                &String::from_utf8_lossy(&output.stdout),
+                &protocol.to_uppercase(),
+                ip_version,
+            ));
+        }
+    }
+    Ok(ranges)
+}
+
+/// Parse `netsh int ipv4 show excludedportrange` output
+///
+/// Format (headers are localized, so only the numbers are read):
+/// ```text
+/// Start Port    End Port
+/// ----------    --------
+///       1080        1179
+///      50000       50059     *
+///
+/// * - Administered port exclusions.
+/// ```
+fn parse_excluded_port_ranges(
+    output: &str,
+    protocol: &str,
+    ip_version: u8,
+) -> Vec<ExcludedPortRange> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let (start, end) = (parts.first()?.parse().ok()?, parts.get(1)?.parse().ok()?);
+            Some(ExcludedPortRange {
+                protocol: protocol.to_string(),
+                ip_version,
+                start,
+                end,
+                administered: parts.get(2) == Some(&"*"),
+            })
+        })
+        .collect()
+}
+
 /// Parse PowerShell Get-NetTCPConnection JSON output
 fn parse_powershell_connections(
     json_str: &str,
@@ -248,6 +396,7 @@ fn parse_powershell_connections(
             direction: "LISTEN".to_string(),
             network: format!("{}:{}", conn.local_address, conn.local_port),
             environment: NetworkEnvironment::Windows,
+            container: None,
         };
 
         // Port parsed successfully
@@ -285,6 +434,7 @@ fn parse_netstat_output(output_str: &str) -> Vec<PortInfo> {
                 direction: "LISTENING".to_string(),
                 network: local_address.to_string(),
                 environment: NetworkEnvironment::Windows,
+                container: None,
             };
 
             // Port parsed from netstat
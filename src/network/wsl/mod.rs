@@ -3,7 +3,7 @@
 //! This module provides functionality to discover network ports within the WSL environment.
 //! It uses WSL-native commands executed via wsl.exe interop.
 
-use crate::network::{NetworkEnvironment, PortInfo};
+use crate::network::{InterfaceAddress, NetworkEnvironment, PortInfo};
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::process::Command;
@@ -23,6 +23,116 @@ pub fn get_active_ports() -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
     }
 }
 
+/// Get the names of the installed WSL distributions
+///
+/// This function uses `wsl.exe --list --quiet`.
+pub fn get_distributions() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["--list", "--quiet"])
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "wsl.exe --list failed with exit code {:?}",
+            output.status.code()
+        )
+        .into());
+    }
+
+    // wsl.exe writes its own output as UTF-16LE
+    let utf16: Vec<u16> = output
+        .stdout
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect();
+
+    Ok(String::from_utf16_lossy(&utf16)
+        .lines()
+        .map(|distro| distro.trim().trim_matches('\0').to_string())
+        .filter(|distro| !distro.is_empty())
+        .collect())
+}
+
+/// Get the default gateway of the default WSL distribution
+///
+/// This function uses `ip route show default`. With NAT networking the gateway is the Windows
+/// host's vEthernet (WSL) adapter; with mirrored networking it is the Windows default gateway.
+pub fn get_default_gateway() -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["-e", "ip", "route", "show", "default"])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ip route failed: {stderr}").into());
+    }
+
+    // Format: "default via 172.20.0.1 dev eth0 proto kernel"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| {
+            let mut parts = line.split_whitespace();
+            parts.find(|part| *part == "via")?;
+            parts.next().map(|gateway| gateway.to_string())
+        }))
+}
+
+/// Get the nameservers in `/etc/resolv.conf` of the default WSL distribution
+///
+/// With DNS tunneling this is 10.255.255.254, which only answers from inside WSL; with NAT
+/// networking it is usually the Windows host's vEthernet (WSL) address.
+pub fn get_nameservers() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["-e", "cat", "/etc/resolv.conf"])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Reading /etc/resolv.conf failed: {stderr}").into());
+    }
+
+    // Format: "nameserver 10.255.255.254"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            (parts.next() == Some("nameserver"))
+                .then(|| parts.next())
+                .flatten()
+                .map(|server| server.to_string())
+        })
+        .collect())
+}
+
+/// Get the addresses of the WSL interfaces with their prefix lengths
+///
+/// This function uses `ip -o addr show`.
+pub fn get_interface_addresses() -> Result<Vec<InterfaceAddress>, Box<dyn std::error::Error>> {
+    let output = Command::new("wsl.exe")
+        .args(["-e", "ip", "-o", "addr", "show"])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("ip addr failed: {stderr}").into());
+    }
+
+    // Format: "3: tun0    inet 10.8.0.2/24 brd 10.8.0.255 scope global tun0"
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let family = parts
+                .iter()
+                .position(|part| *part == "inet" || *part == "inet6")?;
+            let (address, prefix_length) = parts.get(family + 1)?.split_once('/')?;
+            Some(InterfaceAddress {
+                interface: parts.get(1)?.trim_end_matches(':').to_string(),
+                description: String::new(),
+                address: address.to_string(),
+                prefix_length: prefix_length.parse().ok()?,
+                environment: NetworkEnvironment::Wsl,
+            })
+        })
+        .collect())
+}
+
 /// Get ports using root access for complete process information
 fn get_ports_with_root_access() -> Result<Vec<PortInfo>, Box<dyn std::error::Error>> {
     // Use root access to get ports with process information
@@ -121,6 +231,7 @@ fn parse_ss_with_process_info(output_str: &str) -> Vec<PortInfo> {
                 direction: "LISTEN".to_string(),
                 network: local_address.to_string(),
                 environment: NetworkEnvironment::Wsl,
+                container: None,
             };
 
             ports.push(port_info);
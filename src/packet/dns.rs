@@ -0,0 +1,813 @@
+//! DNS queries
+//!
+//! This module resolves a name with a chosen record type against a chosen resolver and reports
+//! the answers with their TTLs, the response code and the latency. Resolvers differ a lot in a
+//! WSL setup (the Windows DNS client, the WSL DNS tunnel, Docker's embedded DNS), so comparing
+//! them shows which one is wrong.
+//!
+//! From Windows, queries to a specific server are sent in-process over UDP, and the system
+//! resolver is asked through `Resolve-DnsName`. From WSL and containers `dig` is used.
+
+use super::source::ProbeSource;
+use crate::network;
+use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Address of Docker's embedded DNS server, reachable from containers on user-defined networks
+pub const DOCKER_EMBEDDED_DNS: &str = "127.0.0.11";
+
+/// Record types that can be queried
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RecordType {
+    #[default]
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Txt,
+    Srv,
+    Ptr,
+}
+
+impl RecordType {
+    /// The type code on the wire
+    fn code(self) -> u16 {
+        match self {
+            RecordType::A => 1,
+            RecordType::Cname => 5,
+            RecordType::Ptr => 12,
+            RecordType::Mx => 15,
+            RecordType::Txt => 16,
+            RecordType::Aaaa => 28,
+            RecordType::Srv => 33,
+        }
+    }
+}
+
+impl std::fmt::Display for RecordType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordType::A => write!(f, "A"),
+            RecordType::Aaaa => write!(f, "AAAA"),
+            RecordType::Cname => write!(f, "CNAME"),
+            RecordType::Mx => write!(f, "MX"),
+            RecordType::Txt => write!(f, "TXT"),
+            RecordType::Srv => write!(f, "SRV"),
+            RecordType::Ptr => write!(f, "PTR"),
+        }
+    }
+}
+
+impl std::str::FromStr for RecordType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "A" => Ok(RecordType::A),
+            "AAAA" => Ok(RecordType::Aaaa),
+            "CNAME" => Ok(RecordType::Cname),
+            "MX" => Ok(RecordType::Mx),
+            "TXT" => Ok(RecordType::Txt),
+            "SRV" => Ok(RecordType::Srv),
+            "PTR" => Ok(RecordType::Ptr),
+            _ => Err(format!("Unknown record type: {s}")),
+        }
+    }
+}
+
+/// The resolver a query is sent to
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum DnsResolver {
+    /// The resolver the source itself uses: the Windows DNS client, or `/etc/resolv.conf`
+    #[default]
+    System,
+    /// The first DNS server configured on the Windows adapters
+    WindowsDns,
+    /// The first nameserver in `/etc/resolv.conf` of the default WSL distribution
+    WslResolvConf,
+    /// Docker's embedded DNS server, 127.0.0.11
+    DockerEmbedded,
+    Custom(IpAddr),
+}
+
+impl DnsResolver {
+    /// The server to query, `None` for the system resolver
+    pub fn server(&self) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        Ok(match self {
+            DnsResolver::System => None,
+            DnsResolver::WindowsDns => Some(
+                network::windows::get_dns_servers()?
+                    .into_iter()
+                    .next()
+                    .ok_or("No DNS servers are configured on Windows")?,
+            ),
+            DnsResolver::WslResolvConf => Some(
+                network::wsl::get_nameservers()?
+                    .into_iter()
+                    .next()
+                    .ok_or("No nameserver in /etc/resolv.conf of WSL")?,
+            ),
+            DnsResolver::DockerEmbedded => Some(DOCKER_EMBEDDED_DNS.to_string()),
+            DnsResolver::Custom(address) => Some(address.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for DnsResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DnsResolver::System => write!(f, "System"),
+            DnsResolver::WindowsDns => write!(f, "Windows DNS servers"),
+            DnsResolver::WslResolvConf => write!(f, "WSL resolv.conf"),
+            DnsResolver::DockerEmbedded => write!(f, "Docker embedded DNS"),
+            DnsResolver::Custom(address) => write!(f, "{address}"),
+        }
+    }
+}
+
+impl std::str::FromStr for DnsResolver {
+    type Err = String;
+
+    /// Parse a resolver label, or an IP address for a custom resolver
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "System" => Ok(DnsResolver::System),
+            "Windows DNS servers" => Ok(DnsResolver::WindowsDns),
+            "WSL resolv.conf" => Ok(DnsResolver::WslResolvConf),
+            "Docker embedded DNS" => Ok(DnsResolver::DockerEmbedded),
+            other => other
+                .parse()
+                .map(DnsResolver::Custom)
+                .map_err(|_| format!("Invalid DNS server address: {other}")),
+        }
+    }
+}
+
+/// A resource record of the answer section
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsRecord {
+    pub name: String,
+    pub record_type: String,
+    pub ttl: u32,
+    /// The record data as zone files show it, e.g. "10 mail.example.com." for MX
+    pub data: String,
+}
+
+impl std::fmt::Display for DnsRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.name, self.ttl, self.record_type, self.data
+        )
+    }
+}
+
+/// Represents the result of a DNS query
+#[derive(Debug, Clone)]
+pub struct DnsResult {
+    /// The name that was queried; reverse names for PTR queries of an address
+    pub name: String,
+    pub record_type: RecordType,
+    pub resolver: DnsResolver,
+    /// The server that answered, `None` for the system resolver of Windows
+    pub server: Option<String>,
+    /// Response code, e.g. "NOERROR" or "NXDOMAIN"
+    pub rcode: String,
+    pub answers: Vec<DnsRecord>,
+    /// Query time in milliseconds
+    pub latency: f64,
+    pub raw_output: String,
+}
+
+/// Resolve a name
+///
+/// # Arguments
+///
+/// * `source` - The environment to query from
+/// * `name` - The name to resolve; for PTR queries an IP address is turned into its reverse name
+/// * `record_type` - The record type to query
+/// * `resolver` - The resolver to query
+/// * `source_address` - The local address to send from
+/// * `timeout` - How long to wait for the answer
+///
+/// # Returns
+///
+/// A `Result` containing the answers, or an error if no answer arrived
+pub fn query(
+    source: &ProbeSource,
+    name: &str,
+    record_type: RecordType,
+    resolver: &DnsResolver,
+    source_address: Option<&str>,
+    timeout: Duration,
+) -> Result<DnsResult, Box<dyn std::error::Error>> {
+    let name = match (record_type, name.trim().parse::<IpAddr>()) {
+        (RecordType::Ptr, Ok(address)) => reverse_name(address),
+        _ => name.trim().trim_end_matches('.').to_string(),
+    };
+    let server = resolver.server()?;
+
+    let (rcode, answers, latency, raw_output) = match (source, &server) {
+        (ProbeSource::Windows, None) => query_windows_system(&name, record_type)?,
+        (ProbeSource::Windows, Some(server)) => {
+            query_udp(&name, record_type, server, source_address, timeout)?
+        }
+        (_, server) => query_dig(
+            source,
+            &name,
+            record_type,
+            server.as_deref(),
+            source_address,
+            timeout,
+        )?,
+    };
+
+    Ok(DnsResult {
+        name,
+        record_type,
+        resolver: resolver.clone(),
+        server,
+        rcode,
+        answers,
+        latency,
+        raw_output,
+    })
+}
+
+/// Response code, answers, latency in milliseconds and raw output of a query
+type QueryOutcome = (String, Vec<DnsRecord>, f64, String);
+
+/// Query a server over UDP from this process
+fn query_udp(
+    name: &str,
+    record_type: RecordType,
+    server: &str,
+    source_address: Option<&str>,
+    timeout: Duration,
+) -> Result<QueryOutcome, Box<dyn std::error::Error>> {
+    let server_address = SocketAddr::new(
+        server
+            .parse::<IpAddr>()
+            .map_err(|e| format!("Invalid DNS server address {server}: {e}"))?,
+        53,
+    );
+    let local_address: IpAddr = match source_address {
+        Some(address) => address
+            .parse()
+            .map_err(|e| format!("Invalid source address {address}: {e}"))?,
+        None if server_address.is_ipv4() => IpAddr::from([0, 0, 0, 0]),
+        None => IpAddr::from([0u16; 8]),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(local_address, 0))?;
+    socket.connect(server_address)?;
+    socket.set_read_timeout(Some(timeout))?;
+
+    // The low bits of the clock are random enough to match the answer to the query
+    let id = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|time| time.subsec_nanos() as u16)
+        .unwrap_or(0)
+        ^ std::process::id() as u16;
+    let request = encode_query(id, name, record_type)?;
+
+    let start = Instant::now();
+    socket.send(&request)?;
+    let mut buffer = [0; 4096];
+    loop {
+        let count = socket.recv(&mut buffer).map_err(|e| {
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) {
+                format!("No answer from {server} within {} s", timeout.as_secs_f32())
+            } else {
+                format!("Query to {server} failed: {e}")
+            }
+        })?;
+        let latency = start.elapsed().as_secs_f64() * 1000.0;
+        let response = &buffer[..count];
+        // Ignore late answers to other queries
+        if response.len() < 12 || u16::from_be_bytes([response[0], response[1]]) != id {
+            continue;
+        }
+        let (rcode, answers, truncated) = decode_response(response)?;
+        let mut raw_output = answers
+            .iter()
+            .map(DnsRecord::to_string)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if truncated {
+            raw_output.push_str("\n(truncated; the full answer needs TCP)");
+        }
+        return Ok((rcode, answers, latency, raw_output));
+    }
+}
+
+/// Output of the `Resolve-DnsName` script
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WindowsDnsAnswer {
+    rcode: String,
+    latency: f64,
+    #[serde(default)]
+    answers: Vec<WindowsDnsRecord>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WindowsDnsRecord {
+    name: String,
+    #[serde(rename = "Type")]
+    record_type: String,
+    #[serde(rename = "TTL")]
+    ttl: u32,
+    data: String,
+}
+
+/// Ask the Windows DNS client with `Resolve-DnsName`, as Windows applications do
+///
+/// Failures are reported by Win32 error codes, which map to the response codes.
+fn query_windows_system(
+    name: &str,
+    record_type: RecordType,
+) -> Result<QueryOutcome, Box<dyn std::error::Error>> {
+    let quoted_name = name.replace('\'', "''");
+    let script = format!(
+        "$OutputEncoding = [console]::InputEncoding = [console]::OutputEncoding = New-Object System.Text.UTF8Encoding; \
+         $stopwatch = [System.Diagnostics.Stopwatch]::StartNew(); \
+         try {{ \
+           $records = Resolve-DnsName -Name '{quoted_name}' -Type {record_type} -ErrorAction Stop; $stopwatch.Stop(); \
+           $answers = @($records | Where-Object {{ $_.Section -eq 'Answer' }} | ForEach-Object {{ \
+             $data = switch ($_.Type.ToString()) {{ \
+               'A' {{ $_.IPAddress }} 'AAAA' {{ $_.IPAddress }} 'CNAME' {{ $_.NameHost }} 'PTR' {{ $_.NameHost }} \
+               'MX' {{ \"$($_.Preference) $($_.NameExchange)\" }} 'TXT' {{ ($_.Strings | ForEach-Object {{ '\"' + $_ + '\"' }}) -join ' ' }} \
+               'SRV' {{ \"$($_.Priority) $($_.Weight) $($_.Port) $($_.NameTarget)\" }} default {{ $_.ToString() }} }}; \
+             [pscustomobject]@{{ Name = $_.Name; Type = $_.Type.ToString(); TTL = $_.TTL; Data = [string]$data }} }}); \
+           [pscustomobject]@{{ Rcode = 'NOERROR'; Latency = $stopwatch.Elapsed.TotalMilliseconds; Answers = $answers }} | ConvertTo-Json -Depth 3 \
+         }} catch {{ \
+           $stopwatch.Stop(); \
+           $rcode = switch ($_.Exception.NativeErrorCode) {{ 9001 {{ 'FORMERR' }} 9002 {{ 'SERVFAIL' }} 9003 {{ 'NXDOMAIN' }} 9004 {{ 'NOTIMP' }} 9005 {{ 'REFUSED' }} 9501 {{ 'NOERROR' }} default {{ $_.Exception.Message }} }}; \
+           [pscustomobject]@{{ Rcode = $rcode; Latency = $stopwatch.Elapsed.TotalMilliseconds; Answers = @() }} | ConvertTo-Json -Depth 3 \
+         }}"
+    );
+    let output = Command::new("powershell.exe")
+        .args(["-NoProfile", "-Command", &script])
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() || stdout.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Resolve-DnsName failed: {}", stderr.trim()).into());
+    }
+
+    let answer: WindowsDnsAnswer = serde_json::from_str(&stdout)?;
+    let answers = answer
+        .answers
+        .into_iter()
+        .map(|record| DnsRecord {
+            name: record.name,
+            record_type: record.record_type,
+            ttl: record.ttl,
+            data: record.data,
+        })
+        .collect();
+    Ok((answer.rcode, answers, answer.latency, stdout))
+}
+
+/// Query with `dig` in a Linux source environment
+fn query_dig(
+    source: &ProbeSource,
+    name: &str,
+    record_type: RecordType,
+    server: Option<&str>,
+    source_address: Option<&str>,
+    timeout: Duration,
+) -> Result<QueryOutcome, Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "+noall".to_string(),
+        "+comments".to_string(),
+        "+answer".to_string(),
+        "+stats".to_string(),
+        "+tries=1".to_string(),
+        format!("+time={}", timeout.as_secs().max(1)),
+    ];
+    if let Some(server) = server {
+        args.push(format!("@{server}"));
+    }
+    if let Some(source_address) = source_address {
+        args.extend(["-b".to_string(), source_address.to_string()]);
+    }
+    args.extend([name.to_string(), record_type.to_string()]);
+
+    let output = source.command("dig", &args).output().map_err(|e| {
+        format!("Could not run dig from {source} (install dnsutils or bind-tools): {e}")
+    })?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    let (rcode, answers, latency) = parse_dig_output(&stdout);
+    let rcode = rcode.ok_or_else(|| {
+        let message = stdout
+            .lines()
+            .chain(stderr.lines())
+            .find(|line| line.contains("timed out") || line.contains("no servers"))
+            .unwrap_or(stderr.trim());
+        format!(
+            "dig from {source} failed: {}",
+            message.trim_start_matches(";; ")
+        )
+    })?;
+    Ok((
+        rcode,
+        answers,
+        latency.unwrap_or(0.0),
+        format!("{stdout}\n{stderr}"),
+    ))
+}
+
+/// Parse the status, answer section and query time printed by `dig`
+fn parse_dig_output(output: &str) -> (Option<String>, Vec<DnsRecord>, Option<f64>) {
+    let mut rcode = None;
+    let mut answers = Vec::new();
+    let mut latency = None;
+    for line in output.lines().map(str::trim) {
+        // ";; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4242"
+        if let Some((_, status)) = line.split_once("status: ") {
+            rcode = status.split(',').next().map(|status| status.to_string());
+        } else if let Some(time) = line.strip_prefix(";; Query time: ") {
+            // "12 msec"
+            latency = time
+                .split_whitespace()
+                .next()
+                .and_then(|time| time.parse().ok());
+        } else if !line.is_empty() && !line.starts_with(';') {
+            // "example.com.  300  IN  A  93.184.216.34"
+            let mut parts = line.split_whitespace();
+            let (Some(name), Some(ttl), Some(_class), Some(record_type)) =
+                (parts.next(), parts.next(), parts.next(), parts.next())
+            else {
+                continue;
+            };
+            let Ok(ttl) = ttl.parse() else {
+                continue;
+            };
+            answers.push(DnsRecord {
+                name: name.to_string(),
+                record_type: record_type.to_string(),
+                ttl,
+                data: parts.collect::<Vec<_>>().join(" "),
+            });
+        }
+    }
+    (rcode, answers, latency)
+}
+
+/// The reverse lookup name of an address, e.g. "4.3.2.1.in-addr.arpa"
+fn reverse_name(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(address) => {
+            let [a, b, c, d] = address.octets();
+            format!("{d}.{c}.{b}.{a}.in-addr.arpa")
+        }
+        IpAddr::V6(address) => {
+            let nibbles = address
+                .octets()
+                .iter()
+                .rev()
+                .flat_map(|byte| [byte & 0x0f, byte >> 4])
+                .map(|nibble| format!("{nibble:x}"))
+                .collect::<Vec<_>>();
+            format!("{}.ip6.arpa", nibbles.join("."))
+        }
+    }
+}
+
+/// Encode a recursive query for one name and type
+fn encode_query(
+    id: u16,
+    name: &str,
+    record_type: RecordType,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut packet = Vec::with_capacity(512);
+    packet.extend_from_slice(&id.to_be_bytes());
+    // Recursion desired
+    packet.extend_from_slice(&0x0100u16.to_be_bytes());
+    // One question, no answer, authority or additional records
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]);
+    for label in name.split('.').filter(|label| !label.is_empty()) {
+        if label.len() > 63 {
+            return Err(format!("DNS label longer than 63 bytes: {label}").into());
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    packet.extend_from_slice(&record_type.code().to_be_bytes());
+    // Class IN
+    packet.extend_from_slice(&1u16.to_be_bytes());
+    Ok(packet)
+}
+
+/// Decode the response code, the answer records and the truncation flag of a response
+fn decode_response(
+    packet: &[u8],
+) -> Result<(String, Vec<DnsRecord>, bool), Box<dyn std::error::Error>> {
+    if packet.len() < 12 {
+        return Err("Truncated DNS header".into());
+    }
+    let flags = u16::from_be_bytes([packet[2], packet[3]]);
+    let truncated = flags & 0x0200 != 0;
+    let rcode = match flags & 0x000f {
+        0 => "NOERROR".to_string(),
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        code => format!("RCODE{code}"),
+    };
+    let questions = u16::from_be_bytes([packet[4], packet[5]]);
+    let answer_count = u16::from_be_bytes([packet[6], packet[7]]);
+
+    let mut offset = 12;
+    for _ in 0..questions {
+        let (_, end) = read_name(packet, offset)?;
+        // Type and class
+        offset = end + 4;
+    }
+
+    let mut answers = Vec::new();
+    for _ in 0..answer_count {
+        let (name, end) = read_name(packet, offset)?;
+        let header = packet.get(end..end + 10).ok_or("Truncated DNS record")?;
+        let type_code = u16::from_be_bytes([header[0], header[1]]);
+        let ttl = u32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+        let length = u16::from_be_bytes([header[8], header[9]]) as usize;
+        let data_start = end + 10;
+        let data = packet
+            .get(data_start..data_start + length)
+            .ok_or("Truncated DNS record data")?;
+        offset = data_start + length;
+
+        let (record_type, data) = match type_code {
+            1 if data.len() == 4 => (
+                "A".to_string(),
+                IpAddr::from(<[u8; 4]>::try_from(data)?).to_string(),
+            ),
+            28 if data.len() == 16 => (
+                "AAAA".to_string(),
+                IpAddr::from(<[u8; 16]>::try_from(data)?).to_string(),
+            ),
+            5 => ("CNAME".to_string(), read_name(packet, data_start)?.0),
+            12 => ("PTR".to_string(), read_name(packet, data_start)?.0),
+            15 if data.len() > 2 => (
+                "MX".to_string(),
+                format!(
+                    "{} {}",
+                    u16::from_be_bytes([data[0], data[1]]),
+                    read_name(packet, data_start + 2)?.0
+                ),
+            ),
+            16 => {
+                // One or more length-prefixed character strings
+                let mut strings = Vec::new();
+                let mut index = 0;
+                while index < data.len() {
+                    let end = (index + 1 + data[index] as usize).min(data.len());
+                    strings.push(format!(
+                        "\"{}\"",
+                        String::from_utf8_lossy(&data[index + 1..end])
+                    ));
+                    index = end;
+                }
+                ("TXT".to_string(), strings.join(" "))
+            }
+            33 if data.len() > 6 => (
+                "SRV".to_string(),
+                format!(
+                    "{} {} {} {}",
+                    u16::from_be_bytes([data[0], data[1]]),
+                    u16::from_be_bytes([data[2], data[3]]),
+                    u16::from_be_bytes([data[4], data[5]]),
+                    read_name(packet, data_start + 6)?.0
+                ),
+            ),
+            code => (
+                format!("TYPE{code}"),
+                data.iter().map(|byte| format!("{byte:02x}")).collect(),
+            ),
+        };
+        answers.push(DnsRecord {
+            name,
+            record_type,
+            ttl,
+            data,
+        });
+    }
+    Ok((rcode, answers, truncated))
+}
+
+/// Read a possibly compressed name, returning it with a trailing dot and the offset after it
+fn read_name(
+    packet: &[u8],
+    mut offset: usize,
+) -> Result<(String, usize), Box<dyn std::error::Error>> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // A compression pointer has to point before the part of the name it continues, so every
+    // jump goes further back and a loop is impossible
+    let mut segment_start = offset;
+    loop {
+        let length = *packet.get(offset).ok_or("Truncated DNS name")? as usize;
+        if length & 0xc0 == 0xc0 {
+            let low = *packet.get(offset + 1).ok_or("Truncated DNS name")? as usize;
+            end.get_or_insert(offset + 2);
+            let target = ((length & 0x3f) << 8) | low;
+            if target >= segment_start {
+                return Err("DNS name compression pointer does not point backwards".into());
+            }
+            segment_start = target;
+            offset = target;
+            continue;
+        }
+        if length == 0 {
+            let end = end.unwrap_or(offset + 1);
+            return Ok((format!("{}.", labels.join(".")), end));
+        }
+        let label = packet
+            .get(offset + 1..offset + 1 + length)
+            .ok_or("Truncated DNS label")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + length;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A response header with `flags` and the given section counts
+    fn header(flags: u16, questions: u16, answers: u16) -> Vec<u8> {
+        let mut packet = vec![0x12, 0x34];
+        packet.extend_from_slice(&flags.to_be_bytes());
+        packet.extend_from_slice(&questions.to_be_bytes());
+        packet.extend_from_slice(&answers.to_be_bytes());
+        packet.extend_from_slice(&[0, 0, 0, 0]);
+        packet
+    }
+
+    /// Append a record whose name is a pointer to `name_offset`
+    fn push_record(packet: &mut Vec<u8>, name_offset: u16, type_code: u16, data: &[u8]) {
+        packet.extend_from_slice(&(0xc000 | name_offset).to_be_bytes());
+        packet.extend_from_slice(&type_code.to_be_bytes());
+        packet.extend_from_slice(&1u16.to_be_bytes());
+        packet.extend_from_slice(&300u32.to_be_bytes());
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(data);
+    }
+
+    #[test]
+    fn decodes_compressed_answers() {
+        // The question starts right after the header, at offset 12
+        let mut packet = header(0x8180, 1, 6);
+        packet.extend(&encode_query(0, "example.com", RecordType::A).unwrap()[12..]);
+        push_record(&mut packet, 12, 1, &[93, 184, 216, 34]);
+        let mut aaaa = [0u8; 16];
+        aaaa[0] = 0x20;
+        aaaa[1] = 0x01;
+        aaaa[15] = 1;
+        push_record(&mut packet, 12, 28, &aaaa);
+        // "www" followed by a pointer to "example.com"
+        push_record(&mut packet, 12, 5, &[3, b'w', b'w', b'w', 0xc0, 12]);
+        push_record(
+            &mut packet,
+            12,
+            15,
+            &[0, 10, 4, b'm', b'a', b'i', b'l', 0xc0, 12],
+        );
+        push_record(&mut packet, 12, 16, b"\x05hello\x05world");
+        push_record(&mut packet, 12, 99, &[0xab, 0xcd]);
+
+        let (rcode, answers, truncated) = decode_response(&packet).unwrap();
+
+        assert_eq!(rcode, "NOERROR");
+        assert!(!truncated);
+        let records: Vec<String> = answers.iter().map(DnsRecord::to_string).collect();
+        assert_eq!(
+            records,
+            [
+                "example.com. 300 A 93.184.216.34",
+                "example.com. 300 AAAA 2001::1",
+                "example.com. 300 CNAME www.example.com.",
+                "example.com. 300 MX 10 mail.example.com.",
+                "example.com. 300 TXT \"hello\" \"world\"",
+                "example.com. 300 TYPE99 abcd",
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_the_response_code_and_truncation() {
+        let (rcode, answers, truncated) = decode_response(&header(0x8383, 0, 0)).unwrap();
+        assert_eq!(rcode, "NXDOMAIN");
+        assert!(answers.is_empty());
+        assert!(truncated);
+    }
+
+    #[test]
+    fn rejects_truncated_responses() {
+        assert!(decode_response(&[0x12, 0x34, 0x81]).is_err());
+
+        let mut packet = header(0x8180, 0, 1);
+        push_record(&mut packet, 12, 1, &[93, 184, 216, 34]);
+        packet.truncate(packet.len() - 2);
+        assert!(decode_response(&packet).is_err());
+    }
+
+    #[test]
+    fn rejects_compression_pointers_that_do_not_point_backwards() {
+        // A pointer to itself
+        let mut packet = header(0, 0, 0);
+        packet.extend_from_slice(&[0xc0, 12]);
+        assert!(read_name(&packet, 12).is_err());
+
+        // A label followed by a pointer back to the label
+        let mut packet = header(0, 0, 0);
+        packet.extend_from_slice(&[1, b'a', 0xc0, 12]);
+        assert!(read_name(&packet, 12).is_err());
+
+        // A pointer forward to a valid name
+        let mut packet = header(0, 0, 0);
+        packet.extend_from_slice(&[0xc0, 14, 1, b'a', 0]);
+        assert!(read_name(&packet, 12).is_err());
+    }
+
+    #[test]
+    fn reads_names_with_their_end_offset() {
+        let mut packet = header(0, 0, 0);
+        packet.extend_from_slice(&[3, b'c', b'o', b'm', 0]);
+        packet.extend_from_slice(&[7, b'e', b'x', b'a', b'm', b'p', b'l', b'e', 0xc0, 12]);
+
+        assert_eq!(read_name(&packet, 12).unwrap(), ("com.".to_string(), 17));
+        assert_eq!(
+            read_name(&packet, 17).unwrap(),
+            ("example.com.".to_string(), 27)
+        );
+    }
+
+    #[test]
+    fn parses_dig_output() {
+        let output = "\
+; <<>> DiG 9.18.18 <<>> example.com A
+;; global options: +cmd
+;; Got answer:
+;; ->>HEADER<<- opcode: QUERY, status: NOERROR, id: 4242
+;; flags: qr rd ra; QUERY: 1, ANSWER: 2, AUTHORITY: 0, ADDITIONAL: 1
+
+;; QUESTION SECTION:
+;example.com.\t\t\tIN\tA
+
+;; ANSWER SECTION:
+example.com.\t\t300\tIN\tCNAME\tedge.example.net.
+edge.example.net.\t60\tIN\tA\t93.184.216.34
+
+;; Query time: 12 msec
+;; SERVER: 10.255.255.254#53(10.255.255.254) (UDP)
+";
+        let (rcode, answers, latency) = parse_dig_output(output);
+
+        assert_eq!(rcode.as_deref(), Some("NOERROR"));
+        assert_eq!(latency, Some(12.0));
+        assert_eq!(
+            answers,
+            [
+                DnsRecord {
+                    name: "example.com.".to_string(),
+                    record_type: "CNAME".to_string(),
+                    ttl: 300,
+                    data: "edge.example.net.".to_string(),
+                },
+                DnsRecord {
+                    name: "edge.example.net.".to_string(),
+                    record_type: "A".to_string(),
+                    ttl: 60,
+                    data: "93.184.216.34".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_reverse_names() {
+        assert_eq!(
+            reverse_name("192.0.2.1".parse().unwrap()),
+            "1.2.0.192.in-addr.arpa"
+        );
+        assert_eq!(
+            reverse_name("2001:db8::1".parse().unwrap()),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
+}
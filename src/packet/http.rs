@@ -1,62 +1,659 @@
 //! HTTP over TCP implementation
 //!
 //! This module provides functionality to send HTTP requests and receive responses.
-//! It uses the `reqwest` crate to perform HTTP operations.
+//! Requests from WSL and containers run `curl` in the source environment, which reports how long
+//! each phase of the request took. Requests from Windows use the `reqwest` crate, unless
+//! [`HttpRequest::windows_curl`] asks for `curl.exe` (shipped with Windows 10 and later) to
+//! measure the same phases there.
+//! Requests are described by [`HttpRequest`], which supports any method, headers, a body,
+//! authentication, query parameters and a redirect policy.
 
-use reqwest::Client;
-use std::time::Duration;
+use super::source::ProbeSource;
+use reqwest::{Client, Method, Url};
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Marker separating the response from the status and timing written by `curl -w`
+const CURL_WRITE_OUT_MARKER: &str = "__WSLNETMAN_CURL__";
+
+/// Fields written by `curl -w` after the marker, separated by `|`
+///
+/// `proxy_used` needs curl 8.7; older versions write nothing for it.
+const CURL_WRITE_OUT_FIELDS: &str = "%{http_code}|%{time_namelookup}|%{time_connect}|\
+    %{time_appconnect}|%{time_starttransfer}|%{time_total}|%{remote_ip}|%{remote_port}|\
+    %{http_version}|%{num_redirects}|%{proxy_used}";
+
+/// Shown with results of requests sent with `reqwest`
+const REQWEST_NOTE: &str = "Connecting and the TLS handshake are counted in the time to first \
+    byte, and whether a proxy was used is unknown. Tick \"curl.exe on Windows\" to measure \
+    them apart.";
+
+/// Time spent in each phase of an HTTP request, in milliseconds
+///
+/// Phases that were not measured, or did not happen (TLS for plain HTTP), are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HttpTiming {
+    /// Resolving the host name
+    pub dns: Option<f64>,
+    /// Establishing the TCP connection
+    pub connect: Option<f64>,
+    /// The TLS handshake
+    pub tls: Option<f64>,
+    /// From sending the request until the first byte of the response (time to first byte)
+    pub ttfb: Option<f64>,
+    /// Receiving the rest of the response
+    pub download: Option<f64>,
+    /// Whether connecting and the TLS handshake are part of `ttfb`, as they were not measured
+    /// on their own
+    pub connect_in_ttfb: bool,
+}
+
+impl HttpTiming {
+    /// Split the cumulative times reported by `curl -w` (in seconds) into phases
+    fn from_curl(
+        namelookup: f64,
+        connect: f64,
+        appconnect: f64,
+        starttransfer: f64,
+        total: f64,
+    ) -> Self {
+        let ms = |seconds: f64| (seconds * 1000.0).max(0.0);
+        // appconnect stays 0 without a TLS handshake
+        let handshake_done = if appconnect > 0.0 {
+            appconnect
+        } else {
+            connect
+        };
+        HttpTiming {
+            dns: Some(ms(namelookup)),
+            connect: Some(ms(connect - namelookup)),
+            tls: (appconnect > 0.0).then(|| ms(appconnect - connect)),
+            ttfb: Some(ms(starttransfer - handshake_done)),
+            download: Some(ms(total - starttransfer)),
+            connect_in_ttfb: false,
+        }
+    }
+}
+
+impl std::fmt::Display for HttpTiming {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let phases = [
+            ("DNS", self.dns),
+            ("Connect", self.connect),
+            ("TLS", self.tls),
+            (
+                if self.connect_in_ttfb {
+                    "TTFB incl. connect"
+                } else {
+                    "TTFB"
+                },
+                self.ttfb,
+            ),
+            ("Download", self.download),
+        ];
+        let measured = phases
+            .iter()
+            .filter_map(|(name, time)| time.map(|time| format!("{name} {time:.1} ms")))
+            .collect::<Vec<_>>();
+        write!(f, "{}", measured.join(", "))
+    }
+}
 
 /// Represents the result of an HTTP request
 #[derive(Debug, Clone)]
 pub struct HttpRequestResult {
+    pub method: String,
     pub url: String,
     pub status_code: u16,
     pub response_time: f64, // in milliseconds
+    pub timing: HttpTiming,
+    /// The address the connection was made to, the proxy's when one was used
+    pub remote_address: Option<String>,
+    /// Negotiated HTTP version, e.g. "HTTP/2"
+    pub http_version: Option<String>,
+    /// Whether the request went through a proxy, `None` when this could not be determined
+    pub proxy_used: Option<bool>,
+    /// What the request could not measure and how to measure it, shown with the result
+    pub note: Option<String>,
+    /// Number of redirects followed
+    pub redirects: u32,
     pub response_body: String,
     pub response_headers: String,
     pub raw_output: String,
 }
 
-/// Send an HTTP request to the specified URL
+/// The body of an HTTP request
+#[derive(Debug, Clone, Default)]
+pub enum HttpBody {
+    #[default]
+    None,
+    /// Text sent as is, with an optional content type (default: "text/plain")
+    Raw {
+        content_type: Option<String>,
+        text: String,
+    },
+    /// JSON text, checked before sending
+    Json(String),
+    /// URL-encoded form fields
+    Form(Vec<(String, String)>),
+    /// A file read by WSLNetMan, also for requests sent from WSL or a container
+    File {
+        content_type: Option<String>,
+        path: PathBuf,
+    },
+}
+
+/// Content type and bytes of an encoded body
+pub type EncodedBody = (String, Vec<u8>);
+
+impl HttpBody {
+    /// Encode the body, returning its default content type and bytes
+    pub fn encode(&self) -> Result<Option<EncodedBody>, Box<dyn std::error::Error>> {
+        Ok(match self {
+            HttpBody::None => None,
+            HttpBody::Raw { content_type, text } => Some((
+                content_type
+                    .clone()
+                    .unwrap_or_else(|| "text/plain".to_string()),
+                text.as_bytes().to_vec(),
+            )),
+            HttpBody::Json(text) => {
+                serde_json::from_str::<serde_json::Value>(text)
+                    .map_err(|e| format!("Invalid JSON body: {e}"))?;
+                Some(("application/json".to_string(), text.as_bytes().to_vec()))
+            }
+            HttpBody::Form(fields) => Some((
+                "application/x-www-form-urlencoded".to_string(),
+                url::form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(fields)
+                    .finish()
+                    .into_bytes(),
+            )),
+            HttpBody::File { content_type, path } => Some((
+                content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                std::fs::read(path)
+                    .map_err(|e| format!("Could not read {}: {e}", path.display()))?,
+            )),
+        })
+    }
+}
+
+/// Credentials sent with an HTTP request
+#[derive(Debug, Clone, Default)]
+pub enum HttpAuth {
+    #[default]
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer(String),
+}
+
+/// Describes an HTTP request to send
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+    /// Any method, including custom ones
+    pub method: String,
+    pub url: String,
+    /// Query parameters appended to the URL
+    pub query: Vec<(String, String)>,
+    pub headers: Vec<(String, String)>,
+    pub body: HttpBody,
+    pub auth: HttpAuth,
+    /// Maximum number of redirects to follow, 0 to return redirects as they are
+    pub max_redirects: usize,
+    /// Accept invalid and self-signed certificates, e.g. development certificates
+    pub ignore_tls_errors: bool,
+    /// Send requests from Windows with `curl.exe` instead of `reqwest`, which also measures the
+    /// connect and TLS handshake times
+    pub windows_curl: bool,
+    pub timeout: Duration,
+}
+
+impl Default for HttpRequest {
+    fn default() -> Self {
+        HttpRequest {
+            method: "GET".to_string(),
+            url: String::new(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: HttpBody::None,
+            auth: HttpAuth::None,
+            max_redirects: 10,
+            ignore_tls_errors: false,
+            windows_curl: false,
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl HttpRequest {
+    /// Create a request with the default options
+    pub fn new(method: &str, url: &str) -> Self {
+        HttpRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The URL with the query parameters appended
+    pub fn full_url(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let mut url =
+            Url::parse(&self.url).map_err(|e| format!("Invalid URL {}: {e}", self.url))?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+        Ok(url.to_string())
+    }
+
+    /// Host and port of an HTTPS request, whose certificate chain can be inspected
+    pub fn tls_endpoint(&self) -> Option<(String, u16)> {
+        let url = Url::parse(&self.url).ok()?;
+        if url.scheme() != "https" {
+            return None;
+        }
+        Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+    }
+
+    /// Whether the request sets a header itself, compared case-insensitively
+    fn has_header(&self, name: &str) -> bool {
+        self.headers
+            .iter()
+            .any(|(header, _)| header.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Whether Windows has `curl.exe`, checked once
+fn windows_curl_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        Command::new("curl.exe")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    })
+}
+
+/// Send an HTTP request from a source environment
+///
+/// Requests run `curl` in WSL and containers. Windows requests use `reqwest` in this process,
+/// or `curl.exe` when the request asks for it.
+///
+/// # Arguments
+///
+/// * `source` - The environment to send the request from
+/// * `request` - The method, URL, headers, body and options of the request
+/// * `source_address` - The local address to connect from, binding the request to its interface
+///
+/// # Returns
+///
+/// A `Result` containing the HTTP request result or an error
+pub async fn send_http_request_from(
+    source: &ProbeSource,
+    request: &HttpRequest,
+    source_address: Option<&str>,
+) -> Result<HttpRequestResult, Box<dyn std::error::Error>> {
+    if !source.is_linux() {
+        if !request.windows_curl {
+            return send_http_request(request, source_address).await;
+        }
+        if !windows_curl_available() {
+            return Err("curl.exe is not available on Windows".into());
+        }
+    }
+
+    let source = source.clone();
+    let request = request.clone();
+    let source_address = source_address.map(|address| address.to_string());
+    tokio::task::spawn_blocking(move || {
+        send_http_request_curl(&source, &request, source_address.as_deref())
+            .map_err(|e| e.to_string())
+    })
+    .await?
+    .map_err(|e| e.into())
+}
+
+/// Send an HTTP request with `curl` in the source environment
+///
+/// The body is passed on standard input, so files and large bodies do not have to exist in
+/// the source environment. Requests with credentials pass them, the headers and the body in a
+/// curl config on standard input instead, so they stay out of the process list.
+fn send_http_request_curl(
+    source: &ProbeSource,
+    request: &HttpRequest,
+    source_address: Option<&str>,
+) -> Result<HttpRequestResult, Box<dyn std::error::Error>> {
+    let url = request.full_url()?;
+    let body = request.body.encode()?;
+
+    let mut args = vec!["-sS".to_string(), "-i".to_string()];
+    if let Some(source_address) = source_address {
+        // --interface takes an interface name, address or host name
+        args.extend(["--interface".to_string(), source_address.to_string()]);
+    }
+    // -X also applies to the requests after a redirect, so it is only used for methods that
+    // curl cannot pick itself. curl waits for a body after -X HEAD, --head does not.
+    let method = request.method.to_uppercase();
+    match method.as_str() {
+        "HEAD" => args.push("--head".to_string()),
+        "POST" => {}
+        "GET" if body.is_none() => {}
+        _ => args.extend(["-X".to_string(), request.method.clone()]),
+    }
+
+    // With credentials, curl reads its options from a config on standard input, so they do not
+    // show up in the process list. The body then has to be part of that config.
+    let mut config = match request.auth {
+        HttpAuth::None => None,
+        _ => Some(Vec::new()),
+    };
+    let mut option = |args: &mut Vec<String>, name: &str, value: &[u8]| match &mut config {
+        Some(config) => config.extend(curl_config_line(name, value)),
+        None => args.extend([
+            format!("--{name}"),
+            String::from_utf8_lossy(value).to_string(),
+        ]),
+    };
+    for (name, value) in &request.headers {
+        option(&mut args, "header", format!("{name}: {value}").as_bytes());
+    }
+    match &request.auth {
+        HttpAuth::None => {}
+        HttpAuth::Basic { username, password } => {
+            option(
+                &mut args,
+                "user",
+                format!("{username}:{password}").as_bytes(),
+            );
+        }
+        HttpAuth::Bearer(token) => {
+            option(
+                &mut args,
+                "header",
+                format!("Authorization: Bearer {token}").as_bytes(),
+            );
+        }
+    }
+    if let Some((content_type, _)) = &body {
+        if !request.has_header("Content-Type") {
+            option(
+                &mut args,
+                "header",
+                format!("Content-Type: {content_type}").as_bytes(),
+            );
+        }
+    }
+    match (&mut config, &body) {
+        (Some(config), Some((_, bytes))) => {
+            // A config is read as text, where a NUL byte ends the value
+            if bytes.contains(&0) {
+                return Err(
+                    "curl cannot read a binary body together with credentials; send the request from Windows"
+                        .into(),
+                );
+            }
+            config.extend(curl_config_line("data-raw", bytes));
+        }
+        (None, Some(_)) => args.extend(["--data-binary".to_string(), "@-".to_string()]),
+        // A POST without a body needs empty data, or curl sends a GET. The empty "Content-Type:"
+        // drops the form content type curl adds for data.
+        (config, None) if method == "POST" => {
+            if !request.has_header("Content-Type") {
+                args.extend(["-H".to_string(), "Content-Type:".to_string()]);
+            }
+            match config {
+                Some(config) => config.extend(curl_config_line("data-raw", b"")),
+                None => args.extend(["--data-raw".to_string(), String::new()]),
+            }
+        }
+        _ => {}
+    }
+    if config.is_some() {
+        args.extend(["-K".to_string(), "-".to_string()]);
+    }
+    if request.max_redirects > 0 {
+        args.extend([
+            "-L".to_string(),
+            "--max-redirs".to_string(),
+            request.max_redirects.to_string(),
+        ]);
+    }
+    if request.ignore_tls_errors {
+        args.push("-k".to_string());
+    }
+    args.extend([
+        "--max-time".to_string(),
+        request.timeout.as_secs().max(1).to_string(),
+        "-w".to_string(),
+        format!(r"\n{CURL_WRITE_OUT_MARKER}{CURL_WRITE_OUT_FIELDS}"),
+        url.clone(),
+    ]);
+
+    let program = if source.is_linux() {
+        "curl"
+    } else {
+        "curl.exe"
+    };
+    let mut child = source
+        .command(program, &args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run curl from {source}: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        match (&config, &body) {
+            (Some(config), _) => stdin.write_all(config)?,
+            (None, Some((_, bytes))) => stdin.write_all(bytes)?,
+            (None, None) => {}
+        }
+        // Dropping stdin ends the body
+    }
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // Drop the warning of curl versions without proxy_used
+    let stderr = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .filter(|line| !line.starts_with("curl: unknown --write-out variable"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if !output.status.success() {
+        return Err(format!("curl from {source} failed: {}", stderr.trim()).into());
+    }
+    let (response, write_out) = stdout
+        .rsplit_once(CURL_WRITE_OUT_MARKER)
+        .ok_or_else(|| format!("Unexpected curl output from {source}: {stdout}"))?;
+    let fields = write_out.trim().split('|').collect::<Vec<_>>();
+    let field = |index: usize| fields.get(index).copied().unwrap_or("").trim();
+    let seconds = |index: usize| field(index).parse::<f64>().unwrap_or(0.0);
+    let status_code = field(0).parse().unwrap_or(0);
+    let timing = HttpTiming::from_curl(seconds(1), seconds(2), seconds(3), seconds(4), seconds(5));
+    let response_time = seconds(5) * 1000.0;
+    let remote_address = match (field(6), field(7)) {
+        ("", _) => None,
+        (ip, port) if ip.contains(':') => Some(format!("[{ip}]:{port}")),
+        (ip, port) => Some(format!("{ip}:{port}")),
+    };
+    let http_version = Some(field(8))
+        .filter(|version| !version.is_empty() && *version != "0")
+        .map(|version| format!("HTTP/{version}"));
+    let redirects = field(9).parse().unwrap_or(0);
+    let proxy_used = match field(10) {
+        "1" => Some(true),
+        "0" => Some(false),
+        _ => None,
+    };
+
+    // Strip interim responses (100 Continue) and redirects so only the final response remains
+    let mut rest = response.trim_end_matches('\n');
+    let mut header_block = "";
+    while rest.starts_with("HTTP/") {
+        match rest.split_once("\r\n\r\n") {
+            Some((headers, body)) => {
+                header_block = headers;
+                rest = body;
+            }
+            None => {
+                header_block = rest;
+                rest = "";
+            }
+        }
+    }
+    // Skip the status line
+    // A response without a body (HEAD) leaves the blank line's carriage return behind
+    let response_headers = header_block
+        .lines()
+        .skip(1)
+        .map(|line| line.trim_end_matches('\r'))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let response_body = rest.to_string();
+
+    Ok(HttpRequestResult {
+        method: request.method.clone(),
+        url,
+        status_code,
+        response_time,
+        timing,
+        remote_address,
+        http_version,
+        proxy_used,
+        note: None,
+        redirects,
+        response_body,
+        response_headers,
+        raw_output: format!("{stdout}\n{stderr}"),
+    })
+}
+
+/// Format an option of a curl config file, quoting and escaping its value
+fn curl_config_line(name: &str, value: &[u8]) -> Vec<u8> {
+    let mut line = format!("{name} = \"").into_bytes();
+    for &byte in value {
+        match byte {
+            b'\\' => line.extend_from_slice(b"\\\\"),
+            b'"' => line.extend_from_slice(b"\\\""),
+            b'\n' => line.extend_from_slice(b"\\n"),
+            b'\r' => line.extend_from_slice(b"\\r"),
+            b'\t' => line.extend_from_slice(b"\\t"),
+            0x0b => line.extend_from_slice(b"\\v"),
+            _ => line.push(byte),
+        }
+    }
+    line.extend_from_slice(b"\"\n");
+    line
+}
+
+/// Send an HTTP request from this process
 ///
-/// This function uses the `reqwest` crate to send HTTP requests.
+/// This function uses the `reqwest` crate to send HTTP requests. Host names are resolved up
+/// front to time DNS separately; connecting and the TLS handshake are part of the time to
+/// first byte, and whether a proxy was used is not known.
 ///
 /// # Arguments
 ///
-/// * `url` - The URL to send the HTTP request to
-/// * `method` - The HTTP method to use (default: "GET")
-/// * `timeout` - The timeout for the request in seconds (default: 30)
+/// * `request` - The method, URL, headers, body and options of the request
+/// * `source_address` - The local IP address to connect from
 ///
 /// # Returns
 ///
 /// A `Result` containing the HTTP request result or an error
 pub async fn send_http_request(
-    url: &str,
-    method: Option<&str>,
-    timeout: Option<u64>,
+    request: &HttpRequest,
+    source_address: Option<&str>,
 ) -> Result<HttpRequestResult, Box<dyn std::error::Error>> {
-    let method = method.unwrap_or("GET");
-    let timeout = timeout.unwrap_or(30);
+    let url = request.full_url()?;
+    let method = Method::from_bytes(request.method.as_bytes())
+        .map_err(|e| format!("Invalid HTTP method {}: {e}", request.method))?;
+
+    // Binding to a local address makes the connection leave through that address's interface.
+    // reqwest takes no scope ID, so the zone of a link-local address ("fe80::1%12") is dropped
+    // and the address alone selects the interface.
+    let local_address = source_address
+        .map(|address| {
+            let ip = address.split('%').next().unwrap_or(address);
+            ip.parse::<IpAddr>()
+                .map_err(|e| format!("Invalid source address {address}: {e}"))
+        })
+        .transpose()?;
+
+    // Same limit as Policy::limited, counting the redirects that were followed
+    let redirects = Arc::new(AtomicU32::new(0));
+    let redirect_policy = {
+        let redirects = redirects.clone();
+        let max_redirects = request.max_redirects;
+        reqwest::redirect::Policy::custom(move |attempt| {
+            // The previous URLs include the original one
+            let followed = attempt.previous().len();
+            if max_redirects == 0 {
+                attempt.stop()
+            } else if followed > max_redirects {
+                attempt.error("too many redirects")
+            } else {
+                redirects.store(followed as u32, Ordering::Relaxed);
+                attempt.follow()
+            }
+        })
+    };
+
+    // Resolve the host name here, so the lookup is timed on its own and not repeated
+    let parsed = Url::parse(&url)?;
+    let mut dns = None;
+    let mut resolved = Vec::new();
+    if let (Some(url::Host::Domain(host)), Some(port)) =
+        (parsed.host(), parsed.port_or_known_default())
+    {
+        let lookup_time = Instant::now();
+        resolved = tokio::net::lookup_host((host, port)).await?.collect();
+        dns = Some(lookup_time.elapsed().as_secs_f64() * 1000.0);
+    }
 
     // Create a client with the specified timeout
-    let client = Client::builder()
-        .timeout(Duration::from_secs(timeout))
+    let mut client = Client::builder();
+    if !resolved.is_empty() {
+        if let Some(host) = parsed.host_str() {
+            client = client.resolve_to_addrs(host, &resolved);
+        }
+    }
+    let client = client
+        .timeout(request.timeout)
+        .local_address(local_address)
+        .redirect(redirect_policy)
+        .danger_accept_invalid_certs(request.ignore_tls_errors)
         .build()?;
 
-    // Record the start time
-    let start_time = std::time::Instant::now();
-
-    // Send the request based on the method
-    let response = match method {
-        "GET" => client.get(url).send().await?,
-        "POST" => client.post(url).send().await?,
-        "PUT" => client.put(url).send().await?,
-        "DELETE" => client.delete(url).send().await?,
-        _ => return Err(format!("Unsupported HTTP method: {method}").into()),
+    let mut builder = client.request(method, &url);
+    for (name, value) in &request.headers {
+        builder = builder.header(name, value);
+    }
+    builder = match &request.auth {
+        HttpAuth::None => builder,
+        HttpAuth::Basic { username, password } => builder.basic_auth(username, Some(password)),
+        HttpAuth::Bearer(token) => builder.bearer_auth(token),
     };
+    if let Some((content_type, bytes)) = request.body.encode()? {
+        if !request.has_header("Content-Type") {
+            builder = builder.header(reqwest::header::CONTENT_TYPE, content_type);
+        }
+        builder = builder.body(bytes);
+    }
 
-    // Calculate the response time
-    let response_time = start_time.elapsed().as_millis() as f64;
+    let send_time = Instant::now();
+    let response = builder.send().await?;
+    let ttfb = send_time.elapsed().as_secs_f64() * 1000.0;
+    let remote_address = response.remote_addr().map(|address| address.to_string());
+    let http_version = format!("{:?}", response.version());
 
     // Get the status code
     let status_code = response.status().as_u16();
@@ -70,12 +667,30 @@ pub async fn send_http_request(
         .join("\n");
 
     // Get the response body
+    let download_time = Instant::now();
     let response_body = response.text().await?;
+    let download = download_time.elapsed().as_secs_f64() * 1000.0;
+
+    // Calculate the response time, leaving out setting up the client
+    let response_time = dns.unwrap_or(0.0) + ttfb + download;
 
     let result = HttpRequestResult {
-        url: url.to_string(),
+        method: request.method.clone(),
+        url,
         status_code,
         response_time,
+        timing: HttpTiming {
+            dns,
+            ttfb: Some(ttfb),
+            download: Some(download),
+            connect_in_ttfb: true,
+            ..Default::default()
+        },
+        remote_address,
+        http_version: Some(http_version),
+        proxy_used: None,
+        redirects: redirects.load(Ordering::Relaxed),
+        note: Some(REQWEST_NOTE.to_string()),
         response_body: response_body.clone(),
         response_headers: response_headers.clone(),
         raw_output: format!(
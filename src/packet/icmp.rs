@@ -0,0 +1,462 @@
+//! Native ICMP echo
+//!
+//! This module sends ICMP echo requests from the WSLNetMan process itself instead of running
+//! `ping.exe`. It prefers unprivileged ICMP datagram sockets and falls back to raw sockets,
+//! which need administrator rights on Windows. Both IPv4 and IPv6 are supported. Raw sockets
+//! also receive the ICMP errors traceroute matches to its probes.
+
+use super::ping::{EchoReply, PingOptions, PingResult, PingStatistics};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::mem::MaybeUninit;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+const ECHO_REQUEST_V4: u8 = 8;
+const ECHO_REPLY_V4: u8 = 0;
+const DESTINATION_UNREACHABLE_V4: u8 = 3;
+const TIME_EXCEEDED_V4: u8 = 11;
+const ECHO_REQUEST_V6: u8 = 128;
+const ECHO_REPLY_V6: u8 = 129;
+const DESTINATION_UNREACHABLE_V6: u8 = 1;
+const TIME_EXCEEDED_V6: u8 = 3;
+
+pub(super) const IPPROTO_ICMP: u8 = 1;
+pub(super) const IPPROTO_TCP: u8 = 6;
+pub(super) const IPPROTO_UDP: u8 = 17;
+pub(super) const IPPROTO_ICMPV6: u8 = 58;
+
+/// Length of the ICMP echo header: type, code, checksum, identifier and sequence number
+const ECHO_HEADER_LEN: usize = 8;
+
+/// Resolve a destination to the address to ping, preferring IPv4 like ping.exe
+pub fn resolve(destination: &str) -> Result<IpAddr, Box<dyn std::error::Error>> {
+    if let Ok(address) = destination.parse::<IpAddr>() {
+        return Ok(address);
+    }
+
+    let addresses: Vec<SocketAddr> = (destination, 0).to_socket_addrs()?.collect();
+    addresses
+        .iter()
+        .find(|address| address.is_ipv4())
+        .or_else(|| addresses.first())
+        .map(|address| address.ip())
+        .ok_or_else(|| format!("Could not resolve {destination}").into())
+}
+
+/// An ICMP socket connected to one destination
+pub struct IcmpSocket {
+    socket: Socket,
+    destination: SockAddr,
+    address: IpAddr,
+    /// Raw sockets see every ICMP packet, including the IPv4 header
+    raw: bool,
+    identifier: u16,
+}
+
+impl IcmpSocket {
+    /// Open an ICMP socket for `address`
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address to ping
+    /// * `source_address` - The local address to send from
+    /// * `options` - The TTL is applied to the socket
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the socket, or an error when neither datagram nor raw ICMP sockets
+    /// may be opened
+    pub fn open(
+        address: IpAddr,
+        source_address: Option<&str>,
+        options: &PingOptions,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (domain, protocol) = socket_family(address);
+        let (socket, raw) = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+            Ok(socket) => (socket, false),
+            Err(dgram_error) => match Socket::new(domain, Type::RAW, Some(protocol)) {
+                Ok(socket) => (socket, true),
+                Err(raw_error) => {
+                    return Err(format!(
+                        "ICMP sockets unavailable: {dgram_error}; raw sockets need \
+                         administrator rights ({raw_error})"
+                    )
+                    .into())
+                }
+            },
+        };
+
+        let socket = IcmpSocket::bind(socket, raw, address, source_address)?;
+        if let Some(ttl) = options.ttl {
+            socket.set_ttl(ttl)?;
+        }
+        Ok(socket)
+    }
+
+    /// Open a raw ICMP socket for `address`
+    ///
+    /// Unlike datagram sockets, raw sockets also receive the ICMP errors caused by UDP and TCP
+    /// packets, which traceroute relies on. They need administrator rights on Windows.
+    pub(super) fn open_raw(
+        address: IpAddr,
+        source_address: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let (domain, protocol) = socket_family(address);
+        let socket = Socket::new(domain, Type::RAW, Some(protocol))
+            .map_err(|e| format!("Raw ICMP sockets need administrator rights ({e})"))?;
+        IcmpSocket::bind(socket, true, address, source_address)
+    }
+
+    /// Bind a new socket to the source address and wrap it
+    fn bind(
+        socket: Socket,
+        raw: bool,
+        address: IpAddr,
+        source_address: Option<&str>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let source = match source_address {
+            Some(source_address) => Some(source_address.parse()?),
+            // Windows only delivers packets to raw sockets that are bound
+            None if raw => Some(unspecified_address(address)),
+            None => None,
+        };
+        if let Some(source) = source {
+            socket.bind(&SocketAddr::new(source, 0).into())?;
+        }
+
+        Ok(IcmpSocket {
+            socket,
+            destination: SocketAddr::new(address, 0).into(),
+            address,
+            raw,
+            identifier: std::process::id() as u16,
+        })
+    }
+
+    /// Set the TTL (hop limit for IPv6) of outgoing echo requests
+    pub(super) fn set_ttl(&self, ttl: u32) -> std::io::Result<()> {
+        match self.address {
+            IpAddr::V4(_) => self.socket.set_ttl(ttl),
+            IpAddr::V6(_) => self.socket.set_unicast_hops_v6(ttl),
+        }
+    }
+
+    /// Send echo requests until the count is reached or `stop` is set
+    ///
+    /// # Arguments
+    ///
+    /// * `destination` - The destination as entered, used in the result
+    /// * `options` - Count, interval, payload size and timeout
+    /// * `stop` - Set to end the run early
+    /// * `on_reply` - Called for every echo request with its outcome and the statistics so far
+    ///
+    /// # Returns
+    ///
+    /// The statistics of the run
+    pub fn echo(
+        &self,
+        destination: &str,
+        options: &PingOptions,
+        stop: &AtomicBool,
+        on_reply: &mut impl FnMut(&EchoReply, &PingStatistics),
+    ) -> PingResult {
+        let mut statistics = PingStatistics::default();
+        let mut raw_output = format!(
+            "Pinging {destination} [{}] with {} bytes of data ({} ICMP socket):\n",
+            self.address,
+            options.payload_size,
+            if self.raw { "raw" } else { "datagram" }
+        );
+
+        let mut sequence: u16 = 1;
+        while !stop.load(Ordering::Relaxed)
+            && options
+                .count
+                .is_none_or(|count| statistics.transmitted < count)
+        {
+            let sent_at = Instant::now();
+            let reply = match self.send_request(sequence, options.payload_size) {
+                Ok(()) => self.receive_reply(sequence, sent_at, options.timeout),
+                Err(e) => EchoReply {
+                    error: Some(format!("Send failed: {e}")),
+                    ..EchoReply::timeout(sequence as u32)
+                },
+            };
+
+            statistics.record(&reply);
+            raw_output.push_str(&format!("{reply}\n"));
+            on_reply(&reply, &statistics);
+
+            sequence = sequence.wrapping_add(1);
+            let elapsed = sent_at.elapsed();
+            if elapsed < options.interval {
+                // Sleep in short steps so a stop request is honoured promptly
+                let deadline = sent_at + options.interval;
+                while !stop.load(Ordering::Relaxed) && Instant::now() < deadline {
+                    std::thread::sleep(
+                        deadline
+                            .saturating_duration_since(Instant::now())
+                            .min(Duration::from_millis(100)),
+                    );
+                }
+            }
+        }
+
+        let result = statistics.to_result(destination, String::new());
+        raw_output.push_str(&format!(
+            "\n{} packets transmitted, {} received, {:.0}% packet loss\n\
+             rtt min/avg/max/jitter = {:.2}/{:.2}/{:.2}/{:.2} ms\n",
+            result.transmitted,
+            result.received,
+            result.packet_loss,
+            result.min_time,
+            result.avg_time,
+            result.max_time,
+            result.jitter
+        ));
+        PingResult {
+            raw_output,
+            ..result
+        }
+    }
+
+    /// Build and send one echo request
+    pub(super) fn send_request(&self, sequence: u16, payload_size: usize) -> std::io::Result<()> {
+        let request_type = match self.address {
+            IpAddr::V4(_) => ECHO_REQUEST_V4,
+            IpAddr::V6(_) => ECHO_REQUEST_V6,
+        };
+
+        let mut packet = vec![0u8; ECHO_HEADER_LEN + payload_size];
+        packet[0] = request_type;
+        packet[4..6].copy_from_slice(&self.identifier.to_be_bytes());
+        packet[6..8].copy_from_slice(&sequence.to_be_bytes());
+        for (i, byte) in packet[ECHO_HEADER_LEN..].iter_mut().enumerate() {
+            *byte = b'a' + (i % 23) as u8;
+        }
+        // The ICMPv6 checksum covers a pseudo header and is filled in by the kernel
+        if self.address.is_ipv4() {
+            let checksum = checksum(&packet);
+            packet[2..4].copy_from_slice(&checksum.to_be_bytes());
+        }
+
+        self.socket.send_to(&packet, &self.destination)?;
+        Ok(())
+    }
+
+    /// Wait for the reply to the echo request with `sequence`
+    fn receive_reply(&self, sequence: u16, sent_at: Instant, timeout: Duration) -> EchoReply {
+        let protocol = self.protocol();
+        let response = self.wait_for(sent_at + timeout, |packet| match packet {
+            IcmpPacket::EchoReply(echo) => self.is_own_echo(echo, sequence),
+            IcmpPacket::Quoted {
+                protocol: p,
+                header,
+            } => *p == protocol && self.is_own_echo(header, sequence),
+        });
+
+        match response {
+            None => EchoReply::timeout(sequence as u32),
+            Some(response) => EchoReply {
+                sequence: sequence as u32,
+                from: response.from.to_string(),
+                bytes: response.bytes,
+                ttl: response.ttl,
+                rtt: match response.error {
+                    None => {
+                        Some(response.received_at.duration_since(sent_at).as_secs_f32() * 1000.0)
+                    }
+                    Some(_) => None,
+                },
+                error: response.error.map(|error| error.to_string()),
+            },
+        }
+    }
+
+    /// Whether an echo header carries our identifier and `sequence`
+    fn is_own_echo(&self, echo: &[u8], sequence: u16) -> bool {
+        // Datagram sockets get their identifier rewritten by the kernel
+        echo.len() >= ECHO_HEADER_LEN
+            && u16::from_be_bytes([echo[6], echo[7]]) == sequence
+            && (!self.raw || u16::from_be_bytes([echo[4], echo[5]]) == self.identifier)
+    }
+
+    /// IP protocol number of this socket's ICMP version
+    fn protocol(&self) -> u8 {
+        match self.address {
+            IpAddr::V4(_) => IPPROTO_ICMP,
+            IpAddr::V6(_) => IPPROTO_ICMPV6,
+        }
+    }
+
+    /// Receive ICMP messages until one is accepted by `matches` or `deadline` passes
+    ///
+    /// `matches` sees echo replies and the transport header quoted by ICMP errors, so probes
+    /// sent over other sockets (UDP, TCP) can be matched to the errors they caused.
+    pub(super) fn wait_for(
+        &self,
+        deadline: Instant,
+        matches: impl Fn(&IcmpPacket) -> bool,
+    ) -> Option<IcmpResponse> {
+        let mut buffer = [MaybeUninit::<u8>::uninit(); 2048];
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() || self.socket.set_read_timeout(Some(remaining)).is_err() {
+                return None;
+            }
+
+            let (len, from) = self.socket.recv_from(&mut buffer).ok()?;
+            let received_at = Instant::now();
+            // SAFETY: recv_from initialized the first `len` bytes of the buffer
+            let packet: &[u8] =
+                unsafe { std::slice::from_raw_parts(buffer.as_ptr().cast::<u8>(), len) };
+            let Some(from) = from.as_socket().map(|address| address.ip()) else {
+                continue;
+            };
+
+            if let Some(response) = self.parse_packet(packet, from, received_at, &matches) {
+                return Some(response);
+            }
+        }
+    }
+
+    /// Parse a received packet, returning `None` when it is not accepted by `matches`
+    fn parse_packet(
+        &self,
+        packet: &[u8],
+        from: IpAddr,
+        received_at: Instant,
+        matches: &impl Fn(&IcmpPacket) -> bool,
+    ) -> Option<IcmpResponse> {
+        let v4 = self.address.is_ipv4();
+        // IPv4 raw sockets deliver the IP header, which also carries the reply's TTL
+        let (icmp, ttl) = if self.raw && v4 {
+            let header_len = (*packet.first()? as usize & 0x0f) * 4;
+            (
+                packet.get(header_len..)?,
+                packet.get(8).map(|ttl| *ttl as u32),
+            )
+        } else {
+            (packet, None)
+        };
+        if icmp.len() < ECHO_HEADER_LEN {
+            return None;
+        }
+
+        let error = match (v4, icmp[0], icmp[1]) {
+            (true, ECHO_REPLY_V4, _) | (false, ECHO_REPLY_V6, _) => {
+                return matches(&IcmpPacket::EchoReply(icmp)).then_some(IcmpResponse {
+                    from,
+                    received_at,
+                    ttl,
+                    bytes: icmp.len() - ECHO_HEADER_LEN,
+                    error: None,
+                });
+            }
+            (true, TIME_EXCEEDED_V4, _) | (false, TIME_EXCEEDED_V6, _) => IcmpError::TimeExceeded,
+            (true, DESTINATION_UNREACHABLE_V4, 3) | (false, DESTINATION_UNREACHABLE_V6, 4) => {
+                IcmpError::PortUnreachable
+            }
+            (true, DESTINATION_UNREACHABLE_V4, code) => IcmpError::Unreachable(match code {
+                0 => "Network unreachable",
+                1 => "Host unreachable",
+                2 => "Protocol unreachable",
+                4 => "Fragmentation needed",
+                9 | 10 | 13 => "Communication administratively prohibited",
+                _ => "Destination unreachable",
+            }),
+            (false, DESTINATION_UNREACHABLE_V6, code) => IcmpError::Unreachable(match code {
+                0 => "No route to destination",
+                1 => "Communication administratively prohibited",
+                3 => "Address unreachable",
+                _ => "Destination unreachable",
+            }),
+            _ => return None,
+        };
+
+        // Errors quote the IP header and the start of the packet that caused them
+        let quoted = &icmp[ECHO_HEADER_LEN..];
+        let (protocol, header) = if v4 {
+            let header_len = (*quoted.first()? as usize & 0x0f) * 4;
+            (*quoted.get(9)?, quoted.get(header_len..)?)
+        } else {
+            (*quoted.get(6)?, quoted.get(40..)?)
+        };
+        matches(&IcmpPacket::Quoted { protocol, header }).then_some(IcmpResponse {
+            from,
+            received_at,
+            ttl: None,
+            bytes: 0,
+            error: Some(error),
+        })
+    }
+}
+
+/// An ICMP message offered to the matcher of [`IcmpSocket::wait_for`]
+pub(super) enum IcmpPacket<'a> {
+    /// An echo reply, starting with its ICMP header
+    EchoReply(&'a [u8]),
+    /// An ICMP error, with the protocol and transport header of the packet that caused it
+    Quoted { protocol: u8, header: &'a [u8] },
+}
+
+/// Why an ICMP error was sent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum IcmpError {
+    TimeExceeded,
+    /// The destination was reached but nothing listens on the port
+    PortUnreachable,
+    Unreachable(&'static str),
+}
+
+impl std::fmt::Display for IcmpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IcmpError::TimeExceeded => write!(f, "Time to live exceeded"),
+            IcmpError::PortUnreachable => write!(f, "Port unreachable"),
+            IcmpError::Unreachable(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// An ICMP message accepted by the matcher of [`IcmpSocket::wait_for`]
+pub(super) struct IcmpResponse {
+    pub from: IpAddr,
+    pub received_at: Instant,
+    /// TTL of the reply, only known on IPv4 raw sockets
+    pub ttl: Option<u32>,
+    /// Payload size of an echo reply
+    pub bytes: usize,
+    /// `None` for echo replies
+    pub error: Option<IcmpError>,
+}
+
+/// Socket domain and ICMP protocol for an address family
+pub(super) fn socket_family(address: IpAddr) -> (Domain, Protocol) {
+    match address {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    }
+}
+
+/// The unspecified address of the same family as `address`
+pub(super) fn unspecified_address(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V4(_) => IpAddr::V4(std::net::Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(std::net::Ipv6Addr::UNSPECIFIED),
+    }
+}
+
+/// Compute the Internet checksum (RFC 1071) of a packet
+fn checksum(packet: &[u8]) -> u16 {
+    let mut sum: u32 = packet
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
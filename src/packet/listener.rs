@@ -0,0 +1,582 @@
+//! Test listeners
+//!
+//! This module starts a temporary TCP echo, UDP echo or HTTP listener in a probe source, so
+//! inbound reachability can be checked against something known to answer. When a check fails,
+//! the listener tells whether the connection arrived at all: if it never did, a firewall or
+//! the port forwarding is in the way rather than the application being tested.
+//!
+//! On Windows the listener runs in this process. In WSL and containers it runs a Python script,
+//! see [`super::python`], which exits when the listener is stopped.
+
+use super::python;
+use super::socket::{self, SocketProbeOptions, SocketProtocol};
+use super::source::ProbeSource;
+use socket2::{Domain, Socket, Type};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Time allowed for a hit to be reported after a check, as WSL output arrives with a delay
+const HIT_DELAY: Duration = Duration::from_millis(500);
+
+/// Receives a line per connection, datagram or request, from the listener's threads
+type EventHandler = Arc<dyn Fn(String) + Send + Sync>;
+
+/// Payload of TCP and UDP checks, echoed back by the listener
+const CHECK_PAYLOAD: &[u8] = b"wslnetman-check\n";
+
+/// Listener run by WSL and container sources
+///
+/// It prints "READY" once listening, "ERROR <message>" if it cannot, and one line per
+/// connection, datagram or request after that.
+const PYTHON_SCRIPT: &str = r#"
+protocol, host, port = sys.argv[1], sys.argv[2], int(sys.argv[3])
+
+
+def echo(conn, peer):
+    with conn:
+        while True:
+            data = conn.recv(65536)
+            if not data:
+                break
+            conn.sendall(data)
+
+
+def http(conn, peer):
+    with conn:
+        request = b""
+        while b"\r\n\r\n" not in request and len(request) < 65536:
+            data = conn.recv(65536)
+            if not data:
+                break
+            request += data
+        line = request.split(b"\r\n", 1)[0].decode("latin-1")
+        log("HTTP %s from %s" % (line, peer_name(peer)))
+        body = ("WSLNetMan test listener: %s from %s\n" % (line, peer_name(peer))).encode()
+        conn.sendall(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: %d\r\nConnection: close\r\n\r\n" % len(body) + body)
+
+
+exit_with_stdin()
+try:
+    sock = listen(socket.SOCK_DGRAM if protocol == "udp" else socket.SOCK_STREAM, host, port)
+except OSError as e:
+    log("ERROR %s" % e)
+    sys.exit(1)
+log("READY")
+if protocol == "udp":
+    while True:
+        data, peer = sock.recvfrom(65535)
+        log("UDP %d bytes from %s" % (len(data), peer_name(peer)))
+        sock.sendto(data, peer)
+else:
+    while True:
+        conn, peer = sock.accept()
+        if protocol == "tcp":
+            log("TCP connection from %s" % peer_name(peer))
+        threading.Thread(target=http if protocol == "http" else echo, args=(conn, peer), daemon=True).start()
+"#;
+
+/// What a test listener speaks
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ListenerProtocol {
+    /// Echoes back whatever a connection sends
+    #[default]
+    Tcp,
+    /// Echoes back every datagram
+    Udp,
+    /// Answers every request with 200 and a line naming the request and the client
+    Http,
+}
+
+impl std::fmt::Display for ListenerProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenerProtocol::Tcp => write!(f, "TCP"),
+            ListenerProtocol::Udp => write!(f, "UDP"),
+            ListenerProtocol::Http => write!(f, "HTTP"),
+        }
+    }
+}
+
+impl ListenerProtocol {
+    /// Transport protocol the listener uses, "TCP" or "UDP"
+    pub fn transport(&self) -> &'static str {
+        match self {
+            ListenerProtocol::Tcp | ListenerProtocol::Http => "TCP",
+            ListenerProtocol::Udp => "UDP",
+        }
+    }
+}
+
+impl std::str::FromStr for ListenerProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "TCP" => Ok(ListenerProtocol::Tcp),
+            "UDP" => Ok(ListenerProtocol::Udp),
+            "HTTP" => Ok(ListenerProtocol::Http),
+            _ => Err(format!("Unknown listener protocol: {s}")),
+        }
+    }
+}
+
+/// Result of checking whether a test listener can be reached from a source
+#[derive(Debug, Clone)]
+pub struct ReachabilityCheck {
+    /// Label of the source the check was sent from
+    pub source: String,
+    /// Address and port the check was sent to
+    pub target: String,
+    /// Whether the listener's answer came back
+    pub reachable: bool,
+    /// Whether the listener saw the connection, datagram or request arrive
+    pub arrived: bool,
+    pub detail: String,
+}
+
+impl ReachabilityCheck {
+    /// What the outcome means for the user
+    pub fn verdict(&self) -> &'static str {
+        match (self.reachable, self.arrived) {
+            (true, _) => "Reachable",
+            (false, true) => "Arrived, but the answer did not get back",
+            (false, false) => "Never arrived: blocked by a firewall or not forwarded",
+        }
+    }
+}
+
+/// How the listener runs
+enum Running {
+    Native(std::thread::JoinHandle<()>),
+    Process(Child),
+}
+
+/// A running test listener, stopped when dropped
+pub struct TestListener {
+    pub source: ProbeSource,
+    pub protocol: ListenerProtocol,
+    pub address: String,
+    pub port: u16,
+    stop: Arc<AtomicBool>,
+    hits: Arc<AtomicUsize>,
+    running: Option<Running>,
+}
+
+impl TestListener {
+    /// Start listening on `address` and `port` in `source`
+    ///
+    /// `on_event` is called with a line per connection, datagram or request, from another
+    /// thread.
+    pub fn start(
+        source: &ProbeSource,
+        protocol: ListenerProtocol,
+        address: &str,
+        port: u16,
+        on_event: impl Fn(String) + Send + 'static,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let on_event = Mutex::new(on_event);
+        let on_event: EventHandler = Arc::new(move |event| {
+            if let Ok(on_event) = on_event.lock() {
+                on_event(event);
+            }
+        });
+        let address = address.trim().trim_start_matches('[').trim_end_matches(']');
+        // Windows refuses to bind ports it reserved, and cannot relay them to WSL either; only
+        // warn, as the ranges are cached and change at any time
+        let warning =
+            crate::network::excluded_port_warning(protocol.transport(), address.parse().ok(), port);
+        if let Some(warning) = &warning {
+            on_event(format!("Warning: {warning}"));
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let hits = Arc::new(AtomicUsize::new(0));
+        let running = if source.is_linux() {
+            start_process(source, protocol, address, port, hits.clone(), on_event)?
+        } else {
+            let ip: IpAddr = address
+                .parse()
+                .map_err(|e| format!("Invalid listen address {address}: {e}"))?;
+            start_native(
+                protocol,
+                SocketAddr::new(ip, port),
+                stop.clone(),
+                hits.clone(),
+                on_event,
+            )
+            .map_err(|e| match &warning {
+                Some(warning) => format!("{e}. {warning}").into(),
+                None => e,
+            })?
+        };
+        Ok(TestListener {
+            source: source.clone(),
+            protocol,
+            address: address.to_string(),
+            port,
+            stop,
+            hits,
+            running: Some(running),
+        })
+    }
+
+    /// Connections, datagrams and requests received so far
+    pub fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// Sources and addresses to check the listener from
+    ///
+    /// A listener on every address is checked on the loopback address from its own
+    /// environment, and from the other one the way it reaches across: WSL reaches Windows at
+    /// its default gateway, while Windows reaches WSL both through `localhost` forwarding and
+    /// at the WSL `eth0` address.
+    pub fn check_targets(&self) -> Vec<(ProbeSource, String)> {
+        let wildcard = matches!(self.address.as_str(), "0.0.0.0" | "::");
+        let local = if wildcard {
+            "127.0.0.1".to_string()
+        } else {
+            self.address.clone()
+        };
+        let mut targets = vec![(self.source.clone(), local.clone())];
+        match &self.source {
+            ProbeSource::Windows => {
+                let address = if wildcard {
+                    crate::network::wsl::get_default_gateway().ok().flatten()
+                } else {
+                    Some(self.address.clone())
+                };
+                if let Some(address) = address {
+                    targets.push((ProbeSource::Wsl { distro: None }, address));
+                }
+            }
+            ProbeSource::Wsl { .. } => {
+                if wildcard || local == "127.0.0.1" {
+                    targets.push((ProbeSource::Windows, "127.0.0.1".to_string()));
+                }
+                if wildcard {
+                    let eth0 = crate::network::wsl::get_interface_addresses()
+                        .ok()
+                        .into_iter()
+                        .flatten()
+                        .find(|address| {
+                            address.interface == "eth0" && !address.address.contains(':')
+                        });
+                    if let Some(eth0) = eth0 {
+                        targets.push((ProbeSource::Windows, eth0.address));
+                    }
+                } else if local != "127.0.0.1" {
+                    targets.push((ProbeSource::Windows, local));
+                }
+            }
+            // Containers are only reachable through published ports
+            ProbeSource::Container { .. } => {}
+        }
+        targets
+    }
+
+    /// Check whether the listener can be reached from `from` at `address`
+    pub fn check(&self, from: &ProbeSource, address: &str) -> ReachabilityCheck {
+        let host = address.trim_start_matches('[').trim_end_matches(']');
+        let target = if host.contains(':') {
+            format!("[{host}]:{}", self.port)
+        } else {
+            format!("{host}:{}", self.port)
+        };
+        let payload = match self.protocol {
+            ListenerProtocol::Http => {
+                format!("GET /wslnetman-check HTTP/1.0\r\nHost: {target}\r\n\r\n").into_bytes()
+            }
+            _ => CHECK_PAYLOAD.to_vec(),
+        };
+        let options = SocketProbeOptions {
+            protocol: match self.protocol {
+                ListenerProtocol::Udp => SocketProtocol::Udp,
+                _ => SocketProtocol::Tcp,
+            },
+            port: self.port,
+            payload: payload.clone(),
+            connect_timeout: Duration::from_secs(3),
+            idle_timeout: Duration::from_secs(1),
+            ..Default::default()
+        };
+
+        let hits = self.hits();
+        let result = socket::probe(from, host, None, &options, &AtomicBool::new(false));
+        let deadline = Instant::now() + HIT_DELAY;
+        while self.hits() == hits && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let arrived = self.hits() > hits;
+
+        let (reachable, detail) = match result {
+            Ok(result) => {
+                let reachable = match self.protocol {
+                    ListenerProtocol::Http => result.received.starts_with(b"HTTP/1."),
+                    _ => result
+                        .received
+                        .windows(payload.len())
+                        .any(|window| window == payload.as_slice()),
+                };
+                let detail = if reachable {
+                    match result.connect_time.or(result.first_byte_time) {
+                        Some(time) => format!("Answered in {time:.1} ms"),
+                        None => "Answered".to_string(),
+                    }
+                } else if let Some(error) = result.error {
+                    error
+                } else if result.received.is_empty() {
+                    "No answer".to_string()
+                } else {
+                    format!("Unexpected answer of {} bytes", result.received.len())
+                };
+                (reachable, detail)
+            }
+            Err(e) => (false, e.to_string()),
+        };
+        ReachabilityCheck {
+            source: from.to_string(),
+            target,
+            reachable,
+            arrived,
+            detail,
+        }
+    }
+
+    /// Run every check of [`TestListener::check_targets`]
+    pub fn check_all(&self) -> Vec<ReachabilityCheck> {
+        self.check_targets()
+            .iter()
+            .map(|(source, address)| self.check(source, address))
+            .collect()
+    }
+}
+
+impl Drop for TestListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        match self.running.take() {
+            Some(Running::Native(handle)) => {
+                let _ = handle.join();
+            }
+            Some(Running::Process(mut child)) => {
+                // Closing its input ends the script even when killing wsl.exe does not reach it
+                drop(child.stdin.take());
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Start the Python listener in a WSL or container source and wait until it listens
+fn start_process(
+    source: &ProbeSource,
+    protocol: ListenerProtocol,
+    address: &str,
+    port: u16,
+    hits: Arc<AtomicUsize>,
+    on_event: EventHandler,
+) -> Result<Running, Box<dyn std::error::Error>> {
+    let mut child = python::spawn(
+        source,
+        PYTHON_SCRIPT,
+        &[
+            protocol.to_string().to_lowercase(),
+            address.to_string(),
+            port.to_string(),
+        ],
+    )?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or("Could not capture the listener's output")?;
+    let stderr = child
+        .stderr
+        .take()
+        .ok_or("Could not capture the listener's errors")?;
+
+    let (ready_sender, ready) = mpsc::channel();
+    let on_output = on_event.clone();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if line == python::READY {
+                let _ = ready_sender.send(Ok(()));
+            } else if let Some(error) = line.strip_prefix("ERROR ") {
+                let _ = ready_sender.send(Err(error.to_string()));
+            } else {
+                hits.fetch_add(1, Ordering::SeqCst);
+                on_output(line);
+            }
+        }
+    });
+    let (error_sender, errors) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            let _ = error_sender.send(line.clone());
+            on_event(line);
+        }
+    });
+
+    let outcome = match ready.recv_timeout(python::START_TIMEOUT) {
+        Ok(Ok(())) => return Ok(Running::Process(child)),
+        Ok(Err(error)) => error,
+        Err(_) => {
+            let errors: Vec<String> = errors.try_iter().collect();
+            if errors.is_empty() {
+                "no response".to_string()
+            } else {
+                errors.join("\n")
+            }
+        }
+    };
+    drop(child.stdin.take());
+    let _ = child.kill();
+    let _ = child.wait();
+    Err(format!("Could not listen on {address} port {port} in {source}: {outcome}").into())
+}
+
+/// Start listening in this process
+fn start_native(
+    protocol: ListenerProtocol,
+    address: SocketAddr,
+    stop: Arc<AtomicBool>,
+    hits: Arc<AtomicUsize>,
+    on_event: EventHandler,
+) -> Result<Running, Box<dyn std::error::Error>> {
+    let socket_type = match protocol {
+        ListenerProtocol::Udp => Type::DGRAM,
+        _ => Type::STREAM,
+    };
+    let socket = Socket::new(Domain::for_address(address), socket_type, None)?;
+    if address.ip().is_unspecified() && address.is_ipv6() {
+        socket.set_only_v6(false)?;
+    }
+    socket
+        .bind(&address.into())
+        .map_err(|e| format!("Could not listen on {address}: {e}"))?;
+
+    let handle = match protocol {
+        ListenerProtocol::Udp => {
+            let socket: UdpSocket = socket.into();
+            socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+            std::thread::spawn(move || serve_udp(socket, &stop, &hits, &*on_event))
+        }
+        _ => {
+            socket.listen(16)?;
+            let listener: TcpListener = socket.into();
+            listener.set_nonblocking(true)?;
+            std::thread::spawn(move || serve_tcp(listener, protocol, stop, hits, on_event))
+        }
+    };
+    Ok(Running::Native(handle))
+}
+
+fn serve_udp(
+    socket: UdpSocket,
+    stop: &AtomicBool,
+    hits: &AtomicUsize,
+    on_event: &(dyn Fn(String) + Send + Sync),
+) {
+    let mut buffer = vec![0u8; 65535];
+    while !stop.load(Ordering::SeqCst) {
+        match socket.recv_from(&mut buffer) {
+            Ok((read, peer)) => {
+                hits.fetch_add(1, Ordering::SeqCst);
+                on_event(format!("UDP {read} bytes from {peer}"));
+                let _ = socket.send_to(&buffer[..read], peer);
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            // Windows reports an earlier ICMP port unreachable on the next receive
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => {}
+            Err(e) => {
+                on_event(format!("Error: {e}"));
+                return;
+            }
+        }
+    }
+}
+
+fn serve_tcp(
+    listener: TcpListener,
+    protocol: ListenerProtocol,
+    stop: Arc<AtomicBool>,
+    hits: Arc<AtomicUsize>,
+    on_event: EventHandler,
+) {
+    while !stop.load(Ordering::SeqCst) {
+        let (connection, peer) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(50));
+                continue;
+            }
+            Err(e) => {
+                on_event(format!("Error: {e}"));
+                return;
+            }
+        };
+        if protocol == ListenerProtocol::Tcp {
+            hits.fetch_add(1, Ordering::SeqCst);
+            on_event(format!("TCP connection from {peer}"));
+        }
+        let (stop, hits, on_event) = (stop.clone(), hits.clone(), on_event.clone());
+        std::thread::spawn(move || {
+            let _ = connection.set_nonblocking(false);
+            let _ = connection.set_read_timeout(Some(Duration::from_millis(500)));
+            let _ = match protocol {
+                ListenerProtocol::Http => answer_http(connection, peer, &hits, &*on_event),
+                _ => echo(connection, &stop),
+            };
+        });
+    }
+}
+
+/// Send back whatever arrives until the peer closes or the listener stops
+fn echo(mut connection: TcpStream, stop: &AtomicBool) -> std::io::Result<()> {
+    let mut buffer = vec![0u8; 65536];
+    while !stop.load(Ordering::SeqCst) {
+        match connection.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => connection.write_all(&buffer[..read])?,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Answer one HTTP request with 200 and a line naming the request and the client
+fn answer_http(
+    mut connection: TcpStream,
+    peer: SocketAddr,
+    hits: &AtomicUsize,
+    on_event: &(dyn Fn(String) + Send + Sync),
+) -> std::io::Result<()> {
+    connection.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") && request.len() < 65536 {
+        let read = connection.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let line = request.lines().next().unwrap_or_default();
+    hits.fetch_add(1, Ordering::SeqCst);
+    on_event(format!("HTTP {line} from {peer}"));
+    let body = format!("WSLNetMan test listener: {line} from {peer}\n");
+    connection.write_all(
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .as_bytes(),
+    )
+}
@@ -1,52 +1,252 @@
 //! Packet module
 //!
-//! This module provides functionality to send packets (ping and HTTP over TCP).
+//! This module provides functionality to send packets (ping, traceroute, DNS queries, HTTP over
+//! TCP and raw TCP/UDP payloads), to inspect the TLS certificate chain of HTTPS servers, to
+//! measure the throughput between two sources and to run test listeners that probes can reach.
+//! Every probe is sent from a [`ProbeSource`]: Windows, a WSL distribution or a container.
+//! Probes that were sent are kept in a searchable [`ProbeHistory`].
 
+pub mod dns;
+pub mod history;
 pub mod http;
+pub mod icmp;
+pub mod listener;
 pub mod ping;
+pub mod python;
+pub mod socket;
+pub mod source;
+pub mod throughput;
+pub mod tls;
+pub mod traceroute;
 
 // Re-export the result types from submodules
-pub use http::HttpRequestResult;
-pub use ping::PingResult;
+pub use dns::{DnsResolver, DnsResult, RecordType};
+pub use history::{ProbeCollection, ProbeCollections, ProbeHistory, ProbeParameters};
+pub use http::{HttpAuth, HttpBody, HttpRequest, HttpRequestResult};
+pub use listener::{ListenerProtocol, TestListener};
+pub use ping::{EchoReply, PingOptions, PingResult, PingStatistics};
+pub use socket::{SocketProbeOptions, SocketProbeResult};
+pub use source::ProbeSource;
+pub use throughput::{ThroughputOptions, ThroughputResult};
+pub use tls::TlsInspection;
+pub use traceroute::{TracerouteHop, TracerouteOptions, TracerouteResult};
 
-/// Send a ping request to the specified destination
+use std::sync::atomic::AtomicBool;
+
+/// Send ping requests to the specified destination
 ///
-/// This function uses the system's ping command to send ICMP echo requests.
+/// This function sends ICMP echo requests natively from Windows, or uses the ping command of
+/// the source environment, and reports every reply as it arrives.
 ///
 /// # Arguments
 ///
+/// * `source` - The environment to send the ping from
 /// * `destination` - The IP address or hostname to ping
-/// * `count` - The number of ping requests to send (default: 4)
-/// * `timeout` - The timeout for each ping request in seconds (default: 5)
+/// * `source_address` - The local address to send from, binding the ping to its interface
+/// * `options` - Count (or continuous), interval, payload size, TTL and timeout
+/// * `stop` - Set to end the run early
+/// * `on_reply` - Called for every echo request with its outcome and the statistics so far
 ///
 /// # Returns
 ///
 /// A `Result` containing the ping result or an error
 pub fn send_ping(
+    source: &ProbeSource,
     destination: &str,
-    count: Option<u32>,
-    timeout: Option<u32>,
+    source_address: Option<&str>,
+    options: &PingOptions,
+    stop: &AtomicBool,
+    on_reply: impl FnMut(&EchoReply, &PingStatistics),
 ) -> Result<PingResult, Box<dyn std::error::Error>> {
-    ping::send_ping(destination, count, timeout)
+    ping::send_ping(source, destination, source_address, options, stop, on_reply)
 }
 
-/// Send an HTTP request to the specified URL
+/// Trace the path to the specified destination
 ///
-/// This function uses the `reqwest` crate to send HTTP requests.
+/// This function traces natively from Windows (falling back to `tracert`), or uses
+/// `traceroute` in WSL or a container, and reports every hop as it is found.
 ///
 /// # Arguments
 ///
-/// * `url` - The URL to send the HTTP request to
-/// * `method` - The HTTP method to use (default: "GET")
-/// * `timeout` - The timeout for the request in seconds (default: 30)
+/// * `source` - The environment to trace from
+/// * `destination` - The IP address or hostname to trace
+/// * `source_address` - The local address to send from, binding the trace to its interface
+/// * `options` - Protocol (ICMP, UDP or TCP), maximum hops, probes per hop, timeout and port
+/// * `stop` - Set to end the trace early
+/// * `on_hop` - Called for every hop as it is found
+///
+/// # Returns
+///
+/// A `Result` containing the trace result or an error
+pub fn trace_route(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &TracerouteOptions,
+    stop: &AtomicBool,
+    on_hop: impl FnMut(&TracerouteHop),
+) -> Result<TracerouteResult, Box<dyn std::error::Error>> {
+    traceroute::trace(source, destination, source_address, options, stop, on_hop)
+}
+
+/// Resolve a name with a chosen record type and resolver
+///
+/// This function queries DNS servers over UDP or the Windows DNS client (`Resolve-DnsName`)
+/// from Windows, or uses `dig` in WSL or a container.
+///
+/// # Arguments
+///
+/// * `source` - The environment to query from
+/// * `name` - The name to resolve, or an IP address for PTR queries
+/// * `record_type` - A, AAAA, CNAME, MX, TXT, SRV or PTR
+/// * `resolver` - System, Windows DNS servers, WSL resolv.conf, Docker embedded DNS or a custom
+///   server
+/// * `source_address` - The local address to send from
+/// * `timeout` - How long to wait for the answer
+///
+/// # Returns
+///
+/// A `Result` containing the answers with TTLs, response code and latency, or an error
+pub fn resolve_dns(
+    source: &ProbeSource,
+    name: &str,
+    record_type: RecordType,
+    resolver: &DnsResolver,
+    source_address: Option<&str>,
+    timeout: std::time::Duration,
+) -> Result<DnsResult, Box<dyn std::error::Error>> {
+    dns::query(source, name, record_type, resolver, source_address, timeout)
+}
+
+/// Send a raw TCP or UDP payload and collect the response
+///
+/// This function uses sockets in this process from Windows, or `nc` in WSL or a container.
+///
+/// # Arguments
+///
+/// * `source` - The environment to send from
+/// * `destination` - The IP address or hostname to connect to
+/// * `source_address` - The local address to send from, binding the probe to its interface
+/// * `options` - Protocol, port, payload and timeouts
+/// * `stop` - Set to stop waiting for more data
+///
+/// # Returns
+///
+/// A `Result` containing the bytes received with timing, or an error
+pub fn send_socket_probe(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &SocketProbeOptions,
+    stop: &AtomicBool,
+) -> Result<SocketProbeResult, Box<dyn std::error::Error>> {
+    socket::probe(source, destination, source_address, options, stop)
+}
+
+/// Measure the throughput between two sources
+///
+/// This function starts a server in `server` and sends a TCP stream or paced UDP datagrams to it
+/// from `client` for the configured duration, like `iperf`. Windows runs both sides in this
+/// process; WSL and containers run them with `python3`.
+///
+/// # Arguments
+///
+/// * `client` - The environment to send from
+/// * `server` - The environment to receive in
+/// * `address` - The address the client reaches the server at, worked out when `None`
+/// * `options` - Protocol, port, duration and UDP rate
+/// * `stop` - Set to abort the test
+///
+/// # Returns
+///
+/// A `Result` containing the throughput, retransmits, and UDP loss and jitter, or an error
+pub fn test_throughput(
+    client: &ProbeSource,
+    server: &ProbeSource,
+    address: Option<&str>,
+    options: &ThroughputOptions,
+    stop: &AtomicBool,
+) -> Result<ThroughputResult, Box<dyn std::error::Error>> {
+    let address = match address {
+        Some(address) => address.to_string(),
+        None => throughput::default_server_address(client, server).ok_or_else(|| {
+            format!("Enter the address {client} can reach the server in {server} at")
+        })?,
+    };
+    throughput::run(client, server, &address, options, stop)
+}
+
+/// Start a temporary TCP echo, UDP echo or HTTP listener
+///
+/// This function listens in this process on Windows, or with `python3` in WSL or a container.
+/// The listener stops when the returned `TestListener` is dropped.
+///
+/// # Arguments
+///
+/// * `source` - The environment to listen in
+/// * `protocol` - TCP echo, UDP echo or HTTP
+/// * `address` - The local address to listen on, e.g. "0.0.0.0" for every IPv4 address
+/// * `port` - The port to listen on
+/// * `on_event` - Called with a line per connection, datagram or request received
+///
+/// # Returns
+///
+/// A `Result` containing the running `TestListener`, which runs the reachability checks, or an
+/// error
+pub fn start_test_listener(
+    source: &ProbeSource,
+    protocol: ListenerProtocol,
+    address: &str,
+    port: u16,
+    on_event: impl Fn(String) + Send + 'static,
+) -> Result<TestListener, Box<dyn std::error::Error>> {
+    TestListener::start(source, protocol, address, port, on_event)
+}
+
+/// Send an HTTP request
+///
+/// This function uses the `reqwest` crate to send HTTP requests from Windows (or `curl.exe` when
+/// the request asks for it), and `curl` in WSL or a container.
+///
+/// # Arguments
+///
+/// * `source` - The environment to send the request from
+/// * `request` - The method, URL, query parameters, headers, body, authentication, redirect
+///   policy and TLS options of the request
+/// * `source_address` - The local address to connect from, binding the request to its interface
 ///
 /// # Returns
 ///
 /// A `Result` containing the HTTP request result or an error
 pub async fn send_http_request(
-    url: &str,
-    method: Option<&str>,
-    timeout: Option<u64>,
+    source: &ProbeSource,
+    request: &HttpRequest,
+    source_address: Option<&str>,
 ) -> Result<HttpRequestResult, Box<dyn std::error::Error>> {
-    http::send_http_request(url, method, timeout).await
+    http::send_http_request_from(source, request, source_address).await
+}
+
+/// Inspect the TLS certificate chain of a server
+///
+/// This function captures the chain presented to the source with .NET's `SslStream` on
+/// Windows or `openssl s_client` in WSL and containers, and verifies it against the Windows
+/// root store and the WSL CA bundle.
+///
+/// # Arguments
+///
+/// * `source` - The environment to connect from
+/// * `host` - The host name or IP address of the server
+/// * `port` - The TLS port
+/// * `source_address` - The local address to connect from (Windows only)
+///
+/// # Returns
+///
+/// A `Result` containing the chain and its verification, or an error
+pub fn inspect_tls(
+    source: &ProbeSource,
+    host: &str,
+    port: u16,
+    source_address: Option<&str>,
+) -> Result<TlsInspection, Box<dyn std::error::Error>> {
+    tls::inspect(source, host, port, source_address)
 }
@@ -1,9 +1,15 @@
 //! Ping implementation
 //!
 //! This module provides functionality to send ping requests and receive responses.
-//! It uses the system's ping command to perform the actual ping operation.
+//! Replies are streamed to the caller as they arrive. Pings from Windows use the in-process
+//! ICMP engine (see [`super::icmp`]) when ICMP sockets are available; otherwise, and for WSL
+//! and container sources, the ping command of the source environment is run and its output
+//! parsed line by line.
 
-use std::process::Command;
+use super::icmp;
+use super::source::ProbeSource;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 
 /// Represents the result of a ping operation
 #[derive(Debug, Clone)]
@@ -15,57 +21,664 @@ pub struct PingResult {
     pub min_time: f32,
     pub avg_time: f32,
     pub max_time: f32,
+    pub jitter: f32, // Mean difference between consecutive round trip times
     pub raw_output: String,
 }
 
-/// Send a ping request to the specified destination
+/// Options of a ping run
+#[derive(Debug, Clone)]
+pub struct PingOptions {
+    /// Number of echo requests, `None` to ping until stopped
+    pub count: Option<u32>,
+    /// Delay between echo requests (ping.exe always waits one second)
+    pub interval: Duration,
+    /// Payload size in bytes
+    pub payload_size: usize,
+    /// Time to live (hop limit for IPv6), `None` for the system default
+    pub ttl: Option<u32>,
+    /// How long to wait for each reply
+    pub timeout: Duration,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        PingOptions {
+            count: Some(4),
+            interval: Duration::from_secs(1),
+            payload_size: 32,
+            ttl: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Represents the outcome of a single echo request
+#[derive(Debug, Clone)]
+pub struct EchoReply {
+    pub sequence: u32,
+    /// Address the reply (or error) came from, empty for timeouts
+    pub from: String,
+    pub bytes: usize,
+    pub ttl: Option<u32>,
+    /// Round trip time in milliseconds, `None` when no echo reply arrived
+    pub rtt: Option<f32>,
+    /// Why no echo reply arrived, e.g. "Request timed out" or "Time to live exceeded"
+    pub error: Option<String>,
+}
+
+impl EchoReply {
+    /// A request that got no answer within the timeout
+    pub fn timeout(sequence: u32) -> Self {
+        EchoReply {
+            sequence,
+            from: String::new(),
+            bytes: 0,
+            ttl: None,
+            rtt: None,
+            error: Some("Request timed out".to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for EchoReply {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.rtt, &self.error) {
+            (Some(rtt), _) => {
+                write!(
+                    f,
+                    "Reply from {}: seq={} bytes={} time={rtt:.2}ms",
+                    self.from, self.sequence, self.bytes
+                )?;
+                if let Some(ttl) = self.ttl {
+                    write!(f, " TTL={ttl}")?;
+                }
+                Ok(())
+            }
+            (None, Some(error)) if !self.from.is_empty() => {
+                write!(f, "From {}: seq={} {error}", self.from, self.sequence)
+            }
+            (None, error) => write!(
+                f,
+                "seq={} {}",
+                self.sequence,
+                error.as_deref().unwrap_or("Request timed out")
+            ),
+        }
+    }
+}
+
+/// Running statistics over the replies of a ping run
+#[derive(Debug, Clone, Default)]
+pub struct PingStatistics {
+    pub transmitted: u32,
+    pub received: u32,
+    min_time: Option<f32>,
+    max_time: f32,
+    total_time: f32,
+    last_time: Option<f32>,
+    total_jitter: f32,
+    jitter_samples: u32,
+}
+
+impl PingStatistics {
+    /// Account for the outcome of one echo request
+    pub fn record(&mut self, reply: &EchoReply) {
+        self.transmitted += 1;
+        let Some(rtt) = reply.rtt else {
+            return;
+        };
+
+        self.received += 1;
+        self.min_time = Some(self.min_time.map_or(rtt, |min| min.min(rtt)));
+        self.max_time = self.max_time.max(rtt);
+        self.total_time += rtt;
+        if let Some(last) = self.last_time {
+            self.total_jitter += (rtt - last).abs();
+            self.jitter_samples += 1;
+        }
+        self.last_time = Some(rtt);
+    }
+
+    /// Percentage of requests without an echo reply
+    pub fn packet_loss(&self) -> f32 {
+        if self.transmitted == 0 {
+            0.0
+        } else {
+            (self.transmitted - self.received) as f32 * 100.0 / self.transmitted as f32
+        }
+    }
+
+    pub fn min_time(&self) -> f32 {
+        self.min_time.unwrap_or(0.0)
+    }
+
+    pub fn avg_time(&self) -> f32 {
+        if self.received == 0 {
+            0.0
+        } else {
+            self.total_time / self.received as f32
+        }
+    }
+
+    pub fn max_time(&self) -> f32 {
+        self.max_time
+    }
+
+    /// Mean absolute difference between consecutive round trip times
+    pub fn jitter(&self) -> f32 {
+        if self.jitter_samples == 0 {
+            0.0
+        } else {
+            self.total_jitter / self.jitter_samples as f32
+        }
+    }
+
+    /// Create the final result of a run
+    pub fn to_result(&self, destination: &str, raw_output: String) -> PingResult {
+        PingResult {
+            destination: destination.to_string(),
+            transmitted: self.transmitted,
+            received: self.received,
+            packet_loss: self.packet_loss(),
+            min_time: self.min_time(),
+            avg_time: self.avg_time(),
+            max_time: self.max_time(),
+            jitter: self.jitter(),
+            raw_output,
+        }
+    }
+}
+
+/// Send ping requests to the specified destination, streaming the replies
 ///
-/// This function uses the system's ping command to send ICMP echo requests.
+/// Pings from Windows use the in-process ICMP engine and fall back to `ping.exe` when ICMP
+/// sockets are not available (they need administrator rights on Windows). Other sources run
+/// Linux `ping` in WSL or a container.
 ///
 /// # Arguments
 ///
+/// * `source` - The environment to send the ping from
 /// * `destination` - The IP address or hostname to ping
-/// * `count` - The number of ping requests to send (default: 4)
-/// * `timeout` - The timeout for each ping request in seconds (default: 5)
+/// * `source_address` - The local address to send from, binding the ping to its interface
+/// * `options` - Count, interval, payload size, TTL and timeout
+/// * `stop` - Set to end a run early, e.g. a continuous one
+/// * `on_reply` - Called for every echo request with its outcome and the statistics so far
 ///
 /// # Returns
 ///
 /// A `Result` containing the ping result or an error
 pub fn send_ping(
+    source: &ProbeSource,
     destination: &str,
-    count: Option<u32>,
-    timeout: Option<u32>,
+    source_address: Option<&str>,
+    options: &PingOptions,
+    stop: &AtomicBool,
+    mut on_reply: impl FnMut(&EchoReply, &PingStatistics),
 ) -> Result<PingResult, Box<dyn std::error::Error>> {
-    let count = count.unwrap_or(4);
-    let timeout = timeout.unwrap_or(5);
-
-    // Use Windows ping command
-    let output = Command::new("ping")
-        .args([
-            "-n",
-            &count.to_string(),
-            "-w",
-            &(timeout * 1000).to_string(), // Windows ping expects timeout in milliseconds
-            destination,
-        ])
-        .output()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    // In a real implementation, we would parse the output to extract statistics
-    // For now, we'll simulate the result
-
-    let result = PingResult {
-        destination: destination.to_string(),
-        transmitted: count,
-        received: count - 1, // Simulate 1 packet loss
-        packet_loss: 25.0,   // 25% packet loss
-        min_time: 10.5,
-        avg_time: 15.2,
-        max_time: 22.1,
-        raw_output: format!("{stdout}\n{stderr}"),
+    if *source == ProbeSource::Windows {
+        let address = icmp::resolve(destination)?;
+        match icmp::IcmpSocket::open(address, source_address, options) {
+            Ok(socket) => {
+                return Ok(socket.echo(destination, options, stop, &mut on_reply));
+            }
+            Err(e) => println!("Native ICMP unavailable ({e}), falling back to ping.exe"),
+        }
+    }
+
+    stream_ping_command(
+        source,
+        destination,
+        source_address,
+        options,
+        stop,
+        &mut on_reply,
+    )
+}
+
+/// Run the ping command of the source environment and stream its replies
+fn stream_ping_command(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &PingOptions,
+    stop: &AtomicBool,
+    on_reply: &mut impl FnMut(&EchoReply, &PingStatistics),
+) -> Result<PingResult, Box<dyn std::error::Error>> {
+    let linux = source.is_linux();
+    let mut args = Vec::new();
+    if linux {
+        if let Some(count) = options.count {
+            args.extend(["-c".to_string(), count.to_string()]);
+        }
+        // Linux ping expects the interval and timeout in seconds
+        args.extend([
+            "-i".to_string(),
+            format!("{:.1}", options.interval.as_secs_f32()),
+            "-W".to_string(),
+            options.timeout.as_secs().max(1).to_string(),
+            "-s".to_string(),
+            options.payload_size.to_string(),
+        ]);
+        if let Some(ttl) = options.ttl {
+            args.extend(["-t".to_string(), ttl.to_string()]);
+        }
+    } else {
+        match options.count {
+            Some(count) => args.extend(["-n".to_string(), count.to_string()]),
+            None => args.push("-t".to_string()),
+        }
+        // Windows ping expects the timeout in milliseconds
+        args.extend([
+            "-w".to_string(),
+            options.timeout.as_millis().to_string(),
+            "-l".to_string(),
+            options.payload_size.to_string(),
+        ]);
+        if let Some(ttl) = options.ttl {
+            args.extend(["-i".to_string(), ttl.to_string()]);
+        }
+    }
+    if let Some(source_address) = source_address {
+        // Linux ping takes an address or interface name, Windows ping only an address
+        let flag = if linux { "-I" } else { "-S" };
+        args.extend([flag.to_string(), source_address.to_string()]);
+    }
+    args.push(destination.to_string());
+
+    let mut statistics = PingStatistics::default();
+    let mut next_sequence = 1;
+    let (stdout, stderr) = source.stream("ping", &args, stop, |line| {
+        for reply in parse_reply_line(line, linux, &mut next_sequence) {
+            statistics.record(&reply);
+            on_reply(&reply, &statistics);
+        }
+    })?;
+    let raw_output = stdout + &stderr;
+
+    // The summary also counts trailing lost requests, which the stream cannot see
+    let summary = if linux {
+        parse_linux_ping_output(&raw_output)
+    } else {
+        parse_windows_ping_output(&raw_output)
     };
+    match summary {
+        Some(summary) => Ok(PingResult {
+            destination: destination.to_string(),
+            jitter: statistics.jitter(),
+            raw_output,
+            ..summary
+        }),
+        None if statistics.transmitted > 0 => Ok(statistics.to_result(destination, raw_output)),
+        None => Err(format!("ping from {source} failed: {}", raw_output.trim()).into()),
+    }
+}
+
+/// Parse a line of streamed `ping` output into the requests it reports
+///
+/// `next_sequence` is the sequence number expected next, and is moved past the reported ones.
+/// Linux ping prints nothing for lost requests, so they are reported when a later one arrives.
+fn parse_reply_line(line: &str, linux: bool, next_sequence: &mut u32) -> Vec<EchoReply> {
+    let reply = if linux {
+        parse_linux_reply_line(line)
+    } else {
+        parse_windows_reply_line(line, *next_sequence)
+    };
+    let Some(reply) = reply else {
+        return Vec::new();
+    };
+    let mut replies: Vec<EchoReply> = (*next_sequence..reply.sequence)
+        .map(EchoReply::timeout)
+        .collect();
+    *next_sequence = reply.sequence + 1;
+    replies.push(reply);
+    replies
+}
+
+/// Parse a reply line of Linux `ping` (iputils and BusyBox)
+///
+/// Format:
+/// ```text
+/// 64 bytes from 8.8.8.8: icmp_seq=1 ttl=117 time=12.3 ms
+/// From 10.0.0.1 icmp_seq=2 Destination Host Unreachable
+/// ```
+/// BusyBox reports "seq=0" instead of "icmp_seq=1" and counts from zero.
+fn parse_linux_reply_line(line: &str) -> Option<EchoReply> {
+    let sequence = number_after(line, "icmp_seq=")
+        .or_else(|| number_after(line, " seq=").map(|sequence| sequence + 1.0))?
+        as u32;
+
+    if let Some((bytes, rest)) = line.split_once(" bytes from ") {
+        let from = rest.split(": ").next()?.trim_end_matches(':');
+        return Some(EchoReply {
+            sequence,
+            // "from host (1.2.3.4)" when the name was resolved
+            from: from.to_string(),
+            bytes: bytes.trim().parse().unwrap_or(0),
+            ttl: number_after(line, "ttl=").map(|ttl| ttl as u32),
+            rtt: number_after(line, "time="),
+            error: None,
+        });
+    }
+
+    let rest = line.strip_prefix("From ")?;
+    let from = rest.split_whitespace().next()?.trim_end_matches(':');
+    let error = rest
+        .split_once("seq=")
+        .and_then(|(_, rest)| rest.split_once(' '))
+        .map(|(_, error)| error.trim().to_string());
+    Some(EchoReply {
+        sequence,
+        from: from.to_string(),
+        bytes: 0,
+        ttl: None,
+        rtt: None,
+        error,
+    })
+}
+
+/// Parse a reply line of Windows `ping.exe`
+///
+/// Format:
+/// ```text
+/// Reply from 8.8.8.8: bytes=32 time=12ms TTL=117
+/// Reply from ::1: time<1ms
+/// Reply from 10.0.0.1: Destination host unreachable.
+/// Request timed out.
+/// ```
+/// ping.exe does not print sequence numbers, so `sequence` is the next expected one.
+fn parse_windows_reply_line(line: &str, sequence: u32) -> Option<EchoReply> {
+    let line = line.trim();
+    if line == "Request timed out." {
+        return Some(EchoReply::timeout(sequence));
+    }
+
+    let (from, rest) = line.strip_prefix("Reply from ")?.split_once(": ")?;
+    // Sub-millisecond replies are reported as "time<1ms"
+    let rtt = number_after(rest, "time=").or_else(|| number_after(rest, "time<"));
+    Some(EchoReply {
+        sequence,
+        from: from.to_string(),
+        bytes: number_after(rest, "bytes=").unwrap_or(0.0) as usize,
+        ttl: number_after(rest, "TTL=").map(|ttl| ttl as u32),
+        error: if rtt.is_none() {
+            Some(rest.trim_end_matches('.').to_string())
+        } else {
+            None
+        },
+        rtt,
+    })
+}
+
+/// Parse the statistics of Windows `ping.exe`
+///
+/// Format:
+/// ```text
+///     Packets: Sent = 4, Received = 4, Lost = 0 (0% loss),
+///     Minimum = 1ms, Maximum = 3ms, Average = 2ms
+/// ```
+fn parse_windows_ping_output(output: &str) -> Option<PingResult> {
+    let transmitted = number_after(output, "Sent = ")? as u32;
+    let received = number_after(output, "Received = ")? as u32;
+
+    Some(PingResult {
+        min_time: number_after(output, "Minimum = ").unwrap_or(0.0),
+        avg_time: number_after(output, "Average = ").unwrap_or(0.0),
+        max_time: number_after(output, "Maximum = ").unwrap_or(0.0),
+        ..ping_result(transmitted, received)
+    })
+}
+
+/// Parse the statistics of Linux `ping` (iputils and BusyBox)
+///
+/// Format:
+/// ```text
+/// 4 packets transmitted, 4 received, 0% packet loss, time 3004ms
+/// rtt min/avg/max/mdev = 0.031/0.041/0.049/0.007 ms
+/// ```
+/// BusyBox reports "4 packets received" and "round-trip min/avg/max = ..." instead.
+fn parse_linux_ping_output(output: &str) -> Option<PingResult> {
+    let summary = output
+        .lines()
+        .find(|line| line.contains("packets transmitted"))?;
+    let mut parts = summary.split(", ");
+    let transmitted = parts.next()?.split_whitespace().next()?.parse().ok()?;
+    let received = parts.next()?.split_whitespace().next()?.parse().ok()?;
+
+    let mut result = ping_result(transmitted, received);
+
+    let times = output
+        .lines()
+        .find(|line| line.starts_with("rtt ") || line.starts_with("round-trip "))
+        .and_then(|line| line.split_once(" = "))
+        .map(|(_, values)| {
+            values
+                .split_whitespace()
+                .next()
+                .unwrap_or("")
+                .split('/')
+                .filter_map(|value| value.parse::<f32>().ok())
+                .collect::<Vec<_>>()
+        });
+    if let Some([min, avg, max, ..]) = times.as_deref() {
+        result.min_time = *min;
+        result.avg_time = *avg;
+        result.max_time = *max;
+    }
+
+    Some(result)
+}
+
+/// Create a result with counts and loss filled in and no timing
+fn ping_result(transmitted: u32, received: u32) -> PingResult {
+    let packet_loss = if transmitted == 0 {
+        0.0
+    } else {
+        transmitted.saturating_sub(received) as f32 * 100.0 / transmitted as f32
+    };
+
+    PingResult {
+        destination: String::new(),
+        transmitted,
+        received,
+        packet_loss,
+        min_time: 0.0,
+        avg_time: 0.0,
+        max_time: 0.0,
+        jitter: 0.0,
+        raw_output: String::new(),
+    }
+}
+
+/// Parse the number following `key` in `text`, e.g. 12 in "Minimum = 12ms"
+fn number_after(text: &str, key: &str) -> Option<f32> {
+    let start = text.find(key)? + key.len();
+    let number: String = text[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    number.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPUTILS_OUTPUT: &str = "\
+PING 8.8.8.8 (8.8.8.8) 56(84) bytes of data.
+64 bytes from 8.8.8.8: icmp_seq=1 ttl=117 time=12.3 ms
+From 172.20.0.1 icmp_seq=2 Destination Host Unreachable
+64 bytes from 8.8.8.8: icmp_seq=4 ttl=117 time=11.9 ms
+
+--- 8.8.8.8 ping statistics ---
+4 packets transmitted, 2 received, +1 errors, 50% packet loss, time 3005ms
+rtt min/avg/max/mdev = 11.900/12.100/12.300/0.200 ms
+";
+
+    const BUSYBOX_OUTPUT: &str = "\
+PING 8.8.8.8 (8.8.8.8): 56 data bytes
+64 bytes from 8.8.8.8: seq=0 ttl=117 time=12.345 ms
+64 bytes from 8.8.8.8: seq=2 ttl=117 time=12.100 ms
+
+--- 8.8.8.8 ping statistics ---
+3 packets transmitted, 2 packets received, 33% packet loss
+round-trip min/avg/max = 12.100/12.222/12.345 ms
+";
+
+    const PING_EXE_OUTPUT: &str = "\r
+Pinging 8.8.8.8 with 32 bytes of data:\r
+Reply from 8.8.8.8: bytes=32 time=12ms TTL=117\r
+Request timed out.\r
+Reply from 10.0.0.1: Destination host unreachable.\r
+Reply from 8.8.8.8: bytes=32 time<1ms TTL=117\r
+\r
+Ping statistics for 8.8.8.8:\r
+    Packets: Sent = 4, Received = 3, Lost = 1 (25% loss),\r
+Approximate round trip times in milli-seconds:\r
+    Minimum = 0ms, Maximum = 12ms, Average = 6ms\r
+";
+
+    /// Every request reported while streaming `output`, as (sequence, rtt, error)
+    fn stream(output: &str, linux: bool) -> Vec<(u32, Option<f32>, Option<String>)> {
+        let mut next_sequence = 1;
+        output
+            .lines()
+            .flat_map(|line| parse_reply_line(line, linux, &mut next_sequence))
+            .map(|reply| (reply.sequence, reply.rtt, reply.error))
+            .collect()
+    }
+
+    #[test]
+    fn parses_iputils_replies() {
+        let reply =
+            parse_linux_reply_line("64 bytes from 8.8.8.8: icmp_seq=1 ttl=117 time=12.3 ms")
+                .unwrap();
+        assert_eq!(reply.sequence, 1);
+        assert_eq!(reply.from, "8.8.8.8");
+        assert_eq!(reply.bytes, 64);
+        assert_eq!(reply.ttl, Some(117));
+        assert_eq!(reply.rtt, Some(12.3));
+        assert_eq!(reply.error, None);
+
+        let reply = parse_linux_reply_line(
+            "64 bytes from dns.google (8.8.8.8): icmp_seq=7 ttl=117 time=12.3 ms",
+        )
+        .unwrap();
+        assert_eq!(reply.sequence, 7);
+        assert_eq!(reply.from, "dns.google (8.8.8.8)");
+
+        let reply =
+            parse_linux_reply_line("From 172.20.0.1 icmp_seq=2 Destination Host Unreachable")
+                .unwrap();
+        assert_eq!(reply.sequence, 2);
+        assert_eq!(reply.from, "172.20.0.1");
+        assert_eq!(reply.rtt, None);
+        assert_eq!(reply.error.as_deref(), Some("Destination Host Unreachable"));
+
+        assert!(parse_linux_reply_line("PING 8.8.8.8 (8.8.8.8) 56(84) bytes of data.").is_none());
+    }
+
+    #[test]
+    fn numbers_busybox_replies_from_one() {
+        let reply =
+            parse_linux_reply_line("64 bytes from 8.8.8.8: seq=0 ttl=117 time=12.345 ms").unwrap();
+        assert_eq!(reply.sequence, 1);
+        assert_eq!(reply.rtt, Some(12.345));
+    }
+
+    #[test]
+    fn parses_ping_exe_replies() {
+        let reply =
+            parse_windows_reply_line("Reply from 8.8.8.8: bytes=32 time=12ms TTL=117", 3).unwrap();
+        assert_eq!(reply.sequence, 3);
+        assert_eq!(reply.from, "8.8.8.8");
+        assert_eq!(reply.bytes, 32);
+        assert_eq!(reply.ttl, Some(117));
+        assert_eq!(reply.rtt, Some(12.0));
+
+        let reply = parse_windows_reply_line("Reply from ::1: time<1ms", 1).unwrap();
+        assert_eq!(reply.from, "::1");
+        assert_eq!(reply.rtt, Some(1.0));
+
+        let reply =
+            parse_windows_reply_line("Reply from 10.0.0.1: Destination host unreachable.", 1)
+                .unwrap();
+        assert_eq!(reply.rtt, None);
+        assert_eq!(reply.error.as_deref(), Some("Destination host unreachable"));
+
+        let reply = parse_windows_reply_line("Request timed out.", 2).unwrap();
+        assert_eq!(reply.sequence, 2);
+        assert_eq!(reply.rtt, None);
+        assert!(reply.error.is_some());
+    }
+
+    #[test]
+    fn reports_requests_linux_ping_skipped_as_lost() {
+        let replies = stream(IPUTILS_OUTPUT, true);
+        let sequences: Vec<u32> = replies.iter().map(|reply| reply.0).collect();
+        assert_eq!(sequences, [1, 2, 3, 4]);
+        assert_eq!(
+            replies[1].2.as_deref(),
+            Some("Destination Host Unreachable")
+        );
+        assert_eq!(replies[2].1, None);
+        assert!(replies[2].2.is_some());
+        assert_eq!(replies[3].1, Some(11.9));
+    }
+
+    #[test]
+    fn reports_requests_busybox_skipped_as_lost() {
+        let replies = stream(BUSYBOX_OUTPUT, true);
+        let sequences: Vec<u32> = replies.iter().map(|reply| reply.0).collect();
+        assert_eq!(sequences, [1, 2, 3]);
+        assert_eq!(replies[0].1, Some(12.345));
+        assert_eq!(replies[1].1, None);
+        assert_eq!(replies[2].1, Some(12.1));
+    }
+
+    #[test]
+    fn numbers_ping_exe_requests_in_order() {
+        let replies = stream(PING_EXE_OUTPUT, false);
+        let sequences: Vec<u32> = replies.iter().map(|reply| reply.0).collect();
+        assert_eq!(sequences, [1, 2, 3, 4]);
+        assert_eq!(replies[1].1, None);
+        assert_eq!(replies[3].1, Some(1.0));
+    }
+
+    #[test]
+    fn parses_iputils_statistics() {
+        let result = parse_linux_ping_output(IPUTILS_OUTPUT).unwrap();
+        assert_eq!((result.transmitted, result.received), (4, 2));
+        assert_eq!(result.packet_loss, 50.0);
+        assert_eq!(
+            (result.min_time, result.avg_time, result.max_time),
+            (11.9, 12.1, 12.3)
+        );
+    }
+
+    #[test]
+    fn parses_busybox_statistics() {
+        let result = parse_linux_ping_output(BUSYBOX_OUTPUT).unwrap();
+        assert_eq!((result.transmitted, result.received), (3, 2));
+        assert_eq!(
+            (result.min_time, result.avg_time, result.max_time),
+            (12.1, 12.222, 12.345)
+        );
+    }
+
+    #[test]
+    fn parses_ping_exe_statistics() {
+        let result = parse_windows_ping_output(PING_EXE_OUTPUT).unwrap();
+        assert_eq!((result.transmitted, result.received), (4, 3));
+        assert_eq!(result.packet_loss, 25.0);
+        assert_eq!(
+            (result.min_time, result.avg_time, result.max_time),
+            (0.0, 6.0, 12.0)
+        );
+    }
 
-    Ok(result)
+    #[test]
+    fn statistics_need_a_summary() {
+        assert!(parse_linux_ping_output("ping: unknown host nowhere").is_none());
+        assert!(parse_windows_ping_output("Ping request could not find host nowhere.").is_none());
+    }
 }
@@ -0,0 +1,122 @@
+//! Python helpers of Linux sources
+//!
+//! The throughput test and the test listener run in this process on Windows. In WSL and
+//! containers they run a small Python 3 script instead, so no extra tools are needed there.
+//! Every script runs after [`PRELUDE`], which holds what they share: opening the listening
+//! socket, logging, and exiting once WSLNetMan goes away.
+
+use super::source::ProbeSource;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use std::io::Write;
+use std::process::{Child, Stdio};
+use std::time::Duration;
+
+/// Line a script prints once it listens
+pub const READY: &str = "READY";
+
+/// Time allowed for a script to start listening, which includes starting WSL
+pub const START_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Exit code of `docker exec` and `podman exec` when the program is not in the container
+const NOT_FOUND_EXIT_CODE: i32 = 127;
+
+/// Functions shared by every script
+const PRELUDE: &str = r#"
+import json, os, socket, struct, sys, threading, time
+
+
+def log(message):
+    print(message, flush=True)
+
+
+def peer_name(peer):
+    return ("[%s]:%d" if ":" in peer[0] else "%s:%d") % peer[:2]
+
+
+def watch_stdin():
+    # Exit once WSLNetMan closes the pipe or goes away, as killing wsl.exe may not reach us
+    sys.stdin.read()
+    os._exit(0)
+
+
+def exit_with_stdin():
+    threading.Thread(target=watch_stdin, daemon=True).start()
+
+
+def bind(kind, family, host, port):
+    sock = socket.socket(family, kind)
+    sock.setsockopt(socket.SOL_SOCKET, socket.SO_REUSEADDR, 1)
+    if host == "::":
+        sock.setsockopt(socket.IPPROTO_IPV6, socket.IPV6_V6ONLY, 0)
+    sock.bind((host, port))
+    if kind == socket.SOCK_STREAM:
+        sock.listen(16)
+    return sock
+
+
+def listen(kind, host, port):
+    # "::" listens on every IPv4 and IPv6 address, or on every IPv4 address without IPv6
+    if host == "::":
+        try:
+            return bind(kind, socket.AF_INET6, host, port)
+        except OSError:
+            host = "0.0.0.0"
+    return bind(kind, socket.AF_INET6 if ":" in host else socket.AF_INET, host, port)
+"#;
+
+/// Start `script` after [`PRELUDE`] in `source`, passing `args` to it
+///
+/// The script is sent on standard input, which then stays open, so a long running script
+/// can exit once it closes: when WSLNetMan drops the child's input or goes away.
+pub fn spawn(
+    source: &ProbeSource,
+    script: &str,
+    args: &[String],
+) -> Result<Child, Box<dyn std::error::Error>> {
+    // Slim container images often come without Python, which would only show as a timeout
+    if let ProbeSource::Container { .. } = source {
+        check_python(source)?;
+    }
+    let mut python_args = vec![
+        "-c".to_string(),
+        "import base64, sys; exec(base64.b64decode(sys.stdin.readline()))".to_string(),
+    ];
+    python_args.extend(args.iter().cloned());
+    let mut child = source
+        .command("python3", &python_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run python3 in {source}: {e}"))?;
+    if let Some(stdin) = child.stdin.as_mut() {
+        let line = format!("{}\n", BASE64_STANDARD.encode(format!("{PRELUDE}{script}")));
+        stdin.write_all(line.as_bytes())?;
+        stdin.flush()?;
+    }
+    Ok(child)
+}
+
+/// Check that `python3` runs in `source`
+fn check_python(source: &ProbeSource) -> Result<(), String> {
+    let output = source
+        .command("python3", &["--version".to_string()])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Could not run python3 in {source}: {e}"))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let detail = stderr.trim();
+    if output.status.code() == Some(NOT_FOUND_EXIT_CODE)
+        || detail.contains("not found")
+        || detail.contains("No such file or directory")
+    {
+        Err(format!(
+            "python3 is not installed in {source}, add Python 3 to its image to run this there"
+        ))
+    } else {
+        Err(format!("Could not run python3 in {source}: {detail}"))
+    }
+}
@@ -0,0 +1,468 @@
+//! Raw TCP and UDP probes
+//!
+//! This module opens a TCP connection or sends a UDP datagram to a port, sends a payload and
+//! collects whatever comes back, like `nc`. It is meant for databases, Redis, SMTP and custom
+//! protocols that the HTTP probe cannot talk to. From Windows the probe runs in this process;
+//! from WSL and containers it runs `nc` there.
+
+use super::source::ProbeSource;
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Transport protocol of a socket probe
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SocketProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+impl std::fmt::Display for SocketProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SocketProtocol::Tcp => write!(f, "TCP"),
+            SocketProtocol::Udp => write!(f, "UDP"),
+        }
+    }
+}
+
+impl std::str::FromStr for SocketProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "TCP" => Ok(SocketProtocol::Tcp),
+            "UDP" => Ok(SocketProtocol::Udp),
+            _ => Err(format!("Unknown socket protocol: {s}")),
+        }
+    }
+}
+
+/// Options of a socket probe
+#[derive(Debug, Clone)]
+pub struct SocketProbeOptions {
+    pub protocol: SocketProtocol,
+    pub port: u16,
+    /// Bytes sent once connected; may be empty to only read a banner
+    pub payload: Vec<u8>,
+    /// Time allowed to connect
+    pub connect_timeout: Duration,
+    /// How long to keep waiting when nothing more arrives
+    pub idle_timeout: Duration,
+    /// Stop reading after this many bytes
+    pub max_bytes: usize,
+}
+
+impl Default for SocketProbeOptions {
+    fn default() -> Self {
+        SocketProbeOptions {
+            protocol: SocketProtocol::Tcp,
+            port: 0,
+            payload: Vec::new(),
+            connect_timeout: Duration::from_secs(5),
+            idle_timeout: Duration::from_secs(2),
+            max_bytes: 64 * 1024,
+        }
+    }
+}
+
+/// Represents the result of a socket probe
+#[derive(Debug, Clone)]
+pub struct SocketProbeResult {
+    pub destination: String,
+    pub protocol: SocketProtocol,
+    pub remote_address: Option<String>,
+    pub local_address: Option<String>,
+    /// Time to establish the TCP connection, in milliseconds (not measured for `nc`)
+    pub connect_time: Option<f64>,
+    /// Time from sending the payload until the first byte came back, in milliseconds
+    pub first_byte_time: Option<f64>,
+    /// Time of the whole probe, in milliseconds, including the idle wait at the end
+    pub total_time: f64,
+    pub bytes_sent: usize,
+    pub received: Vec<u8>,
+    /// Whether the peer closed the connection before the idle timeout
+    pub closed_by_peer: bool,
+    /// Error that ended reading, e.g. a UDP port reported unreachable
+    pub error: Option<String>,
+    pub raw_output: String,
+}
+
+/// Parse a payload typed by the user
+///
+/// Hex payloads ignore whitespace and an optional `0x` or `0X` prefix per byte ("0d 0a",
+/// "0x0d0x0a").
+/// Text payloads understand `\r`, `\n`, `\t`, `\0`, `\\` and `\xNN` escapes, so line based
+/// protocols can be spoken ("PING\r\n").
+pub fn parse_payload(text: &str, hex: bool) -> Result<Vec<u8>, String> {
+    if hex {
+        let digits = text
+            .split_whitespace()
+            .flat_map(|word| word.split("0x").flat_map(|part| part.split("0X")))
+            .collect::<String>();
+        if let Some(invalid) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!("Invalid hex digit: {invalid}"));
+        }
+        if digits.len() % 2 != 0 {
+            return Err("Hex payload has an odd number of digits".to_string());
+        }
+        // Only ASCII digits are left, so every byte is one digit
+        return digits
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| {
+                let pair = std::str::from_utf8(pair).unwrap_or_default();
+                u8::from_str_radix(pair, 16).map_err(|_| format!("Invalid hex byte: {pair}"))
+            })
+            .collect();
+    }
+
+    let mut bytes = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('r') => bytes.push(b'\r'),
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('0') => bytes.push(0),
+            Some('\\') => bytes.push(b'\\'),
+            Some('x') => {
+                let code = chars.by_ref().take(2).collect::<String>();
+                let byte = u8::from_str_radix(&code, 16)
+                    .map_err(|_| format!("Invalid escape: \\x{code}"))?;
+                bytes.push(byte);
+            }
+            Some(other) => return Err(format!("Unknown escape: \\{other}")),
+            None => bytes.push(b'\\'),
+        }
+    }
+    Ok(bytes)
+}
+
+/// Format bytes as a hex dump with an offset, 16 bytes per line and a printable column
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            let text = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:08x}  {hex:<47}  |{text}|", line * 16)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Send a payload over TCP or UDP and collect the response
+///
+/// # Arguments
+///
+/// * `source` - The environment to send from
+/// * `destination` - The IP address or hostname to connect to
+/// * `source_address` - The local address to send from
+/// * `options` - Protocol, port, payload and timeouts
+/// * `stop` - Set to stop waiting for more data (Windows only)
+///
+/// # Returns
+///
+/// A `Result` containing the probe result or an error if no connection could be made
+pub fn probe(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &SocketProbeOptions,
+    stop: &AtomicBool,
+) -> Result<SocketProbeResult, Box<dyn std::error::Error>> {
+    if source.is_linux() {
+        probe_netcat(source, destination, source_address, options)
+    } else {
+        probe_native(destination, source_address, options, stop)
+    }
+}
+
+/// Probe from this process with a TCP or UDP socket
+fn probe_native(
+    destination: &str,
+    source_address: Option<&str>,
+    options: &SocketProbeOptions,
+    stop: &AtomicBool,
+) -> Result<SocketProbeResult, Box<dyn std::error::Error>> {
+    let host = destination.trim_start_matches('[').trim_end_matches(']');
+    let source_ip = source_address
+        .map(|address| {
+            address
+                .parse::<IpAddr>()
+                .map_err(|e| format!("Invalid source address {address}: {e}"))
+        })
+        .transpose()?;
+    // Prefer an address of the source address's family
+    let addresses = (host, options.port).to_socket_addrs()?.collect::<Vec<_>>();
+    let address = addresses
+        .iter()
+        .find(|address| source_ip.is_none_or(|ip| ip.is_ipv4() == address.is_ipv4()))
+        .or(addresses.first())
+        .copied()
+        .ok_or_else(|| format!("Could not resolve {destination}"))?;
+
+    let (socket_type, protocol) = match options.protocol {
+        SocketProtocol::Tcp => (Type::STREAM, Protocol::TCP),
+        SocketProtocol::Udp => (Type::DGRAM, Protocol::UDP),
+    };
+    let socket = Socket::new(Domain::for_address(address), socket_type, Some(protocol))?;
+    if let Some(ip) = source_ip {
+        socket.bind(&SockAddr::from(SocketAddr::new(ip, 0)))?;
+    }
+
+    let start = Instant::now();
+    let mut connect_time = None;
+    match options.protocol {
+        SocketProtocol::Tcp => {
+            socket
+                .connect_timeout(&SockAddr::from(address), options.connect_timeout)
+                .map_err(|e| {
+                    // Nothing, not even the WSL localhost relay, listens in an excluded range
+                    match crate::network::excluded_port_warning(
+                        "TCP",
+                        Some(address.ip()),
+                        options.port,
+                    ) {
+                        Some(warning) if address.ip().is_loopback() => {
+                            format!("Could not connect to {address}: {e}. {warning}")
+                        }
+                        _ => format!("Could not connect to {address}: {e}"),
+                    }
+                })?;
+            connect_time = Some(start.elapsed().as_secs_f64() * 1000.0);
+        }
+        // Connecting a UDP socket only filters datagrams to those from the destination
+        SocketProtocol::Udp => socket.connect(&SockAddr::from(address))?,
+    }
+    let local_address = socket
+        .local_addr()
+        .ok()
+        .and_then(|address| address.as_socket())
+        .map(|address| address.to_string());
+
+    let mut stream: std::net::TcpStream;
+    let mut datagram: std::net::UdpSocket;
+    let connection: &mut dyn ReadWrite = match options.protocol {
+        SocketProtocol::Tcp => {
+            stream = socket.into();
+            &mut stream
+        }
+        SocketProtocol::Udp => {
+            datagram = socket.into();
+            &mut datagram
+        }
+    };
+    connection.set_idle_timeout(options.idle_timeout)?;
+
+    let sent_at = Instant::now();
+    if !options.payload.is_empty() || options.protocol == SocketProtocol::Udp {
+        connection.send(&options.payload)?;
+    }
+
+    let mut received = Vec::new();
+    let mut first_byte_time = None;
+    let mut closed_by_peer = false;
+    let mut error = None;
+    let mut buffer = vec![0; 65536];
+    while received.len() < options.max_bytes && !stop.load(Ordering::Relaxed) {
+        match connection.receive(&mut buffer) {
+            Ok(0) if options.protocol == SocketProtocol::Tcp => {
+                closed_by_peer = true;
+                break;
+            }
+            Ok(count) => {
+                first_byte_time.get_or_insert(sent_at.elapsed().as_secs_f64() * 1000.0);
+                received.extend_from_slice(&buffer[..count]);
+            }
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) => {
+                // UDP reports a closed port as a connection reset (ICMP port unreachable)
+                closed_by_peer = true;
+                error = Some(e.to_string());
+                break;
+            }
+        }
+    }
+    received.truncate(options.max_bytes);
+
+    Ok(SocketProbeResult {
+        destination: destination.to_string(),
+        protocol: options.protocol,
+        remote_address: Some(address.to_string()),
+        local_address,
+        connect_time,
+        first_byte_time,
+        total_time: start.elapsed().as_secs_f64() * 1000.0,
+        bytes_sent: options.payload.len(),
+        received,
+        closed_by_peer,
+        raw_output: error.clone().unwrap_or_default(),
+        error,
+    })
+}
+
+/// The operations a probe needs from a TCP stream or a connected UDP socket
+trait ReadWrite {
+    fn set_idle_timeout(&mut self, timeout: Duration) -> std::io::Result<()>;
+    fn send(&mut self, payload: &[u8]) -> std::io::Result<()>;
+    fn receive(&mut self, buffer: &mut [u8]) -> std::io::Result<usize>;
+}
+
+impl ReadWrite for std::net::TcpStream {
+    fn set_idle_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))
+    }
+
+    fn send(&mut self, payload: &[u8]) -> std::io::Result<()> {
+        self.write_all(payload)
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        self.read(buffer)
+    }
+}
+
+impl ReadWrite for std::net::UdpSocket {
+    fn set_idle_timeout(&mut self, timeout: Duration) -> std::io::Result<()> {
+        self.set_read_timeout(Some(timeout))
+    }
+
+    fn send(&mut self, payload: &[u8]) -> std::io::Result<()> {
+        std::net::UdpSocket::send(self, payload).map(|_| ())
+    }
+
+    fn receive(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+        self.recv(buffer)
+    }
+}
+
+/// Probe with `nc` in a Linux source environment
+///
+/// `nc -w` closes the connection once nothing arrives for the idle timeout. The times include
+/// starting `nc` through `wsl.exe`, so only the total is reported.
+fn probe_netcat(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &SocketProbeOptions,
+) -> Result<SocketProbeResult, Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "-w".to_string(),
+        options.idle_timeout.as_secs().max(1).to_string(),
+    ];
+    if options.protocol == SocketProtocol::Udp {
+        args.push("-u".to_string());
+    }
+    if let Some(source_address) = source_address {
+        args.extend(["-s".to_string(), source_address.to_string()]);
+    }
+    args.extend([
+        destination
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string(),
+        options.port.to_string(),
+    ]);
+
+    let start = Instant::now();
+    let mut child = source
+        .command("nc", &args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not run nc from {source}: {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&options.payload)?;
+        // Dropping stdin tells nc the payload is complete
+    }
+    let output = child.wait_with_output()?;
+    let total_time = start.elapsed().as_secs_f64() * 1000.0;
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    if !output.status.success() && output.stdout.is_empty() {
+        let message = if stderr.trim().is_empty() {
+            format!("Could not connect to {destination}:{}", options.port)
+        } else {
+            stderr.trim().to_string()
+        };
+        return Err(format!("nc from {source} failed: {message}").into());
+    }
+
+    let mut received = output.stdout;
+    received.truncate(options.max_bytes);
+    Ok(SocketProbeResult {
+        destination: destination.to_string(),
+        protocol: options.protocol,
+        remote_address: None,
+        local_address: None,
+        connect_time: None,
+        first_byte_time: None,
+        total_time,
+        bytes_sent: options.payload.len(),
+        received,
+        closed_by_peer: false,
+        error: None,
+        raw_output: stderr,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_payloads_with_prefixes() {
+        assert_eq!(parse_payload("0d 0a", true).unwrap(), b"\r\n");
+        assert_eq!(parse_payload("0x0d0X0a", true).unwrap(), b"\r\n");
+        assert_eq!(
+            parse_payload("DEADbeef", true).unwrap(),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_hex_payloads() {
+        assert_eq!(
+            parse_payload("aé1", true).unwrap_err(),
+            "Invalid hex digit: é"
+        );
+        assert_eq!(
+            parse_payload("0g", true).unwrap_err(),
+            "Invalid hex digit: g"
+        );
+        assert!(parse_payload("abc", true).is_err());
+    }
+
+    #[test]
+    fn parses_text_escapes() {
+        assert_eq!(parse_payload(r"PING\r\n", false).unwrap(), b"PING\r\n");
+        assert_eq!(parse_payload(r"\x41\0é", false).unwrap(), b"A\0\xc3\xa9");
+        assert!(parse_payload(r"\q", false).is_err());
+    }
+}
@@ -0,0 +1,195 @@
+//! Probe sources
+//!
+//! This module describes where a probe runs: on Windows, inside a WSL distribution, or inside a
+//! container. Linux sources run the probe tools (`ping`, `curl`) through `wsl.exe`, with
+//! `docker exec`/`podman exec` in between for containers.
+
+use crate::network::{ContainerRuntime, NetworkEnvironment};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Represents the environment a probe is sent from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeSource {
+    /// The WSLNetMan process on Windows
+    Windows,
+    /// A WSL distribution, `None` for the default distribution
+    Wsl { distro: Option<String> },
+    /// A running container, reached through the runtime's CLI in the default WSL distribution
+    Container {
+        runtime: ContainerRuntime,
+        name: String,
+    },
+}
+
+impl ProbeSource {
+    /// Whether the probe runs Linux tools rather than Windows ones
+    pub fn is_linux(&self) -> bool {
+        !matches!(self, ProbeSource::Windows)
+    }
+
+    /// The environment whose network interfaces the probe can be bound to
+    ///
+    /// WSL 2 distributions share one network namespace, while containers have their own and
+    /// their interfaces are not listed.
+    pub fn environment(&self) -> Option<NetworkEnvironment> {
+        match self {
+            ProbeSource::Windows => Some(NetworkEnvironment::Windows),
+            ProbeSource::Wsl { .. } => Some(NetworkEnvironment::Wsl),
+            ProbeSource::Container { .. } => None,
+        }
+    }
+
+    /// The form parsed by `from_str`, e.g. "wsl:Ubuntu", used to store a source in files
+    pub fn spec(&self) -> String {
+        match self {
+            ProbeSource::Windows => "windows".to_string(),
+            ProbeSource::Wsl { distro: None } => "wsl".to_string(),
+            ProbeSource::Wsl {
+                distro: Some(distro),
+            } => format!("wsl:{distro}"),
+            ProbeSource::Container { runtime, name } => {
+                format!("{}:{name}", runtime.to_string().to_lowercase())
+            }
+        }
+    }
+
+    /// Build a command that runs `program` with `args` in this source
+    pub fn command(&self, program: &str, args: &[String]) -> Command {
+        match self {
+            ProbeSource::Windows => {
+                let mut command = Command::new(program);
+                command.args(args);
+                command
+            }
+            ProbeSource::Wsl { distro } => {
+                let mut command = Command::new("wsl.exe");
+                if let Some(distro) = distro {
+                    command.args(["-d", distro]);
+                }
+                command.arg("-e").arg(program).args(args);
+                command
+            }
+            ProbeSource::Container { runtime, name } => {
+                let cli = match runtime {
+                    ContainerRuntime::Docker => "docker",
+                    ContainerRuntime::Podman => "podman",
+                };
+                // Keep standard input attached, which the Python helpers are passed on
+                let mut command = Command::new("wsl.exe");
+                command
+                    .args(["-e", cli, "exec", "-i", name])
+                    .arg(program)
+                    .args(args);
+                command
+            }
+        }
+    }
+}
+
+impl ProbeSource {
+    /// Run `program` with `args` in this source, passing each line of its output to `on_line`
+    ///
+    /// The program is killed when `stop` is set, so commands that run until interrupted can be
+    /// streamed too.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the complete standard output and standard error
+    pub fn stream(
+        &self,
+        program: &str,
+        args: &[String],
+        stop: &AtomicBool,
+        mut on_line: impl FnMut(&str),
+    ) -> Result<(String, String), Box<dyn std::error::Error>> {
+        let mut child = self
+            .command(program, args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| format!("Could not capture {program} output"))?;
+        let mut stderr = child
+            .stderr
+            .take()
+            .ok_or_else(|| format!("Could not capture {program} errors"))?;
+
+        let mut output = String::new();
+        let mut errors = String::new();
+        let finished = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            let child = &mut child;
+            let finished = &finished;
+            scope.spawn(move || {
+                while !finished.load(Ordering::Relaxed) {
+                    if stop.load(Ordering::Relaxed) {
+                        let _ = child.kill();
+                        return;
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+            });
+            // Drain standard error alongside, so a chatty command cannot block on a full pipe
+            let errors = &mut errors;
+            scope.spawn(move || {
+                let _ = stderr.read_to_string(errors);
+            });
+
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                on_line(&line);
+                output.push_str(&line);
+                output.push('\n');
+            }
+            finished.store(true, Ordering::Relaxed);
+        });
+
+        child.wait()?;
+        Ok((output, errors))
+    }
+}
+
+impl std::fmt::Display for ProbeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProbeSource::Windows => write!(f, "Windows"),
+            ProbeSource::Wsl { distro: None } => write!(f, "WSL (default)"),
+            ProbeSource::Wsl {
+                distro: Some(distro),
+            } => write!(f, "WSL ({distro})"),
+            ProbeSource::Container { runtime, name } => write!(f, "{runtime} container {name}"),
+        }
+    }
+}
+
+impl std::str::FromStr for ProbeSource {
+    type Err = String;
+
+    /// Parse "windows", "wsl", "wsl:<distro>", "docker:<container>" or "podman:<container>"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, name) = match s.split_once(':') {
+            Some((kind, name)) => (kind, Some(name.to_string())),
+            None => (s, None),
+        };
+        match (kind.to_ascii_lowercase().as_str(), name) {
+            ("windows", None) => Ok(ProbeSource::Windows),
+            ("wsl", distro) => Ok(ProbeSource::Wsl { distro }),
+            ("docker", Some(name)) => Ok(ProbeSource::Container {
+                runtime: ContainerRuntime::Docker,
+                name,
+            }),
+            ("podman", Some(name)) => Ok(ProbeSource::Container {
+                runtime: ContainerRuntime::Podman,
+                name,
+            }),
+            _ => Err(format!(
+                "Unknown source {s}: use windows, wsl, wsl:<distro>, docker:<container> or podman:<container>"
+            )),
+        }
+    }
+}
@@ -0,0 +1,863 @@
+//! Throughput tests
+//!
+//! This module measures the bandwidth between two probe sources, like `iperf`: a server is
+//! started in one source and a client in another sends a TCP stream or paced UDP datagrams to
+//! it for a few seconds. The server reports what arrived, so the result shows the throughput
+//! that made it through, TCP retransmits of the client, and UDP loss and jitter.
+//!
+//! On Windows both sides run in this process. In WSL and containers they run a Python script
+//! speaking the same protocol, see [`super::python`].
+
+use super::python;
+use super::socket::SocketProtocol;
+use super::source::ProbeSource;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Socket, Type};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Sequence number of the datagram that ends a UDP test, carrying the number of datagrams sent
+const END_OF_TEST: u64 = u64::MAX;
+
+/// Size of the sequence number and send time at the start of every UDP datagram
+const UDP_HEADER_SIZE: usize = 16;
+
+/// Size of the buffers written by TCP clients
+const TCP_BUFFER_SIZE: usize = 128 * 1024;
+
+/// Time the server waits for the client, on top of the test duration
+const SERVER_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Server and client run by WSL and container sources
+///
+/// It prints "READY" once the server listens and one JSON report per side when done.
+const PYTHON_SCRIPT: &str = r#"
+END = 0xFFFFFFFFFFFFFFFF
+HEADER = struct.Struct("!QQ")
+
+
+def report(value):
+    log(json.dumps(value))
+
+
+def tcp_retransmits(sock):
+    try:
+        # tcpi_total_retrans of struct tcp_info
+        return struct.unpack_from("=I", sock.getsockopt(socket.IPPROTO_TCP, socket.TCP_INFO, 104), 100)[0]
+    except (AttributeError, OSError, struct.error):
+        return None
+
+
+def tcp_server(port, timeout):
+    listener = listen(socket.SOCK_STREAM, "::", port)
+    log("READY")
+    listener.settimeout(timeout)
+    conn, _ = listener.accept()
+    conn.settimeout(timeout)
+    received = 0
+    start = end = None
+    while True:
+        data = conn.recv(131072)
+        if not data:
+            break
+        end = time.monotonic()
+        if start is None:
+            start = end
+        received += len(data)
+    result = {"bytes": received, "seconds": end - start if start is not None else 0.0}
+    conn.sendall((json.dumps(result) + "\n").encode())
+    conn.close()
+    report(result)
+
+
+def udp_server(port, timeout):
+    sock = listen(socket.SOCK_DGRAM, "::", port)
+    log("READY")
+    sock.settimeout(timeout)
+    received = total = out_of_order = 0
+    highest = -1
+    jitter = 0.0
+    previous_transit = None
+    start = end = sent = peer = None
+    while True:
+        try:
+            data, peer = sock.recvfrom(65535)
+        except socket.timeout:
+            break
+        if len(data) < HEADER.size:
+            continue
+        sequence, value = HEADER.unpack_from(data)
+        if sequence == END:
+            sent = value
+            break
+        # RFC 3550 interarrival jitter; the clock offset between the hosts cancels out
+        transit = time.time_ns() // 1000 - value
+        if previous_transit is not None:
+            jitter += (abs(transit - previous_transit) - jitter) / 16
+        previous_transit = transit
+        if sequence < highest:
+            out_of_order += 1
+        else:
+            highest = sequence
+        received += 1
+        total += len(data)
+        end = time.monotonic()
+        if start is None:
+            start = end
+            sock.settimeout(3)
+    expected = sent if sent is not None else highest + 1
+    result = {
+        "bytes": total,
+        "seconds": end - start if start is not None else 0.0,
+        "packets": received,
+        "lost": max(expected - received, 0),
+        "out_of_order": out_of_order,
+        "jitter_ms": jitter / 1000,
+    }
+    if sent is not None:
+        for _ in range(3):
+            sock.sendto(json.dumps(result).encode(), peer)
+    report(result)
+
+
+def tcp_client(host, port, seconds, timeout):
+    conn = socket.create_connection((host, port), timeout=timeout)
+    buffer = bytes(131072)
+    sent = 0
+    start = time.monotonic()
+    while time.monotonic() - start < seconds:
+        sent += conn.send(buffer)
+    elapsed = time.monotonic() - start
+    conn.shutdown(socket.SHUT_WR)
+    line = conn.makefile("r").readline()
+    server = json.loads(line) if line else None
+    report({"bytes_sent": sent, "seconds": elapsed, "retransmits": tcp_retransmits(conn), "server": server})
+
+
+def udp_client(host, port, seconds, timeout, bitrate, size):
+    family, kind, _, _, address = socket.getaddrinfo(host, port, 0, socket.SOCK_DGRAM)[0]
+    # Not connected, as a server bound to every address may answer from another one
+    sock = socket.socket(family, kind)
+    payload = bytearray(max(size, HEADER.size))
+    interval = len(payload) * 8 / bitrate
+    sent = 0
+    start = next_send = time.monotonic()
+    while True:
+        now = time.monotonic()
+        if now - start >= seconds:
+            break
+        if now < next_send:
+            time.sleep(min(next_send - now, 0.01))
+            continue
+        HEADER.pack_into(payload, 0, sent, time.time_ns() // 1000)
+        try:
+            sock.sendto(payload, address)
+            sent += 1
+        except OSError:
+            pass
+        next_send += interval
+    elapsed = time.monotonic() - start
+    sock.settimeout(0.2)
+    server = None
+    for _ in range(int(timeout / 0.2) + 1):
+        try:
+            sock.sendto(HEADER.pack(END, sent), address)
+            server = json.loads(sock.recv(65535))
+            break
+        except (OSError, ValueError):
+            pass
+    report({"bytes_sent": sent * len(payload), "seconds": elapsed, "packets_sent": sent, "server": server})
+
+
+try:
+    role, protocol, args = sys.argv[1], sys.argv[2], sys.argv[3:]
+    if role == "server":
+        exit_with_stdin()
+        (tcp_server if protocol == "tcp" else udp_server)(int(args[0]), float(args[1]))
+    elif protocol == "tcp":
+        tcp_client(args[0], int(args[1]), float(args[2]), float(args[3]))
+    else:
+        udp_client(args[0], int(args[1]), float(args[2]), float(args[3]), float(args[4]), int(args[5]))
+except Exception as e:
+    report({"error": str(e)})
+"#;
+
+/// Options of a throughput test
+#[derive(Debug, Clone)]
+pub struct ThroughputOptions {
+    pub protocol: SocketProtocol,
+    pub port: u16,
+    /// How long the client sends
+    pub duration: Duration,
+    /// Rate UDP datagrams are sent at, in bits per second; TCP sends as fast as it can
+    ///
+    /// UDP tests need at least one datagram per second, see [`ThroughputOptions::min_bitrate`].
+    pub bitrate: u64,
+    /// Size of UDP datagrams, in bytes
+    pub datagram_size: usize,
+}
+
+impl Default for ThroughputOptions {
+    fn default() -> Self {
+        ThroughputOptions {
+            protocol: SocketProtocol::Tcp,
+            port: 5201,
+            duration: Duration::from_secs(10),
+            bitrate: 100_000_000,
+            datagram_size: 1400,
+        }
+    }
+}
+
+impl ThroughputOptions {
+    /// Lowest UDP bitrate, one datagram per second
+    pub fn min_bitrate(&self) -> u64 {
+        self.datagram_size.max(UDP_HEADER_SIZE) as u64 * 8
+    }
+}
+
+/// Represents the result of a throughput test
+#[derive(Debug, Clone)]
+pub struct ThroughputResult {
+    pub protocol: SocketProtocol,
+    /// Address the client sent to
+    pub destination: String,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Time the server received data for, in seconds
+    pub seconds: f64,
+    /// Received bits per second
+    pub throughput: f64,
+    /// TCP segments the client retransmitted
+    pub retransmits: Option<u64>,
+    /// Whether `retransmits` counts every connection of the client's system, as Windows does
+    /// not report them per connection
+    pub retransmits_system_wide: bool,
+    pub packets_sent: Option<u64>,
+    pub packets_received: Option<u64>,
+    pub packets_lost: Option<u64>,
+    pub out_of_order: Option<u64>,
+    /// UDP interarrival jitter, in milliseconds
+    pub jitter: Option<f64>,
+    pub raw_output: String,
+}
+
+impl ThroughputResult {
+    /// Share of the UDP datagrams that were lost, in percent
+    pub fn loss(&self) -> Option<f64> {
+        let sent = self.packets_sent.filter(|sent| *sent > 0)?;
+        Some(self.packets_lost? as f64 * 100.0 / sent as f64)
+    }
+}
+
+/// What the server received
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ServerReport {
+    bytes: u64,
+    seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    packets: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lost: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    out_of_order: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    jitter_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// What the client sent, with the server's report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ClientReport {
+    bytes_sent: u64,
+    seconds: f64,
+    packets_sent: Option<u64>,
+    retransmits: Option<u64>,
+    server: Option<ServerReport>,
+    error: Option<String>,
+}
+
+/// Address the client can reach the server at, when it can be worked out
+///
+/// Sources in the same environment use the loopback address. Windows reaches WSL at the WSL
+/// `eth0` address and WSL reaches Windows at its default gateway, which is the Windows host
+/// with NAT networking. Containers have to be given an address.
+pub fn default_server_address(client: &ProbeSource, server: &ProbeSource) -> Option<String> {
+    match (client, server) {
+        (ProbeSource::Windows, ProbeSource::Windows) => Some("127.0.0.1".to_string()),
+        (ProbeSource::Wsl { .. }, ProbeSource::Wsl { .. }) => Some("127.0.0.1".to_string()),
+        (ProbeSource::Windows, ProbeSource::Wsl { .. }) => {
+            crate::network::wsl::get_interface_addresses()
+                .ok()?
+                .into_iter()
+                .find(|address| address.interface == "eth0" && !address.address.contains(':'))
+                .map(|address| address.address)
+        }
+        (_, ProbeSource::Windows) => crate::network::wsl::get_default_gateway().ok()?,
+        _ => None,
+    }
+}
+
+/// Run a throughput test from `client` to a server started in `server`, reachable at `address`
+pub fn run(
+    client: &ProbeSource,
+    server: &ProbeSource,
+    address: &str,
+    options: &ThroughputOptions,
+    stop: &AtomicBool,
+) -> Result<ThroughputResult, Box<dyn std::error::Error>> {
+    if options.protocol == SocketProtocol::Udp && options.bitrate < options.min_bitrate() {
+        return Err(format!(
+            "Bitrate must be at least {} bit/s, one datagram per second",
+            options.min_bitrate()
+        )
+        .into());
+    }
+    let host = address.trim_start_matches('[').trim_end_matches(']');
+    let idle_timeout = options.duration + SERVER_IDLE_TIMEOUT;
+    let mut raw_output = format!(
+        "{} throughput test from {client} to {server} at {host}:{} for {}s\n",
+        options.protocol,
+        options.port,
+        options.duration.as_secs_f64()
+    );
+
+    let (client_report, server_output) = std::thread::scope(|scope| {
+        // Start the server, then run the client against it
+        let server_output: Box<dyn FnOnce() -> String + Send> = if server.is_linux() {
+            let mut process = ServerProcess::start(server, options, idle_timeout)?;
+            if let Err(e) = process.wait_ready(stop) {
+                return Err(format!("Server in {server} did not start: {e}").into());
+            }
+            Box::new(move || process.finish())
+        } else {
+            if let Some(warning) = excluded_port_warning(options) {
+                raw_output.push_str(&format!("Warning: {warning}\n"));
+            }
+            let listener = NativeListener::bind(options)?;
+            let handle = scope.spawn(move || listener.serve(idle_timeout, stop));
+            Box::new(move || match handle.join() {
+                Ok(Ok(report)) => serde_json::to_string(&report).unwrap_or_default(),
+                Ok(Err(e)) => format!("Server error: {e}"),
+                Err(_) => "Server panicked".to_string(),
+            })
+        };
+
+        let client_report = if client.is_linux() {
+            run_python_client(client, host, options)
+        } else {
+            run_native_client(host, options, stop)
+        };
+        Ok::<_, Box<dyn std::error::Error>>((client_report, server_output()))
+    })?;
+
+    raw_output.push_str(&format!("Server: {}\n", server_output.trim()));
+    let (report, retransmits_system_wide) = client_report?;
+    raw_output.push_str(&format!(
+        "Client: {}\n",
+        serde_json::to_string(&report).unwrap_or_default()
+    ));
+    if let Some(error) = report.error {
+        return Err(format!("Client in {client} failed: {error}").into());
+    }
+    let received = report.server.ok_or_else(|| {
+        format!(
+            "The server did not report a result; check that {host}:{} is reachable from {client} and not blocked by a firewall",
+            options.port
+        )
+    })?;
+    if let Some(error) = received.error {
+        return Err(format!("Server in {server} failed: {error}").into());
+    }
+
+    let seconds = if received.seconds > 0.0 {
+        received.seconds
+    } else {
+        report.seconds
+    };
+    Ok(ThroughputResult {
+        protocol: options.protocol,
+        destination: format!("{host}:{}", options.port),
+        bytes_sent: report.bytes_sent,
+        bytes_received: received.bytes,
+        seconds,
+        throughput: if seconds > 0.0 {
+            received.bytes as f64 * 8.0 / seconds
+        } else {
+            0.0
+        },
+        retransmits: report.retransmits,
+        retransmits_system_wide,
+        packets_sent: report.packets_sent,
+        packets_received: received.packets,
+        packets_lost: received.lost,
+        out_of_order: received.out_of_order,
+        jitter: received.jitter_ms,
+        raw_output,
+    })
+}
+
+/// Server started in a WSL or container source
+struct ServerProcess {
+    child: std::process::Child,
+    lines: mpsc::Receiver<String>,
+    output: Vec<String>,
+}
+
+impl ServerProcess {
+    fn start(
+        source: &ProbeSource,
+        options: &ThroughputOptions,
+        idle_timeout: Duration,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let protocol = options.protocol.to_string().to_lowercase();
+        let mut child = python::spawn(
+            source,
+            PYTHON_SCRIPT,
+            &[
+                "server".to_string(),
+                protocol,
+                options.port.to_string(),
+                idle_timeout.as_secs().to_string(),
+            ],
+        )?;
+
+        // Forward both output streams, so the script's errors show up as lines too
+        let (sender, lines) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    let _ = sender.send(line);
+                }
+            });
+        }
+        if let Some(stderr) = child.stderr.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    let _ = sender.send(line);
+                }
+            });
+        }
+        Ok(ServerProcess {
+            child,
+            lines,
+            output: Vec::new(),
+        })
+    }
+
+    /// Wait until the server listens
+    fn wait_ready(&mut self, stop: &AtomicBool) -> Result<(), String> {
+        let deadline = Instant::now() + python::START_TIMEOUT;
+        while Instant::now() < deadline && !stop.load(Ordering::Relaxed) {
+            match self.lines.recv_timeout(Duration::from_millis(100)) {
+                Ok(line) if line == python::READY => return Ok(()),
+                Ok(line) => self.output.push(line),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let output = self.output.join("\n");
+        Err(if output.is_empty() {
+            "no response".to_string()
+        } else {
+            output
+        })
+    }
+
+    /// Collect the server's output, stopping it if it is still waiting for the client
+    fn finish(mut self) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            match self.lines.recv_timeout(remaining) {
+                Ok(line) => self.output.push(line),
+                Err(_) => break,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        self.output.join("\n")
+    }
+}
+
+/// Run the client in a WSL or container source
+fn run_python_client(
+    source: &ProbeSource,
+    host: &str,
+    options: &ThroughputOptions,
+) -> Result<(ClientReport, bool), Box<dyn std::error::Error>> {
+    let protocol = options.protocol.to_string().to_lowercase();
+    let child = python::spawn(
+        source,
+        PYTHON_SCRIPT,
+        &[
+            "client".to_string(),
+            protocol,
+            host.to_string(),
+            options.port.to_string(),
+            options.duration.as_secs_f64().to_string(),
+            "10".to_string(),
+            options.bitrate.to_string(),
+            options.datagram_size.to_string(),
+        ],
+    )?;
+    let output = child.wait_with_output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let report = stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<ClientReport>(line).ok())
+        .ok_or_else(|| {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            format!("Client in {source} failed: {}", stderr.trim())
+        })?;
+    Ok((report, false))
+}
+
+/// Warning about the server port being reserved on every address the native server binds
+fn excluded_port_warning(options: &ThroughputOptions) -> Option<String> {
+    crate::network::excluded_port_warning(
+        &options.protocol.to_string(),
+        Some(std::net::Ipv6Addr::UNSPECIFIED.into()),
+        options.port,
+    )
+}
+
+/// Bound server socket of a test served by this process
+enum NativeListener {
+    Tcp(TcpListener),
+    Udp(UdpSocket),
+}
+
+impl NativeListener {
+    /// Listen on every IPv4 and IPv6 address, or on every IPv4 address without IPv6
+    fn bind(options: &ThroughputOptions) -> Result<Self, Box<dyn std::error::Error>> {
+        let socket_type = match options.protocol {
+            SocketProtocol::Tcp => Type::STREAM,
+            SocketProtocol::Udp => Type::DGRAM,
+        };
+        let bind = |domain: Domain, address: SocketAddr| -> std::io::Result<Socket> {
+            let socket = Socket::new(domain, socket_type, None)?;
+            if domain == Domain::IPV6 {
+                socket.set_only_v6(false)?;
+            }
+            socket.bind(&address.into())?;
+            Ok(socket)
+        };
+        let socket = bind(
+            Domain::IPV6,
+            (std::net::Ipv6Addr::UNSPECIFIED, options.port).into(),
+        )
+        .or_else(|_| {
+            bind(
+                Domain::IPV4,
+                (std::net::Ipv4Addr::UNSPECIFIED, options.port).into(),
+            )
+        })
+        .map_err(|e| match excluded_port_warning(options) {
+            Some(warning) => format!("Could not listen on port {}: {e}. {warning}", options.port),
+            None => format!("Could not listen on port {}: {e}", options.port),
+        })?;
+        Ok(match options.protocol {
+            SocketProtocol::Tcp => {
+                socket.listen(1)?;
+                NativeListener::Tcp(socket.into())
+            }
+            SocketProtocol::Udp => NativeListener::Udp(socket.into()),
+        })
+    }
+
+    /// Receive one test and report it to the client
+    fn serve(self, timeout: Duration, stop: &AtomicBool) -> Result<ServerReport, String> {
+        match self {
+            NativeListener::Tcp(listener) => serve_tcp(listener, timeout, stop),
+            NativeListener::Udp(socket) => serve_udp(socket, timeout, stop),
+        }
+        .map_err(|e| e.to_string())
+    }
+}
+
+fn serve_tcp(
+    listener: TcpListener,
+    timeout: Duration,
+    stop: &AtomicBool,
+) -> std::io::Result<ServerReport> {
+    listener.set_nonblocking(true)?;
+    let deadline = Instant::now() + timeout;
+    let mut connection = loop {
+        match listener.accept() {
+            Ok((connection, _)) => break connection,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if stop.load(Ordering::Relaxed) || Instant::now() > deadline {
+                    return Err(std::io::Error::new(
+                        ErrorKind::TimedOut,
+                        "No client connected",
+                    ));
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(e) => return Err(e),
+        }
+    };
+    connection.set_nonblocking(false)?;
+    connection.set_read_timeout(Some(timeout))?;
+
+    let mut buffer = vec![0u8; TCP_BUFFER_SIZE];
+    let mut received = 0u64;
+    let mut start = None;
+    let mut end = None;
+    loop {
+        let read = connection.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        let now = Instant::now();
+        start.get_or_insert(now);
+        end = Some(now);
+        received += read as u64;
+    }
+    let report = ServerReport {
+        bytes: received,
+        seconds: match (start, end) {
+            (Some(start), Some(end)) => (end - start).as_secs_f64(),
+            _ => 0.0,
+        },
+        ..Default::default()
+    };
+    let line = serde_json::to_string(&report).unwrap_or_default();
+    connection.write_all(format!("{line}\n").as_bytes())?;
+    Ok(report)
+}
+
+fn serve_udp(
+    socket: UdpSocket,
+    timeout: Duration,
+    stop: &AtomicBool,
+) -> std::io::Result<ServerReport> {
+    let deadline = Instant::now() + timeout;
+    let mut buffer = vec![0u8; 65535];
+    let mut report = ServerReport::default();
+    let (mut received, mut out_of_order) = (0u64, 0u64);
+    let mut highest: Option<u64> = None;
+    let mut jitter = 0.0f64;
+    let mut previous_transit: Option<f64> = None;
+    let (mut start, mut end) = (None, None);
+    let mut sent = None;
+    let mut peer = None;
+
+    socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+    loop {
+        let (read, from) = match socket.recv_from(&mut buffer) {
+            Ok(datagram) => datagram,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                // Give up once the client went quiet for a few seconds, or never showed up
+                let idle_limit = match end {
+                    Some(end) => end + Duration::from_secs(3),
+                    None => deadline,
+                };
+                if stop.load(Ordering::Relaxed) || Instant::now() > idle_limit {
+                    break;
+                }
+                continue;
+            }
+            // Windows reports an earlier ICMP port unreachable on the next receive
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => continue,
+            Err(e) => return Err(e),
+        };
+        if read < UDP_HEADER_SIZE {
+            continue;
+        }
+        peer = Some(from);
+        let sequence = u64::from_be_bytes(buffer[..8].try_into().unwrap_or_default());
+        let value = u64::from_be_bytes(buffer[8..16].try_into().unwrap_or_default());
+        if sequence == END_OF_TEST {
+            sent = Some(value);
+            break;
+        }
+        // RFC 3550 interarrival jitter; the clock offset between the hosts cancels out
+        let transit = now_micros() as f64 - value as f64;
+        if let Some(previous) = previous_transit {
+            jitter += ((transit - previous).abs() - jitter) / 16.0;
+        }
+        previous_transit = Some(transit);
+        match highest {
+            Some(highest) if sequence < highest => out_of_order += 1,
+            _ => highest = Some(sequence),
+        }
+        received += 1;
+        report.bytes += read as u64;
+        let now = Instant::now();
+        start.get_or_insert(now);
+        end = Some(now);
+    }
+
+    let expected = sent.unwrap_or_else(|| highest.map_or(0, |highest| highest + 1));
+    report.seconds = match (start, end) {
+        (Some(start), Some(end)) => (end - start).as_secs_f64(),
+        _ => 0.0,
+    };
+    report.packets = Some(received);
+    report.lost = Some(expected.saturating_sub(received));
+    report.out_of_order = Some(out_of_order);
+    report.jitter_ms = Some(jitter / 1000.0);
+    if let (Some(_), Some(peer)) = (sent, peer) {
+        let line = serde_json::to_string(&report).unwrap_or_default();
+        for _ in 0..3 {
+            socket.send_to(line.as_bytes(), peer)?;
+        }
+    }
+    Ok(report)
+}
+
+/// Run the client in this process
+fn run_native_client(
+    host: &str,
+    options: &ThroughputOptions,
+    stop: &AtomicBool,
+) -> Result<(ClientReport, bool), Box<dyn std::error::Error>> {
+    let address = (host, options.port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("Could not resolve {host}"))?;
+    match options.protocol {
+        SocketProtocol::Tcp => {
+            let before = system_retransmits();
+            let mut report = tcp_client(address, options.duration, stop)?;
+            report.retransmits = before
+                .zip(system_retransmits())
+                .map(|(before, after)| after.saturating_sub(before));
+            Ok((report, true))
+        }
+        SocketProtocol::Udp => Ok((udp_client(address, options, stop)?, false)),
+    }
+}
+
+fn tcp_client(
+    address: SocketAddr,
+    duration: Duration,
+    stop: &AtomicBool,
+) -> Result<ClientReport, Box<dyn std::error::Error>> {
+    let mut connection = TcpStream::connect_timeout(&address, Duration::from_secs(10))
+        .map_err(|e| format!("Could not connect to {address}: {e}"))?;
+    let buffer = vec![0u8; TCP_BUFFER_SIZE];
+    let mut sent = 0u64;
+    let start = Instant::now();
+    while start.elapsed() < duration && !stop.load(Ordering::Relaxed) {
+        sent += connection.write(&buffer)? as u64;
+    }
+    let seconds = start.elapsed().as_secs_f64();
+    connection.shutdown(std::net::Shutdown::Write)?;
+    connection.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut line = String::new();
+    BufReader::new(connection).read_line(&mut line)?;
+    Ok(ClientReport {
+        bytes_sent: sent,
+        seconds,
+        server: serde_json::from_str(line.trim()).ok(),
+        ..Default::default()
+    })
+}
+
+fn udp_client(
+    address: SocketAddr,
+    options: &ThroughputOptions,
+    stop: &AtomicBool,
+) -> Result<ClientReport, Box<dyn std::error::Error>> {
+    let local: SocketAddr = if address.is_ipv4() {
+        (std::net::Ipv4Addr::UNSPECIFIED, 0).into()
+    } else {
+        (std::net::Ipv6Addr::UNSPECIFIED, 0).into()
+    };
+    // Not connected, as a server bound to every address may answer from another one
+    let socket = UdpSocket::bind(local)?;
+
+    let mut payload = vec![0u8; options.datagram_size.max(UDP_HEADER_SIZE)];
+    let interval = Duration::from_secs_f64(payload.len() as f64 * 8.0 / options.bitrate as f64);
+    let mut sent = 0u64;
+    let start = Instant::now();
+    let mut next_send = start;
+    while start.elapsed() < options.duration && !stop.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now < next_send {
+            std::thread::sleep((next_send - now).min(Duration::from_millis(10)));
+            continue;
+        }
+        payload[..8].copy_from_slice(&sent.to_be_bytes());
+        payload[8..16].copy_from_slice(&now_micros().to_be_bytes());
+        // A refused datagram only means the server is not there (yet)
+        if socket.send_to(&payload, address).is_ok() {
+            sent += 1;
+        }
+        next_send += interval;
+    }
+    let seconds = start.elapsed().as_secs_f64();
+
+    // Repeat the end of the test until the server reports, in case it gets lost
+    let mut end = [0u8; UDP_HEADER_SIZE];
+    end[..8].copy_from_slice(&END_OF_TEST.to_be_bytes());
+    end[8..].copy_from_slice(&sent.to_be_bytes());
+    socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+    let mut buffer = vec![0u8; 65535];
+    let mut server = None;
+    for _ in 0..50 {
+        let _ = socket.send_to(&end, address);
+        if let Ok((read, _)) = socket.recv_from(&mut buffer) {
+            server = serde_json::from_slice(&buffer[..read]).ok();
+            if server.is_some() {
+                break;
+            }
+        }
+    }
+    Ok(ClientReport {
+        bytes_sent: sent * payload.len() as u64,
+        seconds,
+        packets_sent: Some(sent),
+        server,
+        ..Default::default()
+    })
+}
+
+/// Microseconds since the Unix epoch, the send time carried by UDP datagrams
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Retransmitted TCP segments of the whole system
+///
+/// Windows does not report retransmits per connection without administrator rights, so the
+/// counter of `netstat -s -p tcp` is compared before and after the test instead.
+fn system_retransmits() -> Option<u64> {
+    if let Ok(snmp) = std::fs::read_to_string("/proc/net/snmp") {
+        // Format: a "Tcp:" line of names followed by a "Tcp:" line of values
+        let mut tcp = snmp.lines().filter(|line| line.starts_with("Tcp:"));
+        let names = tcp.next()?.split_whitespace();
+        let values = tcp.next()?.split_whitespace();
+        return names
+            .zip(values)
+            .find(|(name, _)| *name == "RetransSegs")
+            .and_then(|(_, value)| value.parse().ok());
+    }
+
+    let output = Command::new("netstat")
+        .args(["-s", "-p", "tcp"])
+        .output()
+        .ok()?;
+    // Format: "  Segments Retransmitted              = 1234"
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.trim_start().starts_with("Segments Retransmitted"))
+        .and_then(|line| line.split('=').nth(1))
+        .and_then(|value| value.trim().parse().ok())
+}
@@ -0,0 +1,815 @@
+//! TLS certificate chain inspection
+//!
+//! This module captures the certificate chain a server presents to a probe source and checks
+//! it against both the Windows root store and the CA bundle of WSL. Corporate TLS interception
+//! often installs its root only on Windows, so browsers work while tools inside WSL fail; the
+//! side by side verification shows exactly that.
+//!
+//! From Windows the chain is captured with .NET's `SslStream` through PowerShell; from WSL and
+//! containers with `openssl s_client`. Windows verification uses `X509Chain` (revocation is not
+//! checked), WSL verification uses `openssl verify` with the default distribution's CA bundle.
+
+use super::source::ProbeSource;
+use base64::Engine;
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::io::Write;
+use std::net::IpAddr;
+use std::process::{Command, Stdio};
+use x509_parser::extensions::GeneralName;
+use x509_parser::pem::Pem;
+
+/// A certificate of the presented chain
+#[derive(Debug, Clone)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// DNS names and IP addresses the certificate is valid for
+    pub subject_alt_names: Vec<String>,
+    pub not_before: String,
+    pub not_after: String,
+    /// Whether the current time is within the validity period
+    pub currently_valid: bool,
+    pub serial: String,
+    pub sha256: String,
+    pub sha1: String,
+    /// The DER encoding, used to verify the chain
+    pub der: Vec<u8>,
+}
+
+impl CertificateInfo {
+    /// Parse a DER encoded certificate
+    pub fn parse(der: &[u8]) -> Result<Self, Box<dyn std::error::Error>> {
+        let (_, certificate) = x509_parser::parse_x509_certificate(der)
+            .map_err(|e| format!("Invalid certificate: {e}"))?;
+        let subject_alt_names = certificate
+            .subject_alternative_name()
+            .ok()
+            .flatten()
+            .map(|extension| {
+                extension
+                    .value
+                    .general_names
+                    .iter()
+                    .filter_map(|name| match name {
+                        GeneralName::DNSName(name) => Some(name.to_string()),
+                        GeneralName::IPAddress(bytes) => {
+                            ip_from_bytes(bytes).map(|ip| ip.to_string())
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let validity = certificate.validity();
+        Ok(CertificateInfo {
+            subject: certificate.subject().to_string(),
+            issuer: certificate.issuer().to_string(),
+            subject_alt_names,
+            not_before: validity
+                .not_before
+                .to_rfc2822()
+                .unwrap_or_else(|_| validity.not_before.to_string()),
+            not_after: validity
+                .not_after
+                .to_rfc2822()
+                .unwrap_or_else(|_| validity.not_after.to_string()),
+            currently_valid: validity.is_valid(),
+            serial: certificate.raw_serial_as_string(),
+            sha256: fingerprint(&Sha256::digest(der)),
+            sha1: fingerprint(&Sha1::digest(der)),
+            der: der.to_vec(),
+        })
+    }
+
+    /// Whether the certificate is issued by itself, as roots are
+    pub fn is_self_issued(&self) -> bool {
+        self.subject == self.issuer
+    }
+
+    /// PEM encoding of the certificate
+    pub fn to_pem(&self) -> String {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&self.der);
+        let lines = encoded
+            .as_bytes()
+            .chunks(64)
+            .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+            .collect::<Vec<_>>();
+        format!(
+            "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+            lines.join("\n")
+        )
+    }
+
+    /// Whether the certificate covers `host`, matching wildcards in the leftmost label only
+    pub fn matches_host(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return self
+                .subject_alt_names
+                .iter()
+                .any(|name| name.parse::<IpAddr>().ok() == Some(ip));
+        }
+        let host = host.to_ascii_lowercase();
+        self.subject_alt_names.iter().any(|name| {
+            let name = name.to_ascii_lowercase();
+            match name.strip_prefix("*.") {
+                Some(suffix) => host
+                    .split_once('.')
+                    .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+                None => name == host,
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for CertificateInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Subject: {}{}",
+            self.subject,
+            if self.is_self_issued() {
+                " (self-issued)"
+            } else {
+                ""
+            }
+        )?;
+        writeln!(f, "Issuer: {}", self.issuer)?;
+        if !self.subject_alt_names.is_empty() {
+            writeln!(f, "SANs: {}", self.subject_alt_names.join(", "))?;
+        }
+        writeln!(
+            f,
+            "Valid: {} to {}{}",
+            self.not_before,
+            self.not_after,
+            if self.currently_valid {
+                ""
+            } else {
+                " (NOT VALID NOW)"
+            }
+        )?;
+        writeln!(f, "Serial: {}", self.serial)?;
+        writeln!(f, "SHA-256: {}", self.sha256)?;
+        write!(f, "SHA-1: {}", self.sha1)
+    }
+}
+
+/// The trusted roots a chain is verified against
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrustStore {
+    /// The Windows certificate store, as used by browsers on Windows
+    Windows,
+    /// The CA bundle of a WSL distribution, `None` for the default distribution
+    Wsl(Option<String>),
+    /// The CA bundle inside a container
+    Container(String),
+}
+
+impl std::fmt::Display for TrustStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrustStore::Windows => write!(f, "Windows root store"),
+            TrustStore::Wsl(None) => write!(f, "WSL CA bundle"),
+            TrustStore::Wsl(Some(distro)) => write!(f, "WSL CA bundle ({distro})"),
+            TrustStore::Container(name) => write!(f, "CA bundle of container {name}"),
+        }
+    }
+}
+
+/// Outcome of verifying a chain against a trust store
+#[derive(Debug, Clone)]
+pub struct ChainVerification {
+    pub store: TrustStore,
+    /// `None` when the verification could not be run
+    pub valid: Option<bool>,
+    /// Why the chain does not validate, as reported by the verifier
+    pub reasons: Vec<String>,
+}
+
+impl std::fmt::Display for ChainVerification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let outcome = match self.valid {
+            Some(true) => "trusted",
+            Some(false) => "NOT trusted",
+            None => "not checked",
+        };
+        write!(f, "{}: {outcome}", self.store)?;
+        for reason in &self.reasons {
+            write!(f, "\n  - {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The chain presented to a probe source and its verification
+#[derive(Debug, Clone)]
+pub struct TlsInspection {
+    pub host: String,
+    pub port: u16,
+    /// The presented chain, leaf first. Captured from Windows it is the chain Windows built,
+    /// which can include a root from the store.
+    pub certificates: Vec<CertificateInfo>,
+    /// Whether the leaf certificate covers the host name
+    pub hostname_matches: bool,
+    pub verifications: Vec<ChainVerification>,
+    pub raw_output: String,
+}
+
+impl std::fmt::Display for TlsInspection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}:{}", self.host, self.port)?;
+        writeln!(
+            f,
+            "Host name: {}",
+            if self.hostname_matches {
+                "matches the certificate"
+            } else {
+                "DOES NOT match the certificate"
+            }
+        )?;
+        for verification in &self.verifications {
+            writeln!(f, "{verification}")?;
+        }
+        for (depth, certificate) in self.certificates.iter().enumerate() {
+            write!(f, "\n[{depth}] {certificate}\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Inspect the certificate chain `host` presents to `source` on `port`
+///
+/// The chain is verified by the source itself, and against the Windows root store and the
+/// WSL CA bundle when the source is neither.
+///
+/// # Arguments
+///
+/// * `source` - The environment to connect from
+/// * `host` - The host name or IP address, also sent as the server name (SNI)
+/// * `port` - The TLS port
+/// * `source_address` - The local address to connect from (Windows only)
+///
+/// # Returns
+///
+/// A `Result` containing the inspection or an error if no chain could be captured
+pub fn inspect(
+    source: &ProbeSource,
+    host: &str,
+    port: u16,
+    source_address: Option<&str>,
+) -> Result<TlsInspection, Box<dyn std::error::Error>> {
+    let (certificates, own_verification, raw_output) = match source {
+        ProbeSource::Windows => capture_windows(host, port, source_address)?,
+        _ => capture_openssl(source, host, port)?,
+    };
+    let leaf = certificates
+        .first()
+        .ok_or_else(|| format!("{host}:{port} presented no certificate"))?;
+    let hostname_matches = leaf.matches_host(host);
+
+    let mut verifications = vec![own_verification];
+    if !matches!(source, ProbeSource::Windows) {
+        verifications.push(verify_windows(&certificates));
+    }
+    if !matches!(source, ProbeSource::Wsl { distro: None }) {
+        verifications.push(verify_wsl(&certificates));
+    }
+
+    Ok(TlsInspection {
+        host: host.to_string(),
+        port,
+        certificates,
+        hostname_matches,
+        verifications,
+        raw_output,
+    })
+}
+
+/// Output of the PowerShell capture script
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct WindowsCapture {
+    certificates: Vec<String>,
+    policy_errors: String,
+    #[serde(default)]
+    chain_status: Vec<String>,
+}
+
+/// Capture the chain with `SslStream` in PowerShell, verified by Windows during the handshake
+fn capture_windows(
+    host: &str,
+    port: u16,
+    source_address: Option<&str>,
+) -> Result<(Vec<CertificateInfo>, ChainVerification, String), Box<dyn std::error::Error>> {
+    let local_endpoint = match source_address {
+        Some(address) => {
+            let address = address
+                .parse::<IpAddr>()
+                .map_err(|e| format!("Invalid source address {address}: {e}"))?;
+            format!(
+                "(New-Object System.Net.IPEndPoint([System.Net.IPAddress]::Parse('{address}'), 0))"
+            )
+        }
+        None => String::new(),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let quoted_host = host.replace('\'', "''");
+    let script = format!(
+        "$ErrorActionPreference = 'Stop'; \
+         $client = New-Object System.Net.Sockets.TcpClient{local_endpoint}; \
+         $client.Connect('{quoted_host}', {port}); \
+         $script:capture = $null; \
+         $callback = [System.Net.Security.RemoteCertificateValidationCallback] {{ param($sender, $certificate, $chain, $errors) \
+           $script:capture = [pscustomobject]@{{ \
+             Certificates = @($chain.ChainElements | ForEach-Object {{ [Convert]::ToBase64String($_.Certificate.RawData) }}); \
+             PolicyErrors = $errors.ToString(); \
+             ChainStatus = @($chain.ChainStatus | ForEach-Object {{ $_.StatusInformation.Trim() }}) }}; \
+           $true }}; \
+         $stream = New-Object System.Net.Security.SslStream($client.GetStream(), $false, $callback); \
+         $stream.AuthenticateAsClient('{quoted_host}'); \
+         $stream.Dispose(); $client.Dispose(); \
+         $script:capture | ConvertTo-Json -Depth 3"
+    );
+    let output = Command::new("powershell.exe")
+        .args(["-NoProfile", "-Command", &script])
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if !output.status.success() || stdout.trim().is_empty() {
+        return Err(format!("TLS handshake with {host}:{port} failed: {}", stderr.trim()).into());
+    }
+
+    let capture: WindowsCapture = serde_json::from_str(&stdout)?;
+    let certificates = capture
+        .certificates
+        .iter()
+        .map(|encoded| {
+            let der = base64::engine::general_purpose::STANDARD.decode(encoded.trim())?;
+            CertificateInfo::parse(&der)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // SslPolicyErrors is a flags enum, e.g. "RemoteCertificateNameMismatch, RemoteCertificateChainErrors"
+    let mut reasons = capture
+        .policy_errors
+        .split(',')
+        .map(str::trim)
+        .filter(|error| {
+            !error.is_empty() && *error != "None" && *error != "RemoteCertificateChainErrors"
+        })
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>();
+    reasons.extend(capture.chain_status);
+    let verification = ChainVerification {
+        store: TrustStore::Windows,
+        valid: Some(capture.policy_errors == "None"),
+        reasons,
+    };
+
+    Ok((certificates, verification, format!("{stdout}\n{stderr}")))
+}
+
+/// Capture the chain with `openssl s_client`, verified by the source's CA bundle
+fn capture_openssl(
+    source: &ProbeSource,
+    host: &str,
+    port: u16,
+) -> Result<(Vec<CertificateInfo>, ChainVerification, String), Box<dyn std::error::Error>> {
+    let bare_host = host.trim_start_matches('[').trim_end_matches(']');
+    let connect = match bare_host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(address)) => format!("[{address}]:{port}"),
+        _ => format!("{bare_host}:{port}"),
+    };
+    let mut args = vec![
+        "s_client".to_string(),
+        "-connect".to_string(),
+        connect,
+        "-showcerts".to_string(),
+    ];
+    // SNI takes host names only
+    if bare_host.parse::<IpAddr>().is_err() {
+        args.extend(["-servername".to_string(), bare_host.to_string()]);
+    }
+    let output = source
+        .command("openssl", &args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| format!("Could not run openssl from {source}: {e}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+    let certificates = parse_pem_chain(&stdout)?;
+    if certificates.is_empty() {
+        return Err(format!(
+            "TLS handshake with {host}:{port} from {source} failed: {}",
+            stderr.trim()
+        )
+        .into());
+    }
+
+    let store = match source {
+        ProbeSource::Container { name, .. } => TrustStore::Container(name.clone()),
+        ProbeSource::Wsl { distro } => TrustStore::Wsl(distro.clone()),
+        ProbeSource::Windows => TrustStore::Windows,
+    };
+    let verification = parse_s_client_verification(store, &format!("{stdout}\n{stderr}"));
+    Ok((certificates, verification, format!("{stdout}\n{stderr}")))
+}
+
+/// Verify a chain against the Windows root store with `X509Chain`
+fn verify_windows(certificates: &[CertificateInfo]) -> ChainVerification {
+    let encoded = certificates
+        .iter()
+        .map(|certificate| {
+            format!(
+                "'{}'",
+                base64::engine::general_purpose::STANDARD.encode(&certificate.der)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    let script = format!(
+        "$certificates = @({encoded}) | ForEach-Object {{ New-Object System.Security.Cryptography.X509Certificates.X509Certificate2(,[Convert]::FromBase64String($_)) }}; \
+         $chain = New-Object System.Security.Cryptography.X509Certificates.X509Chain; \
+         $chain.ChainPolicy.RevocationMode = 'NoCheck'; \
+         $certificates | Select-Object -Skip 1 | ForEach-Object {{ [void]$chain.ChainPolicy.ExtraStore.Add($_) }}; \
+         $valid = $chain.Build($certificates[0]); \
+         [pscustomobject]@{{ Valid = $valid; ChainStatus = @($chain.ChainStatus | ForEach-Object {{ $_.StatusInformation.Trim() }}) }} | ConvertTo-Json -Depth 3"
+    );
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct WindowsVerification {
+        valid: bool,
+        #[serde(default)]
+        chain_status: Vec<String>,
+    }
+
+    let result = Command::new("powershell.exe")
+        .args(["-NoProfile", "-Command", &script])
+        .output()
+        .map_err(|e| e.to_string())
+        .and_then(|output| {
+            serde_json::from_slice::<WindowsVerification>(&output.stdout)
+                .map_err(|e| format!("{e}: {}", String::from_utf8_lossy(&output.stderr).trim()))
+        });
+    match result {
+        Ok(verification) => ChainVerification {
+            store: TrustStore::Windows,
+            valid: Some(verification.valid),
+            reasons: verification.chain_status,
+        },
+        Err(e) => ChainVerification {
+            store: TrustStore::Windows,
+            valid: None,
+            reasons: vec![format!("Could not run X509Chain: {e}")],
+        },
+    }
+}
+
+/// Verify a chain against the CA bundle of the default WSL distribution with `openssl verify`
+///
+/// The chain is written to a temporary file inside WSL, where the first certificate is the one
+/// verified and the rest are untrusted intermediates.
+fn verify_wsl(certificates: &[CertificateInfo]) -> ChainVerification {
+    let store = TrustStore::Wsl(None);
+    let pem = certificates
+        .iter()
+        .map(CertificateInfo::to_pem)
+        .collect::<String>();
+    let result = Command::new("wsl.exe")
+        .args([
+            "-e",
+            "sh",
+            "-c",
+            "f=$(mktemp); cat > \"$f\"; openssl verify -untrusted \"$f\" \"$f\" 2>&1; rm -f \"$f\"",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(pem.as_bytes())?;
+            }
+            child.wait_with_output()
+        });
+    match result {
+        Ok(output) => parse_openssl_verify(store, &String::from_utf8_lossy(&output.stdout)),
+        Err(e) => ChainVerification {
+            store,
+            valid: None,
+            reasons: vec![format!("Could not run openssl in WSL: {e}")],
+        },
+    }
+}
+
+/// Parse the certificates printed by `openssl s_client -showcerts`
+fn parse_pem_chain(output: &str) -> Result<Vec<CertificateInfo>, Box<dyn std::error::Error>> {
+    Pem::iter_from_buffer(output.as_bytes())
+        .map_while(Result::ok)
+        .filter(|pem| pem.label == "CERTIFICATE")
+        .map(|pem| CertificateInfo::parse(&pem.contents))
+        .collect()
+}
+
+/// Parse the verification result of `openssl s_client`
+///
+/// s_client prints a "verify error:num=20:unable to get local issuer certificate" line per
+/// problem and ends with "Verify return code: 0 (ok)".
+fn parse_s_client_verification(store: TrustStore, output: &str) -> ChainVerification {
+    let mut reasons = Vec::new();
+    let mut return_code = None;
+    for line in output.lines().map(str::trim) {
+        if let Some((_, error)) = line.split_once("verify error:") {
+            let reason = error
+                .split_once(':')
+                .map(|(_, reason)| reason)
+                .unwrap_or(error)
+                .to_string();
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        } else if let Some(code) = line.strip_prefix("Verify return code:") {
+            return_code = Some(code.trim().to_string());
+        }
+    }
+    let valid = return_code.as_ref().map(|code| code.starts_with("0 "));
+    // The errors are on standard error, the return code on standard output
+    if valid == Some(false) && reasons.is_empty() {
+        reasons.extend(return_code);
+    }
+    ChainVerification {
+        store,
+        valid,
+        reasons,
+    }
+}
+
+/// Parse the output of `openssl verify`
+///
+/// Success prints "<file>: OK"; failures print "error 20 at 0 depth lookup: unable to get
+/// local issuer certificate" lines.
+fn parse_openssl_verify(store: TrustStore, output: &str) -> ChainVerification {
+    let reasons = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("error ") && line.contains(" depth lookup"))
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    let valid = output.lines().any(|line| line.trim_end().ends_with(": OK"));
+    let reasons = if !valid && reasons.is_empty() {
+        vec![output.trim().to_string()]
+    } else {
+        reasons
+    };
+    ChainVerification {
+        store,
+        valid: Some(valid),
+        reasons,
+    }
+}
+
+/// Colon separated upper case hex, as certificate viewers show fingerprints
+fn fingerprint(digest: &[u8]) -> String {
+    digest
+        .iter()
+        .map(|byte| format!("{byte:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// IP address of an iPAddress subject alternative name
+fn ip_from_bytes(bytes: &[u8]) -> Option<IpAddr> {
+    match bytes.len() {
+        4 => <[u8; 4]>::try_from(bytes).ok().map(IpAddr::from),
+        16 => <[u8; 16]>::try_from(bytes).ok().map(IpAddr::from),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Standard output of `openssl s_client -showcerts` for a server that sends its root
+    const S_CLIENT_CHAIN_OUTPUT: &str = "\
+CONNECTED(00000003)
+---
+Certificate chain
+ 0 s:CN=localhost
+   i:CN=Test Root CA
+   a:PKEY: EC, (prime256v1); sigalg: ecdsa-with-SHA256
+   v:NotBefore: Oct 18 16:13:33 2026 GMT; NotAfter: Nov 17 16:13:33 2026 GMT
+-----BEGIN CERTIFICATE-----
+MIIBhTCCASugAwIBAgIUSGGLdM2mH9aoVeYYfPUfKeiVp5EwCgYIKoZIzj0EAwIw
+FzEVMBMGA1UEAwwMVGVzdCBSb290IENBMB4XDTI2MTAxODE2MTMzM1oXDTI2MTEx
+NzE2MTMzM1owFDESMBAGA1UEAwwJbG9jYWxob3N0MFkwEwYHKoZIzj0CAQYIKoZI
+zj0DAQcDQgAEV7ETU6GAHDdCDkIZdY1o/J4WzC6UR9g17eRInHtIPqBlMpxRPRTG
++p151bH7I8Lhg6+yBCDdOJbm2U7Db4liz6NYMFYwFAYDVR0RBA0wC4IJbG9jYWxo
+b3N0MB0GA1UdDgQWBBT1G0sXmBxsImKDa1a8Kq/AWTch3TAfBgNVHSMEGDAWgBS6
+1e6bFUy0LU73/4Zk+mF6lDm5SjAKBggqhkjOPQQDAgNIADBFAiEA0hYmRKqH0fmx
+C3IdwwkD97FnGXPn6DzJKpaqVgCkuPwCIHd9t4NERPCk7Adw89erqRXq210Por9V
+Rxn0ghk86DPT
+-----END CERTIFICATE-----
+ 1 s:CN=Test Root CA
+   i:CN=Test Root CA
+   a:PKEY: EC, (prime256v1); sigalg: ecdsa-with-SHA256
+   v:NotBefore: Oct 18 16:13:33 2026 GMT; NotAfter: Oct 15 16:13:33 2036 GMT
+-----BEGIN CERTIFICATE-----
+MIIBhDCCASmgAwIBAgIUV+OueK3lZp26/Cn7YIWNQ2VYBqEwCgYIKoZIzj0EAwIw
+FzEVMBMGA1UEAwwMVGVzdCBSb290IENBMB4XDTI2MTAxODE2MTMzM1oXDTM2MTAx
+NTE2MTMzM1owFzEVMBMGA1UEAwwMVGVzdCBSb290IENBMFkwEwYHKoZIzj0CAQYI
+KoZIzj0DAQcDQgAE1qZSV+zYvRqdetMRcj8K4KnGYqT8Wt4uEzF4olt2MuWUwRBy
+xA3yKBOFff2HX1ZGgEZzXjULqEzc5AU0LfNt0qNTMFEwHQYDVR0OBBYEFLrV7psV
+TLQtTvf/hmT6YXqUOblKMB8GA1UdIwQYMBaAFLrV7psVTLQtTvf/hmT6YXqUOblK
+MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSQAwRgIhAMjqP0hQkLPTIV8e
+pfm/ovT0FgGR1eoWwxiZSvRjNV8GAiEA7QIXdP+mfZtSNKazJCnK+EroHjajiFmh
+Yr93y8szn7A=
+-----END CERTIFICATE-----
+---
+Server certificate
+subject=CN=localhost
+issuer=CN=Test Root CA
+---
+No client certificate CA names sent
+Peer signing digest: SHA256
+Peer signature type: ecdsa_secp256r1_sha256
+Negotiated TLS1.3 group: X25519MLKEM768
+---
+SSL handshake has read 2249 bytes and written 1604 bytes
+Verification error: self-signed certificate in certificate chain
+---
+New, TLSv1.3, Cipher is TLS_AES_256_GCM_SHA384
+Protocol: TLSv1.3
+Server public key is 256 bit
+This TLS version forbids renegotiation.
+Compression: NONE
+Expansion: NONE
+No ALPN negotiated
+Early data was not sent
+Verify return code: 19 (self-signed certificate in certificate chain)
+---
+";
+
+    /// Standard error of the same run
+    const S_CLIENT_CHAIN_ERRORS: &str = "\
+Connecting to 127.0.0.1
+Can't use SSL_get_servername
+depth=1 CN=Test Root CA
+verify error:num=19:self-signed certificate in certificate chain
+verify return:1
+depth=1 CN=Test Root CA
+verify return:1
+depth=0 CN=localhost
+verify return:1
+DONE
+";
+
+    fn certificate_for(names: &[&str]) -> CertificateInfo {
+        CertificateInfo {
+            subject: "CN=test".to_string(),
+            issuer: "CN=test".to_string(),
+            subject_alt_names: names.iter().map(|name| name.to_string()).collect(),
+            not_before: String::new(),
+            not_after: String::new(),
+            currently_valid: true,
+            serial: String::new(),
+            sha256: String::new(),
+            sha1: String::new(),
+            der: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn matches_exact_names_ignoring_case() {
+        let certificate = certificate_for(&["example.com", "www.example.com"]);
+        assert!(certificate.matches_host("example.com"));
+        assert!(certificate.matches_host("WWW.Example.COM"));
+        assert!(!certificate.matches_host("api.example.com"));
+        assert!(!certificate.matches_host("example.org"));
+    }
+
+    #[test]
+    fn matches_wildcards_in_the_leftmost_label_only() {
+        let certificate = certificate_for(&["*.example.com"]);
+        assert!(certificate.matches_host("a.example.com"));
+        assert!(certificate.matches_host("A.EXAMPLE.com"));
+        assert!(!certificate.matches_host("a.b.example.com"));
+        assert!(!certificate.matches_host("example.com"));
+        assert!(!certificate.matches_host(".example.com"));
+        assert!(!certificate.matches_host("a.example.org"));
+    }
+
+    #[test]
+    fn matches_ip_addresses_by_value() {
+        let certificate = certificate_for(&["10.0.0.1", "::1", "*.example.com"]);
+        assert!(certificate.matches_host("10.0.0.1"));
+        assert!(certificate.matches_host("[::1]"));
+        assert!(certificate.matches_host("0:0:0:0:0:0:0:1"));
+        assert!(!certificate.matches_host("10.0.0.2"));
+        assert!(!certificate_for(&["example.com"]).matches_host("10.0.0.1"));
+    }
+
+    #[test]
+    fn parses_the_chain_printed_by_s_client() {
+        let chain = parse_pem_chain(S_CLIENT_CHAIN_OUTPUT).unwrap();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0].subject, "CN=localhost");
+        assert_eq!(chain[0].issuer, "CN=Test Root CA");
+        assert_eq!(chain[0].subject_alt_names, ["localhost"]);
+        assert!(chain[0].matches_host("localhost"));
+        assert_eq!(chain[1].subject, "CN=Test Root CA");
+        assert!(chain[1].is_self_issued());
+        assert!(parse_pem_chain("CONNECTED(00000003)\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_s_client_success() {
+        let output = "depth=1 CN=Test Root CA\nverify return:1\ndepth=0 CN=localhost\n\
+                      verify return:1\n---\nVerify return code: 0 (ok)\n---\n";
+        let verification = parse_s_client_verification(TrustStore::Wsl(None), output);
+        assert_eq!(verification.valid, Some(true));
+        assert!(verification.reasons.is_empty());
+    }
+
+    #[test]
+    fn parses_s_client_self_signed_in_chain() {
+        let verification = parse_s_client_verification(
+            TrustStore::Wsl(None),
+            &format!("{S_CLIENT_CHAIN_OUTPUT}\n{S_CLIENT_CHAIN_ERRORS}"),
+        );
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(
+            verification.reasons,
+            ["self-signed certificate in certificate chain"]
+        );
+    }
+
+    #[test]
+    fn parses_s_client_expired_certificate() {
+        let output = "Connecting to 127.0.0.1\ndepth=1 CN=Test Root CA\nverify return:1\n\
+                      depth=0 CN=localhost\nverify error:num=10:certificate has expired\n\
+                      notAfter=Jan  1 00:00:00 2021 GMT\nverify return:1\n\
+                      Verify return code: 10 (certificate has expired)\n";
+        let verification = parse_s_client_verification(TrustStore::Container("web".into()), output);
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(verification.reasons, ["certificate has expired"]);
+    }
+
+    #[test]
+    fn falls_back_to_the_s_client_return_code() {
+        let output = "Verify return code: 20 (unable to get local issuer certificate)\n";
+        let verification = parse_s_client_verification(TrustStore::Wsl(None), output);
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(
+            verification.reasons,
+            ["20 (unable to get local issuer certificate)"]
+        );
+        let verification = parse_s_client_verification(TrustStore::Wsl(None), "connect: refused");
+        assert_eq!(verification.valid, None);
+    }
+
+    #[test]
+    fn parses_openssl_verify_success() {
+        let verification = parse_openssl_verify(TrustStore::Wsl(None), "/tmp/tmp.Xy3: OK\n");
+        assert_eq!(verification.valid, Some(true));
+        assert!(verification.reasons.is_empty());
+    }
+
+    #[test]
+    fn parses_openssl_verify_self_signed_in_chain() {
+        let output = "CN=Test Root CA\n\
+                      error 19 at 1 depth lookup: self-signed certificate in certificate chain\n\
+                      error /tmp/tmp.Xy3: verification failed\n";
+        let verification = parse_openssl_verify(TrustStore::Wsl(None), output);
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(
+            verification.reasons,
+            ["error 19 at 1 depth lookup: self-signed certificate in certificate chain"]
+        );
+    }
+
+    #[test]
+    fn parses_openssl_verify_expired_certificate() {
+        let output = "CN=localhost\n\
+                      error 10 at 0 depth lookup: certificate has expired\n\
+                      error /tmp/tmp.Xy3: verification failed\n";
+        let verification = parse_openssl_verify(TrustStore::Wsl(None), output);
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(
+            verification.reasons,
+            ["error 10 at 0 depth lookup: certificate has expired"]
+        );
+    }
+
+    #[test]
+    fn keeps_unrecognized_openssl_verify_output() {
+        let verification = parse_openssl_verify(TrustStore::Wsl(None), "sh: openssl: not found\n");
+        assert_eq!(verification.valid, Some(false));
+        assert_eq!(verification.reasons, ["sh: openssl: not found"]);
+    }
+}
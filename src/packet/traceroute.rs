@@ -0,0 +1,812 @@
+//! Traceroute implementation
+//!
+//! This module traces the path to a destination hop by hop. Traces from Windows use the
+//! in-process engine, which sends ICMP echo, UDP or TCP probes with increasing TTLs and matches
+//! the ICMP errors on a raw socket; without administrator rights ICMP traces fall back to
+//! `tracert`. WSL and container sources run Linux `traceroute` and parse its output.
+
+use super::icmp::{self, IcmpError, IcmpPacket, IcmpSocket, IPPROTO_TCP, IPPROTO_UDP};
+use super::source::ProbeSource;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// First destination port of UDP probes, as used by traceroute
+const UDP_BASE_PORT: u16 = 33434;
+
+/// Payload size of ICMP and UDP probes
+const PROBE_PAYLOAD_SIZE: usize = 32;
+
+/// How often a pending TCP probe connect is checked, which bounds the error of its round trip
+const CONNECT_POLL_INTERVAL: Duration = Duration::from_millis(2);
+
+/// The kinds of probes a trace can send
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TracerouteProtocol {
+    Icmp,
+    Udp,
+    Tcp,
+}
+
+impl std::fmt::Display for TracerouteProtocol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TracerouteProtocol::Icmp => write!(f, "ICMP"),
+            TracerouteProtocol::Udp => write!(f, "UDP"),
+            TracerouteProtocol::Tcp => write!(f, "TCP"),
+        }
+    }
+}
+
+impl std::str::FromStr for TracerouteProtocol {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ICMP" => Ok(TracerouteProtocol::Icmp),
+            "UDP" => Ok(TracerouteProtocol::Udp),
+            "TCP" => Ok(TracerouteProtocol::Tcp),
+            _ => Err(format!("Unknown traceroute protocol: {s}")),
+        }
+    }
+}
+
+/// Options of a trace
+#[derive(Debug, Clone)]
+pub struct TracerouteOptions {
+    pub protocol: TracerouteProtocol,
+    pub max_hops: u32,
+    /// Probes sent per hop
+    pub queries: u32,
+    /// How long to wait for each probe
+    pub timeout: Duration,
+    /// Destination port of UDP and TCP probes, `None` for 33434 (UDP) or 80 (TCP)
+    pub port: Option<u16>,
+}
+
+impl Default for TracerouteOptions {
+    fn default() -> Self {
+        TracerouteOptions {
+            protocol: TracerouteProtocol::Icmp,
+            max_hops: 30,
+            queries: 3,
+            timeout: Duration::from_secs(3),
+            port: None,
+        }
+    }
+}
+
+/// Represents one hop of a trace
+#[derive(Debug, Clone, Default)]
+pub struct TracerouteHop {
+    pub ttl: u32,
+    /// Address that answered, empty when no probe was answered
+    pub address: String,
+    /// Reverse name of the address, empty when it has none
+    pub hostname: String,
+    /// Round trip time of every probe in milliseconds, `None` for unanswered probes
+    pub rtts: Vec<Option<f32>>,
+    /// Errors other than an expired TTL, e.g. "Host unreachable"
+    pub note: String,
+}
+
+impl TracerouteHop {
+    /// Average round trip time of the answered probes
+    pub fn average_rtt(&self) -> Option<f32> {
+        let answered: Vec<f32> = self.rtts.iter().flatten().copied().collect();
+        if answered.is_empty() {
+            None
+        } else {
+            Some(answered.iter().sum::<f32>() / answered.len() as f32)
+        }
+    }
+}
+
+impl std::fmt::Display for TracerouteHop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:>2} ", self.ttl)?;
+        for rtt in &self.rtts {
+            match rtt {
+                Some(rtt) => write!(f, " {rtt:>7.2} ms")?,
+                None => write!(f, " {:>10}", "*")?,
+            }
+        }
+        match (self.address.is_empty(), self.hostname.is_empty()) {
+            (true, _) => write!(f, "  Request timed out.")?,
+            (false, true) => write!(f, "  {}", self.address)?,
+            (false, false) => write!(f, "  {} [{}]", self.hostname, self.address)?,
+        }
+        if !self.note.is_empty() {
+            write!(f, " ({})", self.note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Represents the result of a trace
+#[derive(Debug, Clone)]
+pub struct TracerouteResult {
+    pub destination: String,
+    /// Address the destination resolved to
+    pub address: String,
+    pub protocol: TracerouteProtocol,
+    pub hops: Vec<TracerouteHop>,
+    /// Whether the destination itself answered
+    pub reached: bool,
+    pub raw_output: String,
+}
+
+/// Trace the path to the specified destination, streaming the hops
+///
+/// # Arguments
+///
+/// * `source` - The environment to trace from
+/// * `destination` - The IP address or hostname to trace
+/// * `source_address` - The local address to send from, binding the trace to its interface
+/// * `options` - Protocol, maximum hops, probes per hop, timeout and port
+/// * `stop` - Set to end the trace early
+/// * `on_hop` - Called for every hop once all its probes are answered or timed out
+///
+/// # Returns
+///
+/// A `Result` containing the trace result or an error
+pub fn trace(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &TracerouteOptions,
+    stop: &AtomicBool,
+    mut on_hop: impl FnMut(&TracerouteHop),
+) -> Result<TracerouteResult, Box<dyn std::error::Error>> {
+    if source.is_linux() {
+        return trace_command(
+            source,
+            destination,
+            source_address,
+            options,
+            stop,
+            &mut on_hop,
+        );
+    }
+
+    let address = icmp::resolve(destination)?;
+    match IcmpSocket::open_raw(address, source_address) {
+        Ok(socket) => trace_native(
+            &socket,
+            destination,
+            address,
+            source_address,
+            options,
+            stop,
+            &mut on_hop,
+        ),
+        Err(e) if options.protocol == TracerouteProtocol::Icmp => {
+            println!("Native traceroute unavailable ({e}), falling back to tracert");
+            trace_command(
+                source,
+                destination,
+                source_address,
+                options,
+                stop,
+                &mut on_hop,
+            )
+        }
+        Err(e) => Err(format!(
+            "{} traceroute from Windows needs raw sockets: {e}. Run WSLNetMan as \
+             administrator or trace from WSL.",
+            options.protocol
+        )
+        .into()),
+    }
+}
+
+/// Trace with the in-process engine
+fn trace_native(
+    socket: &IcmpSocket,
+    destination: &str,
+    address: IpAddr,
+    source_address: Option<&str>,
+    options: &TracerouteOptions,
+    stop: &AtomicBool,
+    on_hop: &mut impl FnMut(&TracerouteHop),
+) -> Result<TracerouteResult, Box<dyn std::error::Error>> {
+    let mut raw_output = format!(
+        "Tracing route to {destination} [{address}] over a maximum of {} hops ({}):\n",
+        options.max_hops, options.protocol
+    );
+    let mut hops = Vec::new();
+    let mut reached = false;
+    let mut probe: u16 = 0;
+
+    for ttl in 1..=options.max_hops {
+        let mut hop = TracerouteHop {
+            ttl,
+            ..Default::default()
+        };
+
+        for _ in 0..options.queries {
+            if stop.load(Ordering::Relaxed) {
+                break;
+            }
+            probe = probe.wrapping_add(1);
+
+            let outcome = match options.protocol {
+                TracerouteProtocol::Icmp => probe_icmp(socket, ttl, probe, options.timeout),
+                TracerouteProtocol::Udp => {
+                    let port = options.port.unwrap_or(UDP_BASE_PORT).wrapping_add(probe);
+                    probe_udp(socket, address, source_address, ttl, port, options.timeout)
+                }
+                TracerouteProtocol::Tcp => probe_tcp(
+                    socket,
+                    address,
+                    source_address,
+                    ttl,
+                    options.port.unwrap_or(80),
+                    options.timeout,
+                ),
+            }?;
+
+            hop.rtts.push(outcome.as_ref().map(|outcome| outcome.rtt));
+            if let Some(outcome) = outcome {
+                if hop.address.is_empty() {
+                    hop.address = outcome.from.to_string();
+                }
+                if let Some(note) = outcome.note {
+                    hop.note = note;
+                }
+                reached |= outcome.reached;
+            }
+        }
+
+        if let Ok(address) = hop.address.parse::<IpAddr>() {
+            hop.hostname = dns_lookup::lookup_addr(&address)
+                .ok()
+                .filter(|name| *name != hop.address)
+                .unwrap_or_default();
+        }
+        raw_output.push_str(&format!("{hop}\n"));
+        on_hop(&hop);
+
+        let unreachable = !hop.note.is_empty();
+        hops.push(hop);
+        if reached || unreachable || stop.load(Ordering::Relaxed) {
+            break;
+        }
+    }
+
+    raw_output.push_str(if reached {
+        "\nTrace complete.\n"
+    } else {
+        "\nDestination not reached.\n"
+    });
+    Ok(TracerouteResult {
+        destination: destination.to_string(),
+        address: address.to_string(),
+        protocol: options.protocol,
+        hops,
+        reached,
+        raw_output,
+    })
+}
+
+/// The answer to a single probe
+struct ProbeOutcome {
+    from: IpAddr,
+    rtt: f32,
+    /// Whether the destination itself answered
+    reached: bool,
+    /// Errors that end the trace, e.g. "Host unreachable"
+    note: Option<String>,
+}
+
+impl ProbeOutcome {
+    /// Interpret an ICMP error or echo reply matched to a probe
+    fn from_response(response: icmp::IcmpResponse, sent_at: Instant) -> Self {
+        let rtt = response.received_at.duration_since(sent_at).as_secs_f32() * 1000.0;
+        let (reached, note) = match response.error {
+            None | Some(IcmpError::PortUnreachable) => (true, None),
+            Some(IcmpError::TimeExceeded) => (false, None),
+            Some(error) => (false, Some(error.to_string())),
+        };
+        ProbeOutcome {
+            from: response.from,
+            rtt,
+            reached,
+            note,
+        }
+    }
+}
+
+/// Send an ICMP echo request with `ttl` and wait for the reply or error
+fn probe_icmp(
+    socket: &IcmpSocket,
+    ttl: u32,
+    sequence: u16,
+    timeout: Duration,
+) -> Result<Option<ProbeOutcome>, Box<dyn std::error::Error>> {
+    socket.set_ttl(ttl)?;
+    let sent_at = Instant::now();
+    socket.send_request(sequence, PROBE_PAYLOAD_SIZE)?;
+
+    let response = socket.wait_for(sent_at + timeout, |packet| {
+        let echo = match packet {
+            IcmpPacket::EchoReply(echo) => echo,
+            IcmpPacket::Quoted { header, .. } => header,
+        };
+        echo.len() >= 8 && u16::from_be_bytes([echo[6], echo[7]]) == sequence
+    });
+    Ok(response.map(|response| ProbeOutcome::from_response(response, sent_at)))
+}
+
+/// Create a UDP or TCP socket with `ttl`, bound to the source address
+fn transport_socket(
+    address: IpAddr,
+    source_address: Option<&str>,
+    ttl: u32,
+    tcp: bool,
+) -> Result<Socket, Box<dyn std::error::Error>> {
+    let domain = match address {
+        IpAddr::V4(_) => Domain::IPV4,
+        IpAddr::V6(_) => Domain::IPV6,
+    };
+    let socket = if tcp {
+        Socket::new(domain, Type::STREAM, Some(Protocol::TCP))?
+    } else {
+        Socket::new(domain, Type::DGRAM, Some(Protocol::UDP))?
+    };
+    match address {
+        IpAddr::V4(_) => socket.set_ttl(ttl)?,
+        IpAddr::V6(_) => socket.set_unicast_hops_v6(ttl)?,
+    }
+
+    // Bind explicitly so the local port is known before the probe is sent
+    let source = match source_address {
+        Some(source_address) => source_address.parse()?,
+        None => icmp::unspecified_address(address),
+    };
+    socket.bind(&SocketAddr::new(source, 0).into())?;
+    Ok(socket)
+}
+
+/// Send a UDP datagram with `ttl` to `port` and wait for the ICMP error it causes
+///
+/// Every probe uses its own port, so errors are matched by the quoted destination port. The
+/// destination answers with "port unreachable".
+fn probe_udp(
+    socket: &IcmpSocket,
+    address: IpAddr,
+    source_address: Option<&str>,
+    ttl: u32,
+    port: u16,
+    timeout: Duration,
+) -> Result<Option<ProbeOutcome>, Box<dyn std::error::Error>> {
+    let udp = transport_socket(address, source_address, ttl, false)?;
+    let sent_at = Instant::now();
+    udp.send_to(
+        &[0u8; PROBE_PAYLOAD_SIZE],
+        &SocketAddr::new(address, port).into(),
+    )?;
+
+    let response = socket.wait_for(sent_at + timeout, |packet| match packet {
+        IcmpPacket::Quoted { protocol, header } => {
+            *protocol == IPPROTO_UDP
+                && header.len() >= 4
+                && u16::from_be_bytes([header[2], header[3]]) == port
+        }
+        IcmpPacket::EchoReply(_) => false,
+    });
+    Ok(response.map(|response| ProbeOutcome::from_response(response, sent_at)))
+}
+
+/// Open a TCP connection with `ttl` to `port` and wait for the ICMP error or the connection
+///
+/// Errors are matched by the quoted source port. The destination answers by accepting or
+/// refusing the connection, which works through firewalls that drop UDP and ICMP.
+fn probe_tcp(
+    socket: &IcmpSocket,
+    address: IpAddr,
+    source_address: Option<&str>,
+    ttl: u32,
+    port: u16,
+    timeout: Duration,
+) -> Result<Option<ProbeOutcome>, Box<dyn std::error::Error>> {
+    let tcp = transport_socket(address, source_address, ttl, true)?;
+    let local_port = tcp
+        .local_addr()?
+        .as_socket()
+        .map(|local| local.port())
+        .ok_or("TCP probe socket has no local port")?;
+    let destination = SocketAddr::new(address, port).into();
+    let reached = |sent_at: Instant| {
+        Some(ProbeOutcome {
+            from: address,
+            rtt: sent_at.elapsed().as_secs_f32() * 1000.0,
+            reached: true,
+            note: None,
+        })
+    };
+
+    // The connect does not block, so the ICMP errors are watched for while it is pending
+    tcp.set_nonblocking(true)?;
+    let sent_at = Instant::now();
+    let deadline = sent_at + timeout;
+    match tcp.connect(&destination) {
+        // An accepted or refused connection means the destination answered
+        Ok(()) => return Ok(reached(sent_at)),
+        Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => return Ok(reached(sent_at)),
+        Err(e) if connect_pending(&e) => {}
+        Err(_) => return Ok(None),
+    }
+
+    while Instant::now() < deadline {
+        match tcp.take_error()? {
+            Some(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                return Ok(reached(sent_at))
+            }
+            Some(_) => return Ok(None),
+            // The peer address is only known once the connection is established
+            None if tcp.peer_addr().is_ok() => return Ok(reached(sent_at)),
+            None => {}
+        }
+
+        let poll = (Instant::now() + CONNECT_POLL_INTERVAL).min(deadline);
+        let response = socket.wait_for(poll, |packet| match packet {
+            IcmpPacket::Quoted { protocol, header } => {
+                *protocol == IPPROTO_TCP
+                    && header.len() >= 2
+                    && u16::from_be_bytes([header[0], header[1]]) == local_port
+            }
+            IcmpPacket::EchoReply(_) => false,
+        });
+        if let Some(response) = response {
+            return Ok(Some(ProbeOutcome::from_response(response, sent_at)));
+        }
+    }
+    Ok(None)
+}
+
+/// Whether a non-blocking connect is still in progress
+///
+/// Windows reports `WSAEWOULDBLOCK`, Linux `EINPROGRESS`, which has no `ErrorKind` before
+/// Rust 1.83.
+fn connect_pending(error: &std::io::Error) -> bool {
+    const EINPROGRESS: i32 = 115;
+    error.kind() == std::io::ErrorKind::WouldBlock
+        || (cfg!(target_os = "linux") && error.raw_os_error() == Some(EINPROGRESS))
+}
+
+/// Trace with `tracert` on Windows or `traceroute` in WSL and containers
+fn trace_command(
+    source: &ProbeSource,
+    destination: &str,
+    source_address: Option<&str>,
+    options: &TracerouteOptions,
+    stop: &AtomicBool,
+    on_hop: &mut impl FnMut(&TracerouteHop),
+) -> Result<TracerouteResult, Box<dyn std::error::Error>> {
+    let linux = source.is_linux();
+    let mut args = Vec::new();
+    let program = if linux {
+        match options.protocol {
+            TracerouteProtocol::Icmp => args.push("-I".to_string()),
+            TracerouteProtocol::Tcp => args.push("-T".to_string()),
+            TracerouteProtocol::Udp => {}
+        }
+        if let Some(port) = options.port {
+            args.extend(["-p".to_string(), port.to_string()]);
+        }
+        if let Some(source_address) = source_address {
+            args.extend(["-s".to_string(), source_address.to_string()]);
+        }
+        args.extend([
+            "-m".to_string(),
+            options.max_hops.to_string(),
+            "-q".to_string(),
+            options.queries.to_string(),
+            "-w".to_string(),
+            options.timeout.as_secs().max(1).to_string(),
+        ]);
+        "traceroute"
+    } else {
+        // tracert only sends ICMP, always three probes per hop, and can only bind IPv6 sources
+        if options.protocol != TracerouteProtocol::Icmp {
+            return Err(format!("tracert cannot send {} probes", options.protocol).into());
+        }
+        if let Some(source_address) = source_address.filter(|address| address.contains(':')) {
+            args.extend(["-S".to_string(), source_address.to_string()]);
+        }
+        args.extend([
+            "-h".to_string(),
+            options.max_hops.to_string(),
+            "-w".to_string(),
+            options.timeout.as_millis().to_string(),
+        ]);
+        "tracert"
+    };
+    args.push(destination.to_string());
+
+    let mut hops = Vec::new();
+    let mut address = String::new();
+    let (stdout, stderr) = source.stream(program, &args, stop, |line| {
+        if address.is_empty() {
+            if let Some(header) = parse_trace_header(line) {
+                address = header;
+                return;
+            }
+        }
+        let hop = if linux {
+            parse_traceroute_line(line)
+        } else {
+            parse_tracert_line(line)
+        };
+        if let Some(hop) = hop {
+            on_hop(&hop);
+            hops.push(hop);
+        }
+    })?;
+
+    if hops.is_empty() {
+        let error = stderr.trim();
+        if linux && (error.contains("not found") || error.contains("No such file")) {
+            return Err(format!(
+                "traceroute is not installed in {source}; install it with your package \
+                 manager, e.g. `sudo apt install traceroute`"
+            )
+            .into());
+        }
+        return Err(format!(
+            "{program} from {source} failed: {}",
+            (stdout + &stderr).trim()
+        )
+        .into());
+    }
+
+    let reached = hops.iter().any(|hop| hop.address == address);
+    Ok(TracerouteResult {
+        destination: destination.to_string(),
+        address,
+        protocol: options.protocol,
+        hops,
+        reached,
+        raw_output: stdout + &stderr,
+    })
+}
+
+/// Parse the destination address from the first line of `tracert` or `traceroute`
+///
+/// Format:
+/// ```text
+/// Tracing route to dns.google [8.8.8.8]
+/// traceroute to 8.8.8.8 (8.8.8.8), 30 hops max, 60 byte packets
+/// ```
+fn parse_trace_header(line: &str) -> Option<String> {
+    let line = line.trim();
+    if let Some(rest) = line.strip_prefix("Tracing route to ") {
+        // tracert leaves out the brackets for addresses
+        return Some(match rest.split_once('[') {
+            Some((_, address)) => address.split(']').next()?.to_string(),
+            None => rest.split_whitespace().next()?.to_string(),
+        });
+    }
+    let rest = line.strip_prefix("traceroute to ")?;
+    let (_, address) = rest.split_once('(')?;
+    Some(address.split(')').next()?.to_string())
+}
+
+/// Parse a hop line of Linux `traceroute` (including BusyBox)
+///
+/// Format:
+/// ```text
+///  1  172.20.0.1 (172.20.0.1)  0.345 ms  0.301 ms  0.289 ms
+///  3  * * *
+///  4  10.0.0.1 (10.0.0.1)  5.1 ms !H  *  5.2 ms
+///  5  a.example (1.2.3.4)  10.1 ms b.example (1.2.3.5)  11.2 ms  10.9 ms
+/// ```
+/// When probes of one hop are answered by different routers, the first one is reported.
+fn parse_traceroute_line(line: &str) -> Option<TracerouteHop> {
+    let mut tokens = line.split_whitespace().peekable();
+    let ttl = tokens.next()?.parse().ok()?;
+    let mut hop = TracerouteHop {
+        ttl,
+        ..Default::default()
+    };
+
+    while let Some(token) = tokens.next() {
+        if token == "*" {
+            hop.rtts.push(None);
+        } else if tokens.peek() == Some(&"ms") {
+            tokens.next();
+            hop.rtts.push(token.parse().ok());
+        } else if let Some(annotation) = token.strip_prefix('!') {
+            hop.note = match annotation {
+                "H" => "Host unreachable",
+                "N" => "Network unreachable",
+                "P" => "Protocol unreachable",
+                "X" => "Communication administratively prohibited",
+                _ => token,
+            }
+            .to_string();
+        } else if hop.address.is_empty() {
+            // Either "name (address)" or a bare address when names are not resolved
+            match tokens.peek().and_then(|next| next.strip_prefix('(')) {
+                Some(address) => {
+                    hop.address = address.trim_end_matches(')').to_string();
+                    if token != hop.address {
+                        hop.hostname = token.to_string();
+                    }
+                    tokens.next();
+                }
+                None => hop.address = token.to_string(),
+            }
+        }
+        // Names and addresses of other routers answering this hop are skipped
+    }
+
+    Some(hop)
+}
+
+/// Parse a hop line of Windows `tracert`
+///
+/// Format:
+/// ```text
+///   1    <1 ms    <1 ms    <1 ms  172.20.0.1
+///   2     1 ms     1 ms     1 ms  router.home [192.168.1.1]
+///   3     *        *        *     Request timed out.
+///   4    10 ms     *       12 ms  10.0.0.1  reports: Destination host unreachable.
+/// ```
+fn parse_tracert_line(line: &str) -> Option<TracerouteHop> {
+    let mut tokens = line.split_whitespace().peekable();
+    let ttl = tokens.next()?.parse().ok()?;
+    let mut hop = TracerouteHop {
+        ttl,
+        ..Default::default()
+    };
+
+    // Three probe columns, each "*" or "<n ms"/"n ms"
+    while hop.rtts.len() < 3 {
+        let token = tokens.next()?;
+        if token == "*" {
+            hop.rtts.push(None);
+        } else {
+            // Sub-millisecond replies are reported as "<1 ms"
+            hop.rtts.push(token.trim_start_matches('<').parse().ok());
+            if tokens.peek() == Some(&"ms") {
+                tokens.next();
+            }
+        }
+    }
+
+    let rest: Vec<&str> = tokens.collect();
+    let rest = rest.join(" ");
+    if rest.starts_with("Request timed out") {
+        return Some(hop);
+    }
+    let (target, note) = match rest.split_once(" reports: ") {
+        Some((target, note)) => (target, note.trim_end_matches('.').to_string()),
+        None => (rest.as_str(), String::new()),
+    };
+    match target.split_once(" [") {
+        Some((hostname, address)) => {
+            hop.hostname = hostname.to_string();
+            hop.address = address.trim_end_matches(']').to_string();
+        }
+        None => hop.address = target.trim().to_string(),
+    }
+    hop.note = note;
+    Some(hop)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_trace_headers() {
+        assert_eq!(
+            parse_trace_header("Tracing route to dns.google [8.8.8.8]").as_deref(),
+            Some("8.8.8.8")
+        );
+        assert_eq!(
+            parse_trace_header("Tracing route to 8.8.8.8 over a maximum of 30 hops").as_deref(),
+            Some("8.8.8.8")
+        );
+        assert_eq!(
+            parse_trace_header("traceroute to 8.8.8.8 (8.8.8.8), 30 hops max, 60 byte packets")
+                .as_deref(),
+            Some("8.8.8.8")
+        );
+        assert_eq!(parse_trace_header("over a maximum of 30 hops:"), None);
+    }
+
+    #[test]
+    fn parses_traceroute_hops() {
+        let hop =
+            parse_traceroute_line(" 1  172.20.0.1 (172.20.0.1)  0.345 ms  0.301 ms  0.289 ms")
+                .unwrap();
+        assert_eq!(hop.ttl, 1);
+        assert_eq!(hop.address, "172.20.0.1");
+        assert_eq!(hop.hostname, "");
+        assert_eq!(hop.rtts, [Some(0.345), Some(0.301), Some(0.289)]);
+        assert_eq!(hop.note, "");
+
+        let hop = parse_traceroute_line(" 3  * * *").unwrap();
+        assert_eq!(hop.ttl, 3);
+        assert_eq!(hop.address, "");
+        assert_eq!(hop.rtts, [None, None, None]);
+    }
+
+    #[test]
+    fn parses_traceroute_annotations() {
+        let hop = parse_traceroute_line(" 4  10.0.0.1 (10.0.0.1)  5.1 ms !H  *  5.2 ms").unwrap();
+        assert_eq!(hop.address, "10.0.0.1");
+        assert_eq!(hop.rtts, [Some(5.1), None, Some(5.2)]);
+        assert_eq!(hop.note, "Host unreachable");
+
+        let hop = parse_traceroute_line(" 4  10.0.0.1 (10.0.0.1)  5.1 ms !Z").unwrap();
+        assert_eq!(hop.note, "!Z");
+    }
+
+    #[test]
+    fn reports_the_first_router_of_a_hop() {
+        let hop = parse_traceroute_line(
+            " 5  a.example (1.2.3.4)  10.1 ms b.example (1.2.3.5)  11.2 ms  10.9 ms",
+        )
+        .unwrap();
+        assert_eq!(hop.address, "1.2.3.4");
+        assert_eq!(hop.hostname, "a.example");
+        assert_eq!(hop.rtts, [Some(10.1), Some(11.2), Some(10.9)]);
+    }
+
+    #[test]
+    fn parses_busybox_hops_without_names() {
+        let hop = parse_traceroute_line(" 2  192.168.1.1  1.234 ms  1.100 ms  1.050 ms").unwrap();
+        assert_eq!(hop.address, "192.168.1.1");
+        assert_eq!(hop.hostname, "");
+        assert_eq!(hop.rtts.len(), 3);
+    }
+
+    #[test]
+    fn skips_traceroute_lines_without_a_ttl() {
+        assert!(parse_traceroute_line("traceroute to 8.8.8.8 (8.8.8.8), 30 hops max").is_none());
+        assert!(parse_traceroute_line("").is_none());
+    }
+
+    #[test]
+    fn parses_tracert_hops() {
+        let hop = parse_tracert_line("  1    <1 ms    <1 ms    <1 ms  172.20.0.1").unwrap();
+        assert_eq!(hop.ttl, 1);
+        assert_eq!(hop.address, "172.20.0.1");
+        assert_eq!(hop.hostname, "");
+        assert_eq!(hop.rtts, [Some(1.0), Some(1.0), Some(1.0)]);
+
+        let hop = parse_tracert_line("  2     1 ms     1 ms     1 ms  router.home [192.168.1.1]")
+            .unwrap();
+        assert_eq!(hop.address, "192.168.1.1");
+        assert_eq!(hop.hostname, "router.home");
+        assert_eq!(hop.rtts, [Some(1.0), Some(1.0), Some(1.0)]);
+    }
+
+    #[test]
+    fn parses_tracert_timeouts_and_reports() {
+        let hop = parse_tracert_line("  3     *        *        *     Request timed out.").unwrap();
+        assert_eq!(hop.ttl, 3);
+        assert_eq!(hop.address, "");
+        assert_eq!(hop.rtts, [None, None, None]);
+        assert_eq!(hop.note, "");
+
+        let hop = parse_tracert_line(
+            "  4    10 ms     *       12 ms  10.0.0.1  reports: Destination host unreachable.",
+        )
+        .unwrap();
+        assert_eq!(hop.address, "10.0.0.1");
+        assert_eq!(hop.rtts, [Some(10.0), None, Some(12.0)]);
+        assert_eq!(hop.note, "Destination host unreachable");
+    }
+
+    #[test]
+    fn skips_tracert_lines_without_a_hop() {
+        assert!(parse_tracert_line("Trace complete.").is_none());
+        assert!(parse_tracert_line("over a maximum of 30 hops:").is_none());
+    }
+}
@@ -0,0 +1,173 @@
+//! Storage of WSLNetMan's own files
+//!
+//! The probe history, collections, forwarding rules, saved port filters and port snapshots are
+//! stored as JSON files in `%LOCALAPPDATA%\wslnetman` (`~/.local/share/wslnetman` elsewhere).
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory of WSLNetMan's own files
+pub fn data_dir() -> PathBuf {
+    std::env::var("LOCALAPPDATA")
+        .map(PathBuf::from)
+        .or_else(|_| {
+            std::env::var("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join("wslnetman")
+}
+
+/// Format a Unix timestamp as "YYYY-MM-DD HH:MM:SS" (UTC)
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Read a JSON file written by [`write_json`]
+pub fn read_json<T: serde::de::DeserializeOwned>(
+    path: &Path,
+) -> Result<T, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    serde_json::from_str(&contents)
+        .map_err(|e| format!("Invalid file {}: {e}", path.display()).into())
+}
+
+/// Load a JSON file written by [`write_json`], starting from the default when there is none yet
+///
+/// A file that cannot be read or parsed is moved aside to `<name>.corrupt-<timestamp>` instead
+/// of being replaced by the next save, so its contents can still be recovered.
+pub fn load_json<T: serde::de::DeserializeOwned + Default>(path: &Path) -> T {
+    match std::fs::metadata(path) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return T::default(),
+        _ => {}
+    }
+    match read_json(path) {
+        Ok(value) => value,
+        Err(e) => {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let backup = sibling(path, &format!("corrupt-{timestamp}"));
+            match std::fs::rename(path, &backup) {
+                Ok(()) => eprintln!("{e}; moved it to {}", backup.display()),
+                Err(rename_error) => {
+                    eprintln!("{e}; could not move it aside ({rename_error})")
+                }
+            }
+            T::default()
+        }
+    }
+}
+
+/// Write a value as pretty JSON, creating the directory when needed
+///
+/// The value is written to a temporary file first and then renamed over `path`, so a crash
+/// while writing never leaves a truncated file behind.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temporary = sibling(path, "tmp");
+    std::fs::write(&temporary, serde_json::to_string_pretty(value)?)
+        .and_then(|()| std::fs::rename(&temporary, path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&temporary);
+            format!("Could not write {}: {e}", path.display()).into()
+        })
+}
+
+/// A file next to `path` with `suffix` appended to its name, e.g. "history.json.tmp"
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".{suffix}"));
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty directory of its own for a test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wslnetman-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_the_default_for_a_missing_file() {
+        let dir = test_dir("missing");
+        let value: Vec<String> = load_json(&dir.join("missing.json"));
+        assert!(value.is_empty());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn moves_a_corrupt_file_aside() {
+        let dir = test_dir("corrupt");
+        let path = dir.join("history.json");
+        std::fs::write(&path, "{ not json").unwrap();
+
+        let value: Vec<String> = load_json(&path);
+
+        assert!(value.is_empty());
+        assert!(!path.exists());
+        let backups: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].starts_with("history.json.corrupt-"));
+        assert_eq!(
+            std::fs::read_to_string(dir.join(&backups[0])).unwrap(),
+            "{ not json"
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn writes_and_loads_values() {
+        let dir = test_dir("roundtrip");
+        let path = dir.join("nested").join("values.json");
+
+        write_json(&path, &vec!["a".to_string()]).unwrap();
+        write_json(&path, &vec!["b".to_string(), "c".to_string()]).unwrap();
+
+        let value: Vec<String> = load_json(&path);
+        assert_eq!(value, ["b", "c"]);
+        // The temporary file was renamed into place
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1_709_251_199), "2024-02-29 23:59:59");
+    }
+}